@@ -1,12 +1,44 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_tables;
+mod m20220101_000002_create_refresh_tokens;
+mod m20220101_000003_add_token_revocation;
+mod m20220101_000004_create_roles;
+mod m20220101_000005_seed_roles;
+mod m20220101_000006_create_login_attempts;
+mod m20220101_000007_create_rate_limits;
+mod m20220101_000008_add_email_verification;
+mod m20220101_000009_create_password_resets;
+mod m20220101_000010_create_mfa;
+mod m20220101_000011_create_user_identities;
+mod m20220101_000012_create_api_keys;
+mod m20220101_000013_create_sessions;
+mod m20220101_000014_add_user_soft_delete;
+mod m20220101_000015_create_audit_events;
+mod m20220101_000016_seed_config_permission;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_tables::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_tables::Migration),
+            Box::new(m20220101_000002_create_refresh_tokens::Migration),
+            Box::new(m20220101_000003_add_token_revocation::Migration),
+            Box::new(m20220101_000004_create_roles::Migration),
+            Box::new(m20220101_000005_seed_roles::Migration),
+            Box::new(m20220101_000006_create_login_attempts::Migration),
+            Box::new(m20220101_000007_create_rate_limits::Migration),
+            Box::new(m20220101_000008_add_email_verification::Migration),
+            Box::new(m20220101_000009_create_password_resets::Migration),
+            Box::new(m20220101_000010_create_mfa::Migration),
+            Box::new(m20220101_000011_create_user_identities::Migration),
+            Box::new(m20220101_000012_create_api_keys::Migration),
+            Box::new(m20220101_000013_create_sessions::Migration),
+            Box::new(m20220101_000014_add_user_soft_delete::Migration),
+            Box::new(m20220101_000015_create_audit_events::Migration),
+            Box::new(m20220101_000016_seed_config_permission::Migration),
+        ]
     }
 }
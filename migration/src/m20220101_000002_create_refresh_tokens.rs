@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(RefreshToken::Table)
+                .col(
+                    ColumnDef::new(RefreshToken::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(RefreshToken::UserId).uuid().not_null())
+                .col(ColumnDef::new(RefreshToken::FamilyId).uuid().not_null())
+                .col(ColumnDef::new(RefreshToken::Device).string_len(255))
+                .col(ColumnDef::new(RefreshToken::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(RefreshToken::UsedAt).timestamp_with_time_zone())
+                .col(ColumnDef::new(RefreshToken::RevokedAt).timestamp_with_time_zone())
+                .col(ColumnDef::new(RefreshToken::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_refresh_token_user_id")
+                        .from(RefreshToken::Table, RefreshToken::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_refresh_token_family_id")
+                .table(RefreshToken::Table)
+                .col(RefreshToken::FamilyId)
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_refresh_token_user_id")
+                .table(RefreshToken::Table)
+                .col(RefreshToken::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RefreshToken::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum RefreshToken {
+    Table,
+    Id,
+    UserId,
+    FamilyId,
+    Device,
+    ExpiresAt,
+    UsedAt,
+    RevokedAt,
+    CreatedAt,
+}
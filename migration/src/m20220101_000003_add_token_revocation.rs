@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(ColumnDef::new(TokenVersion::TokenVersion).integer().not_null().default(0))
+                .to_owned(),
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(RevokedToken::Table)
+                .col(
+                    ColumnDef::new(RevokedToken::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(RevokedToken::UserId).uuid().not_null())
+                .col(ColumnDef::new(RevokedToken::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(RevokedToken::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_revoked_token_user_id")
+                        .from(RevokedToken::Table, RevokedToken::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RevokedToken::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_column(TokenVersion::TokenVersion)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum TokenVersion {
+    TokenVersion,
+}
+
+#[derive(Iden)]
+pub enum RevokedToken {
+    Table,
+    Id,
+    UserId,
+    ExpiresAt,
+    CreatedAt,
+}
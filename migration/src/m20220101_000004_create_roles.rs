@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The `role` table's row type would clash with the enum type of the same name,
+        // which is dropped once users are moved over in the seed migration.
+        manager.get_connection().execute_unprepared(
+            r#"ALTER TYPE role RENAME TO role_enum;"#
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Role::Table)
+                .col(
+                    ColumnDef::new(Role::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Role::Name).string_len(64).not_null().unique_key())
+                .col(ColumnDef::new(Role::CreatedAt).timestamp_with_time_zone().not_null())
+                .to_owned(),
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Permission::Table)
+                .col(
+                    ColumnDef::new(Permission::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Permission::Name).string_len(64).not_null().unique_key())
+                .col(ColumnDef::new(Permission::Description).string_len(255))
+                .to_owned(),
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(RolePermission::Table)
+                .col(ColumnDef::new(RolePermission::RoleId).uuid().not_null())
+                .col(ColumnDef::new(RolePermission::PermissionId).uuid().not_null())
+                .primary_key(
+                    Index::create()
+                        .col(RolePermission::RoleId)
+                        .col(RolePermission::PermissionId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_role_permission_role_id")
+                        .from(RolePermission::Table, RolePermission::RoleId)
+                        .to(Role::Table, Role::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_role_permission_permission_id")
+                        .from(RolePermission::Table, RolePermission::PermissionId)
+                        .to(Permission::Table, Permission::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RolePermission::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Permission::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Role::Table).to_owned()).await?;
+        manager.get_connection().execute_unprepared(
+            r#"ALTER TYPE role_enum RENAME TO role;"#
+        ).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Role {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum Permission {
+    Table,
+    Id,
+    Name,
+    Description,
+}
+
+#[derive(Iden)]
+pub enum RolePermission {
+    Table,
+    RoleId,
+    PermissionId,
+}
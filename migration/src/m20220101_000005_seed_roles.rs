@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let connection = manager.get_connection();
+
+        connection.execute_unprepared(
+            r#"
+            INSERT INTO role (id, name, created_at) VALUES
+                ('00000000-0000-0000-0000-000000000001', 'Admin', NOW()),
+                ('00000000-0000-0000-0000-000000000002', 'User', NOW());
+
+            INSERT INTO permission (id, name, description) VALUES
+                (gen_random_uuid(), 'users:read', 'Read any user'),
+                (gen_random_uuid(), 'users:write', 'Update any user'),
+                (gen_random_uuid(), 'users:delete', 'Delete any user'),
+                (gen_random_uuid(), 'roles:read', 'Read roles and their permissions'),
+                (gen_random_uuid(), 'roles:write', 'Create roles and assign permissions');
+
+            INSERT INTO role_permission (role_id, permission_id)
+                SELECT '00000000-0000-0000-0000-000000000001', id FROM permission;
+            "#
+        ).await?;
+
+        connection.execute_unprepared(
+            r#"
+            ALTER TABLE "user" ADD COLUMN role_id UUID;
+            UPDATE "user" SET role_id = role.id FROM role WHERE role.name = "user".role::text;
+            ALTER TABLE "user" ALTER COLUMN role_id SET NOT NULL;
+            ALTER TABLE "user" ADD CONSTRAINT fk_user_role_id FOREIGN KEY (role_id) REFERENCES role (id);
+            ALTER TABLE "user" DROP COLUMN role;
+            DROP TYPE role_enum;
+            "#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let connection = manager.get_connection();
+
+        // Users on roles that did not exist before this migration fall back to `User`.
+        connection.execute_unprepared(
+            r#"
+            CREATE TYPE role_enum AS ENUM ('Admin', 'User');
+            ALTER TABLE "user" ADD COLUMN role role_enum;
+            UPDATE "user" SET role = CASE WHEN role.name = 'Admin' THEN 'Admin' ELSE 'User' END::role_enum
+                FROM role WHERE role.id = "user".role_id;
+            ALTER TABLE "user" ALTER COLUMN role SET NOT NULL;
+            ALTER TABLE "user" DROP COLUMN role_id;
+            "#
+        ).await?;
+
+        connection.execute_unprepared(
+            r#"
+            DELETE FROM role_permission;
+            DELETE FROM permission;
+            DELETE FROM role;
+            "#
+        ).await?;
+
+        Ok(())
+    }
+}
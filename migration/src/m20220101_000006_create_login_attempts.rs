@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(LoginAttempt::Table)
+                .col(
+                    ColumnDef::new(LoginAttempt::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(LoginAttempt::Scope).string_len(16).not_null())
+                .col(ColumnDef::new(LoginAttempt::Key).string_len(255).not_null())
+                .col(ColumnDef::new(LoginAttempt::Failures).integer().not_null().default(0))
+                .col(ColumnDef::new(LoginAttempt::LockedUntil).timestamp_with_time_zone())
+                .col(ColumnDef::new(LoginAttempt::UpdatedAt).timestamp_with_time_zone().not_null())
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_login_attempt_scope_key")
+                .table(LoginAttempt::Table)
+                .col(LoginAttempt::Scope)
+                .col(LoginAttempt::Key)
+                .unique()
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(LoginAttempt::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum LoginAttempt {
+    Table,
+    Id,
+    Scope,
+    Key,
+    Failures,
+    LockedUntil,
+    UpdatedAt,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(RateLimit::Table)
+                .col(ColumnDef::new(RateLimit::Key).string_len(255).not_null())
+                .col(ColumnDef::new(RateLimit::WindowStart).big_integer().not_null())
+                .col(ColumnDef::new(RateLimit::Hits).big_integer().not_null())
+                .primary_key(
+                    Index::create()
+                        .col(RateLimit::Key)
+                        .col(RateLimit::WindowStart),
+                )
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RateLimit::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum RateLimit {
+    Table,
+    Key,
+    WindowStart,
+    Hits,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(ColumnDef::new(UserEmail::Email).string_len(255).null().unique_key())
+                .add_column(ColumnDef::new(UserEmail::EmailVerifiedAt).timestamp_with_time_zone().null())
+                .to_owned(),
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(EmailVerification::Table)
+                .col(
+                    ColumnDef::new(EmailVerification::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(EmailVerification::UserId).uuid().not_null())
+                .col(ColumnDef::new(EmailVerification::Email).string_len(255).not_null())
+                .col(ColumnDef::new(EmailVerification::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(EmailVerification::UsedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(EmailVerification::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_email_verification_user_id")
+                        .from(EmailVerification::Table, EmailVerification::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_email_verification_user_id")
+                .table(EmailVerification::Table)
+                .col(EmailVerification::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(EmailVerification::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_column(UserEmail::Email)
+                .drop_column(UserEmail::EmailVerifiedAt)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum UserEmail {
+    Email,
+    EmailVerifiedAt,
+}
+
+#[derive(Iden)]
+pub enum EmailVerification {
+    Table,
+    Id,
+    UserId,
+    Email,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(PasswordReset::Table)
+                .col(
+                    ColumnDef::new(PasswordReset::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(PasswordReset::UserId).uuid().not_null())
+                .col(ColumnDef::new(PasswordReset::TokenHash).string_len(64).not_null().unique_key())
+                .col(ColumnDef::new(PasswordReset::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(PasswordReset::UsedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(PasswordReset::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_password_reset_user_id")
+                        .from(PasswordReset::Table, PasswordReset::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_password_reset_user_id")
+                .table(PasswordReset::Table)
+                .col(PasswordReset::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(PasswordReset::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum PasswordReset {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(MfaSecret::Table)
+                .col(
+                    ColumnDef::new(MfaSecret::UserId)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(MfaSecret::Secret).string_len(255).not_null())
+                .col(ColumnDef::new(MfaSecret::LastUsedStep).big_integer().null())
+                .col(ColumnDef::new(MfaSecret::ConfirmedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(MfaSecret::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_mfa_secret_user_id")
+                        .from(MfaSecret::Table, MfaSecret::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_table(
+            Table::create()
+                .table(MfaRecoveryCode::Table)
+                .col(
+                    ColumnDef::new(MfaRecoveryCode::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(MfaRecoveryCode::UserId).uuid().not_null())
+                .col(ColumnDef::new(MfaRecoveryCode::CodeHash).string_len(255).not_null())
+                .col(ColumnDef::new(MfaRecoveryCode::UsedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(MfaRecoveryCode::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_mfa_recovery_code_user_id")
+                        .from(MfaRecoveryCode::Table, MfaRecoveryCode::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_mfa_recovery_code_user_id")
+                .table(MfaRecoveryCode::Table)
+                .col(MfaRecoveryCode::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MfaRecoveryCode::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(MfaSecret::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum MfaSecret {
+    Table,
+    UserId,
+    Secret,
+    LastUsedStep,
+    ConfirmedAt,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum MfaRecoveryCode {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(UserIdentity::Table)
+                .col(
+                    ColumnDef::new(UserIdentity::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(UserIdentity::UserId).uuid().not_null())
+                .col(ColumnDef::new(UserIdentity::Provider).string_len(64).not_null())
+                .col(ColumnDef::new(UserIdentity::Subject).string_len(255).not_null())
+                .col(ColumnDef::new(UserIdentity::Email).string_len(255).null())
+                .col(ColumnDef::new(UserIdentity::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_user_identity_user_id")
+                        .from(UserIdentity::Table, UserIdentity::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_user_identity_provider_subject")
+                .table(UserIdentity::Table)
+                .col(UserIdentity::Provider)
+                .col(UserIdentity::Subject)
+                .unique()
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_user_identity_user_id")
+                .table(UserIdentity::Table)
+                .col(UserIdentity::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(UserIdentity::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum UserIdentity {
+    Table,
+    Id,
+    UserId,
+    Provider,
+    Subject,
+    Email,
+    CreatedAt,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(ApiKey::Table)
+                .col(
+                    ColumnDef::new(ApiKey::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ApiKey::UserId).uuid().not_null())
+                .col(ColumnDef::new(ApiKey::Name).string_len(128).not_null())
+                .col(ColumnDef::new(ApiKey::Prefix).string_len(16).not_null().unique_key())
+                .col(ColumnDef::new(ApiKey::KeyHash).string_len(64).not_null())
+                .col(ColumnDef::new(ApiKey::Scopes).text().not_null())
+                .col(ColumnDef::new(ApiKey::ExpiresAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(ApiKey::LastUsedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(ApiKey::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_api_key_user_id")
+                        .from(ApiKey::Table, ApiKey::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_api_key_user_id")
+                .table(ApiKey::Table)
+                .col(ApiKey::UserId)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ApiKey::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum ApiKey {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Prefix,
+    KeyHash,
+    Scopes,
+    ExpiresAt,
+    LastUsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Session::Table)
+                .col(
+                    ColumnDef::new(Session::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Session::UserId).uuid().not_null())
+                .col(ColumnDef::new(Session::Device).string_len(255).null())
+                .col(ColumnDef::new(Session::Ip).string_len(45).null())
+                .col(ColumnDef::new(Session::TokenVersion).integer().not_null())
+                .col(ColumnDef::new(Session::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(Session::LastUsedAt).timestamp_with_time_zone().not_null())
+                .col(ColumnDef::new(Session::RevokedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(Session::CreatedAt).timestamp_with_time_zone().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_session_user_id")
+                        .from(Session::Table, Session::UserId)
+                        .to(User::Table, User::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_session_user_id")
+                .table(Session::Table)
+                .col(Session::UserId)
+                .to_owned(),
+        ).await?;
+
+        // Refresh token families that can still be refreshed become sessions.
+        manager.get_connection().execute_unprepared(
+            r#"
+            INSERT INTO session (id, user_id, device, token_version, expires_at, last_used_at, created_at)
+                SELECT refresh_token.family_id, refresh_token.user_id, MAX(refresh_token.device), "user".token_version,
+                       MAX(refresh_token.expires_at), MAX(refresh_token.created_at), MIN(refresh_token.created_at)
+                FROM refresh_token JOIN "user" ON "user".id = refresh_token.user_id
+                WHERE refresh_token.used_at IS NULL AND refresh_token.revoked_at IS NULL AND refresh_token.expires_at > NOW()
+                GROUP BY refresh_token.family_id, refresh_token.user_id, "user".token_version;
+            "#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Session::Table).to_owned()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Session {
+    Table,
+    Id,
+    UserId,
+    Device,
+    Ip,
+    TokenVersion,
+    ExpiresAt,
+    LastUsedAt,
+    RevokedAt,
+    CreatedAt,
+}
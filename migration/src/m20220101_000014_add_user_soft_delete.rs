@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+use crate::m20220101_000001_create_tables::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(ColumnDef::new(SoftDelete::DeletedAt).timestamp_with_time_zone().null())
+                .to_owned(),
+        ).await?;
+
+        // Deleted users keep their login and email until they are purged,
+        // so uniqueness only applies to live users.
+        manager.get_connection().execute_unprepared(
+            r#"
+            DROP INDEX idx_lower_login;
+            CREATE UNIQUE INDEX idx_lower_login ON "user" (LOWER(login)) WHERE deleted_at IS NULL;
+            ALTER TABLE "user" DROP CONSTRAINT user_email_key;
+            CREATE UNIQUE INDEX idx_user_email ON "user" (email) WHERE deleted_at IS NULL;
+            CREATE INDEX idx_user_deleted_at ON "user" (deleted_at) WHERE deleted_at IS NOT NULL;
+            "#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.get_connection().execute_unprepared(
+            r#"
+            DELETE FROM "user" WHERE deleted_at IS NOT NULL;
+            DROP INDEX idx_user_deleted_at;
+            DROP INDEX idx_user_email;
+            ALTER TABLE "user" ADD CONSTRAINT user_email_key UNIQUE (email);
+            DROP INDEX idx_lower_login;
+            CREATE UNIQUE INDEX idx_lower_login ON "user" (LOWER(login));
+            "#
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(User::Table)
+                .drop_column(SoftDelete::DeletedAt)
+                .to_owned(),
+        ).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum SoftDelete {
+    DeletedAt,
+}
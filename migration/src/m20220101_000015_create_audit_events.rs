@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_query::ColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Actor and target are kept without foreign keys, so events outlive purged users.
+        manager.create_table(
+            Table::create()
+                .table(AuditEvent::Table)
+                .col(
+                    ColumnDef::new(AuditEvent::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(AuditEvent::ActorId).uuid().null())
+                .col(ColumnDef::new(AuditEvent::Action).string_len(64).not_null())
+                .col(ColumnDef::new(AuditEvent::TargetId).uuid().null())
+                .col(ColumnDef::new(AuditEvent::Changes).json_binary().null())
+                .col(ColumnDef::new(AuditEvent::Ip).string_len(45).null())
+                .col(ColumnDef::new(AuditEvent::UserAgent).string_len(255).null())
+                .col(ColumnDef::new(AuditEvent::RequestId).string_len(64).null())
+                .col(ColumnDef::new(AuditEvent::CreatedAt).timestamp_with_time_zone().not_null())
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_audit_event_created_at")
+                .table(AuditEvent::Table)
+                .col(AuditEvent::CreatedAt)
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_audit_event_actor_id")
+                .table(AuditEvent::Table)
+                .col(AuditEvent::ActorId)
+                .to_owned(),
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .name("idx_audit_event_target_id")
+                .table(AuditEvent::Table)
+                .col(AuditEvent::TargetId)
+                .to_owned(),
+        ).await?;
+
+        let connection = manager.get_connection();
+
+        connection.execute_unprepared(
+            r#"
+            CREATE FUNCTION audit_event_append_only() RETURNS trigger AS $$
+            BEGIN
+                RAISE EXCEPTION 'audit_event is append-only';
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER audit_event_append_only
+                BEFORE UPDATE OR DELETE ON audit_event
+                FOR EACH ROW EXECUTE FUNCTION audit_event_append_only();
+            "#
+        ).await?;
+
+        connection.execute_unprepared(
+            r#"
+            INSERT INTO permission (id, name, description) VALUES
+                (gen_random_uuid(), 'audit:read', 'Read the audit log');
+
+            INSERT INTO role_permission (role_id, permission_id)
+                SELECT '00000000-0000-0000-0000-000000000001', id FROM permission WHERE name = 'audit:read';
+            "#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let connection = manager.get_connection();
+
+        connection.execute_unprepared(
+            r#"
+            DELETE FROM role_permission WHERE permission_id IN (SELECT id FROM permission WHERE name = 'audit:read');
+            DELETE FROM permission WHERE name = 'audit:read';
+            "#
+        ).await?;
+
+        manager.drop_table(Table::drop().table(AuditEvent::Table).to_owned()).await?;
+
+        connection.execute_unprepared(r#"DROP FUNCTION audit_event_append_only;"#).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum AuditEvent {
+    Table,
+    Id,
+    ActorId,
+    Action,
+    TargetId,
+    Changes,
+    Ip,
+    UserAgent,
+    RequestId,
+    CreatedAt,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let connection = manager.get_connection();
+
+        connection.execute_unprepared(
+            r#"
+            INSERT INTO permission (id, name, description) VALUES
+                (gen_random_uuid(), 'config:read', 'Read the effective configuration');
+
+            INSERT INTO role_permission (role_id, permission_id)
+                SELECT '00000000-0000-0000-0000-000000000001', id FROM permission WHERE name = 'config:read';
+            "#
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let connection = manager.get_connection();
+
+        connection.execute_unprepared(
+            r#"
+            DELETE FROM role_permission WHERE permission_id IN (SELECT id FROM permission WHERE name = 'config:read');
+            DELETE FROM permission WHERE name = 'config:read';
+            "#
+        ).await?;
+
+        Ok(())
+    }
+}
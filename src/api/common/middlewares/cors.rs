@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use log::warn;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer, ExposeHeaders};
+
+use crate::core::config::{CorsConfig, CorsList};
+
+
+/// Builds the cross-origin policy. `CorsConfig` validation already rejected the combinations
+/// browsers refuse, so the layer can be built without checks.
+pub fn cors(config: &CorsConfig) -> CorsLayer {
+    let allow_origin = match config.origins() {
+        CorsList::Any => AllowOrigin::from(Any),
+        CorsList::Only(origins) if origins.is_empty() => {
+            warn!("No CORS origins configured, browsers only allow same-origin requests");
+            AllowOrigin::list([])
+        },
+        CorsList::Only(origins) => AllowOrigin::predicate(move |origin, _| origins.iter().any(|allowed| allowed.matches(origin))),
+    };
+    let allow_methods = match config.methods() {
+        CorsList::Any => AllowMethods::from(Any),
+        CorsList::Only(methods) => AllowMethods::list(methods),
+    };
+    let allow_headers = match config.allowed_headers() {
+        CorsList::Any => AllowHeaders::from(Any),
+        CorsList::Only(headers) => AllowHeaders::list(headers),
+    };
+    let expose_headers = match config.exposed_headers() {
+        CorsList::Any => ExposeHeaders::from(Any),
+        CorsList::Only(headers) => ExposeHeaders::list(headers),
+    };
+
+    let layer = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(allow_methods)
+        .allow_headers(allow_headers)
+        .expose_headers(expose_headers)
+        .allow_credentials(config.allow_credentials);
+
+    match config.max_age_seconds {
+        0 => layer,
+        seconds => layer.max_age(Duration::from_secs(seconds)),
+    }
+}
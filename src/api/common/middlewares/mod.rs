@@ -1,3 +1,7 @@
 pub mod process_time;
 pub mod setup;
 pub mod error;
+pub mod rate_limit;
+pub mod cors;
+
+pub mod request_id;
\ No newline at end of file
@@ -0,0 +1,303 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::user::User;
+use crate::core::config::{RateLimitConfig, RateLimitGroupConfig};
+use crate::services::gateway::get_gateway;
+
+const MEMORY_STORE_MAX_KEYS: usize = 10_000;
+
+
+/// What the requests of a route group are counted by.
+#[derive(Debug, Clone, Copy)]
+pub enum RateLimitKey {
+    /// Client IP address.
+    Ip,
+    /// Authenticated user id, falling back to the client IP. Needs the `auth` middleware to run first.
+    User,
+    /// Matched route, shared by all clients.
+    Route,
+}
+
+impl FromStr for RateLimitKey {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ip" => Ok(RateLimitKey::Ip),
+            "user" => Ok(RateLimitKey::User),
+            "route" => Ok(RateLimitKey::Route),
+            _ => Err(anyhow::anyhow!("Unknown rate limit key: {value}")),
+        }
+    }
+}
+
+/// Storage for the fixed-window counters the sliding window is estimated from.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Counts a hit for `key` in the window starting at `window_start` and returns
+    /// the hits of the previous and the current window.
+    async fn hit(&self, key: String, window_start: i64, window: i64) -> Result<(u64, u64), AppError>;
+
+    /// Deletes the windows that started before `window_start` and returns how many there were.
+    async fn delete_before(&self, window_start: i64) -> Result<u64, AppError>;
+}
+
+/// Window start, previous and current hits of each key, and the keys in the order they were added.
+#[derive(Default)]
+struct Counters {
+    windows: HashMap<String, (i64, u64, u64)>,
+    order: VecDeque<String>,
+}
+
+/// Per-instance counters. Every instance enforces the limits on its own. Once the store holds
+/// `MEMORY_STORE_MAX_KEYS` keys, a new key evicts the oldest one.
+#[derive(Default)]
+pub struct MemoryStore {
+    counters: Mutex<Counters>,
+}
+
+impl MemoryStore {
+    fn counters(&self) -> Result<MutexGuard<'_, Counters>, AppError> {
+        self.counters.lock().map_err(|_| {
+            AppError::InternalServerError(AppErrorMessage { message: "Rate limit store is unavailable".into(), details: None })
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: String, window_start: i64, window: i64) -> Result<(u64, u64), AppError> {
+        let mut counters = self.counters()?;
+        let Counters { windows, order } = &mut *counters;
+
+        if !windows.contains_key(&key) {
+            if windows.len() >= MEMORY_STORE_MAX_KEYS {
+                if let Some(oldest) = order.pop_front() {
+                    windows.remove(&oldest);
+                }
+            }
+            order.push_back(key.clone());
+        }
+        let (start, previous, current) = windows.entry(key).or_insert((window_start, 0, 0));
+
+        if *start != window_start {
+            *previous = if *start == window_start - window { *current } else { 0 };
+            *current = 0;
+            *start = window_start;
+        }
+        *current += 1;
+
+        Ok((*previous, *current))
+    }
+
+    async fn delete_before(&self, window_start: i64) -> Result<u64, AppError> {
+        let mut counters = self.counters()?;
+        let Counters { windows, order } = &mut *counters;
+
+        let before = windows.len();
+        windows.retain(|_, (start, _, _)| *start >= window_start);
+        order.retain(|key| windows.contains_key(key));
+
+        Ok((before - windows.len()) as u64)
+    }
+}
+
+/// Counters in the `rate_limit` table, shared by all instances using the same database.
+pub struct PostgresStore {
+    connection: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresStore {
+    async fn hit(&self, key: String, window_start: i64, window: i64) -> Result<(u64, u64), AppError> {
+        get_gateway(&*self.connection)
+            .rate_limit()
+            .hit(key, window_start, window)
+            .await
+    }
+
+    async fn delete_before(&self, window_start: i64) -> Result<u64, AppError> {
+        get_gateway(&*self.connection)
+            .rate_limit()
+            .delete_before(window_start)
+            .await
+    }
+}
+
+pub fn get_rate_limit_store(config: &RateLimitConfig, connection: Arc<DatabaseConnection>) -> Result<Arc<dyn RateLimitStore>, anyhow::Error> {
+    match config.store.as_ref() {
+        "memory" => Ok(Arc::new(MemoryStore::default())),
+        "postgres" => Ok(Arc::new(PostgresStore { connection })),
+        store => anyhow::bail!("RATE_LIMIT_STORE must be memory or postgres, got {store}"),
+    }
+}
+
+/// Limits of one route group. Each group counts its requests separately.
+pub struct RateLimiter {
+    name: &'static str,
+    enabled: bool,
+    requests: u64,
+    window: i64,
+    key: RateLimitKey,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(
+        name: &'static str, 
+        config: &RateLimitConfig, 
+        group: &RateLimitGroupConfig, 
+        store: Arc<dyn RateLimitStore>
+    ) -> Result<Arc<Self>, anyhow::Error> {
+        Ok(Arc::new(Self {
+            name,
+            enabled: config.enabled,
+            requests: group.requests,
+            window: group.window_seconds.max(1),
+            key: group.key.parse()?,
+            store,
+        }))
+    }
+
+    fn key(&self, request: &Request) -> String {
+        let ip = || {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| format!("ip:{}", addr.ip()))
+                .unwrap_or_else(|| "ip:unknown".into())
+        };
+
+        let key = match self.key {
+            RateLimitKey::Ip => ip(),
+            RateLimitKey::User => request
+                .extensions()
+                .get::<User>()
+                .map(|user| format!("user:{}", user.id))
+                .unwrap_or_else(ip),
+            RateLimitKey::Route => {
+                let path = request
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map(|path| path.as_str())
+                    .unwrap_or_else(|| request.uri().path());
+                format!("route:{} {}", request.method(), path)
+            }
+        };
+
+        format!("{}:{}", self.name, key)
+    }
+}
+
+/// Sliding-window rate limiter. The hits of the previous window are weighted by how much of it
+/// still overlaps the sliding window and added to the hits of the current one.
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if !limiter.enabled {
+        return Ok(next.run(request).await);
+    }
+
+    let now = Utc::now().timestamp();
+    let window_start = now - now.rem_euclid(limiter.window);
+    let elapsed = now - window_start;
+
+    let (previous, current) = limiter.store
+        .hit(limiter.key(&request), window_start, limiter.window)
+        .await?;
+
+    let overlap = (limiter.window - elapsed) as f64 / limiter.window as f64;
+    let hits = (previous as f64 * overlap) as u64 + current;
+    let reset = limiter.window - elapsed;
+
+    let mut response = if hits > limiter.requests {
+        AppError::TooManyRequestsError(
+            AppErrorMessage {
+                message: "Rate limit exceeded".into(),
+                details: json!({ "retry_after": reset }).into()
+            }
+        ).into_response()
+    } else {
+        next.run(request).await
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("X-RateLimit-Limit", HeaderValue::from(limiter.requests));
+    headers.insert("X-RateLimit-Remaining", HeaderValue::from(limiter.requests.saturating_sub(hits)));
+    headers.insert("X-RateLimit-Reset", HeaderValue::from(reset));
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: i64 = 60;
+
+    async fn hit(store: &MemoryStore, key: &str, window_start: i64) -> (u64, u64) {
+        store.hit(key.into(), window_start, WINDOW).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn memory_store_carries_the_current_window_over_to_the_next() {
+        let store = MemoryStore::default();
+
+        assert_eq!(hit(&store, "api:ip:1", 0).await, (0, 1));
+        assert_eq!(hit(&store, "api:ip:1", 0).await, (0, 2));
+        assert_eq!(hit(&store, "api:ip:2", 0).await, (0, 1));
+
+        assert_eq!(hit(&store, "api:ip:1", WINDOW).await, (2, 1));
+        assert_eq!(hit(&store, "api:ip:1", WINDOW).await, (2, 2));
+
+        // A window without hits in between leaves nothing to carry over
+        assert_eq!(hit(&store, "api:ip:2", 2 * WINDOW).await, (0, 1));
+        assert_eq!(hit(&store, "api:ip:1", 4 * WINDOW).await, (0, 1));
+    }
+
+    #[tokio::test]
+    async fn memory_store_evicts_the_oldest_key_when_full() {
+        let store = MemoryStore::default();
+        for index in 0..MEMORY_STORE_MAX_KEYS {
+            hit(&store, &format!("api:ip:{index}"), 0).await;
+        }
+        hit(&store, "api:ip:0", 0).await;
+
+        assert_eq!(hit(&store, "api:ip:new", 0).await, (0, 1));
+        let counters = store.counters.lock().unwrap();
+        assert_eq!(counters.windows.len(), MEMORY_STORE_MAX_KEYS);
+        assert_eq!(counters.order.len(), MEMORY_STORE_MAX_KEYS);
+        assert!(!counters.windows.contains_key("api:ip:0"));
+        assert!(counters.windows.contains_key("api:ip:1"));
+    }
+
+    #[tokio::test]
+    async fn memory_store_deletes_expired_windows() {
+        let store = MemoryStore::default();
+        hit(&store, "api:ip:1", 0).await;
+        hit(&store, "api:ip:2", 0).await;
+        hit(&store, "api:ip:2", WINDOW).await;
+
+        assert_eq!(store.delete_before(WINDOW).await.unwrap(), 1);
+        let counters = store.counters.lock().unwrap();
+        assert_eq!(counters.order, ["api:ip:2"]);
+        assert!(counters.windows.contains_key("api:ip:2"));
+    }
+}
@@ -3,7 +3,7 @@ use log::info;
 use axum::{middleware, Router};
 
 
-use crate::api::common::middlewares::{error::error_handler, process_time::process_time};
+use crate::api::common::middlewares::{error::error_handler, process_time::process_time, request_id::request_id};
 
 
 pub fn setup_middlewares(router: Router) -> Router {
@@ -11,4 +11,5 @@ pub fn setup_middlewares(router: Router) -> Router {
     router
         .layer(middleware::from_fn(process_time))
         .layer(middleware::from_fn(error_handler))
+        .layer(middleware::from_fn(request_id))
 }
\ No newline at end of file
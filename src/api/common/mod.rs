@@ -1,2 +1,4 @@
 pub mod middlewares;
-pub mod helpers;
\ No newline at end of file
+pub mod helpers;
+pub mod tls;
+pub mod shutdown;
\ No newline at end of file
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use axum_server::Handle;
+use log::{info, warn};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_util::sync::CancellationToken;
+
+
+/// Resolves on SIGTERM or SIGINT.
+#[cfg(unix)]
+async fn terminated() {
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+
+    tokio::select! {
+        _ = terminate.recv() => info!("Received SIGTERM, shutting down"),
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT, shutting down"),
+    }
+}
+
+/// Resolves on Ctrl-C, the only termination request there is without Unix signals.
+#[cfg(not(unix))]
+async fn terminated() {
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => info!("Received Ctrl-C, shutting down"),
+        Err(error) => {
+            warn!("Failed to listen for Ctrl-C, shutting down on request is not possible: {}", error);
+            std::future::pending().await
+        },
+    }
+}
+
+/// On SIGTERM or SIGINT cancels `shutdown`, which fails the healthcheck and stops background tasks.
+/// After `delay` the listeners behind `handle` stop accepting, open connections get `timeout`
+/// to finish before they are closed. A second signal closes them right away.
+pub fn spawn_shutdown(shutdown: CancellationToken, handle: Handle, delay: Duration, timeout: Duration) {
+    tokio::spawn(async move {
+        terminated().await;
+        shutdown.cancel();
+
+        if !delay.is_zero() {
+            info!("Failing the healthcheck for {}s before draining", delay.as_secs());
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {},
+                _ = terminated() => {},
+            }
+        }
+
+        info!("Draining connections for up to {}s", timeout.as_secs());
+        handle.graceful_shutdown(Some(timeout));
+
+        terminated().await;
+        warn!("Closing open connections");
+        handle.shutdown();
+    });
+}
@@ -0,0 +1,229 @@
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use axum::http::Uri;
+use axum::response::Redirect;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
+use log::{info, warn};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use thiserror::Error;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio_util::sync::CancellationToken;
+
+use crate::core::config::ServerConfig;
+
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("Failed to read {path}: {reason}")]
+    Read { path: String, reason: String },
+    #[error("{0} holds no certificate")]
+    NoCertificate(String),
+    #[error("Invalid client CA {path}: {reason}")]
+    ClientCa { path: String, reason: String },
+    #[error("Invalid certificate or key: {0}")]
+    Rustls(#[from] rustls::Error),
+}
+
+/// The PEM files HTTPS is served with.
+#[derive(Debug, Clone)]
+pub struct TlsFiles {
+    cert: Box<str>,
+    key: Box<str>,
+    client_ca: Option<Box<str>>,
+}
+
+impl TlsFiles {
+    /// `None` when the server only speaks plain HTTP.
+    pub fn from_config(config: &ServerConfig) -> Option<Self> {
+        Some(Self {
+            cert: config.tls_cert_path()?.into(),
+            key: config.tls_key_path()?.into(),
+            client_ca: config.tls_client_ca_path().map(Into::into),
+        })
+    }
+
+    pub fn load(&self) -> Result<Arc<rustls::ServerConfig>, TlsError> {
+        let certs = certificates(&self.cert)?;
+        let key = PrivateKeyDer::from_pem_file(&*self.key).map_err(|error| read_error(&self.key, error))?;
+
+        let builder = rustls::ServerConfig::builder();
+        let builder = match &self.client_ca {
+            Some(path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in certificates(path)? {
+                    roots.add(cert).map_err(|error| TlsError::ClientCa { path: path.to_string(), reason: error.to_string() })?;
+                }
+                let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                    .build()
+                    .map_err(|error| TlsError::ClientCa { path: path.to_string(), reason: error.to_string() })?;
+                builder.with_client_cert_verifier(verifier)
+            },
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut config = builder.with_single_cert(certs, key)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+
+    /// Modification times, following symlinks like the ones Kubernetes swaps on secret updates.
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        [Some(&self.cert), Some(&self.key), self.client_ca.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|path| fs::metadata(&**path).and_then(|metadata| metadata.modified()).ok())
+            .collect()
+    }
+}
+
+fn certificates(path: &str) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|error| read_error(path, error))?;
+
+    match certs.is_empty() {
+        true => Err(TlsError::NoCertificate(path.into())),
+        false => Ok(certs),
+    }
+}
+
+fn read_error(path: &str, error: impl ToString) -> TlsError {
+    TlsError::Read { path: path.into(), reason: error.to_string() }
+}
+
+/// Reloads the certificates on SIGHUP, where there are signals, and every `interval_seconds` when
+/// their files changed, until shutdown. Open connections keep the certificates they were accepted
+/// with, a broken reload keeps the current ones and is tried again on the next check.
+pub fn spawn_reload(files: TlsFiles, rustls: RustlsConfig, interval_seconds: u64, shutdown: CancellationToken) {
+    let mut hangup = Hangup::listen();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+        let mut loaded = files.modified();
+
+        loop {
+            tokio::select! {
+                Some(()) = hangup.recv() => info!("Received SIGHUP, reloading TLS certificates"),
+                _ = interval.tick(), if interval_seconds > 0 => {
+                    if files.modified() == loaded {
+                        continue;
+                    }
+                    info!("TLS files changed, reloading certificates");
+                },
+                _ = shutdown.cancelled() => break,
+            }
+            reload(&files, &rustls, &mut loaded);
+        }
+    });
+}
+
+/// SIGHUP listener, never receiving where there are no signals or listening failed.
+#[cfg(unix)]
+struct Hangup(Option<Signal>);
+
+#[cfg(unix)]
+impl Hangup {
+    fn listen() -> Self {
+        let signal = signal(SignalKind::hangup())
+            .map_err(|error| warn!("Failed to listen for SIGHUP, certificates are only reloaded on change: {}", error))
+            .ok();
+        Self(signal)
+    }
+
+    async fn recv(&mut self) -> Option<()> {
+        match &mut self.0 {
+            Some(signal) => signal.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Hangup;
+
+#[cfg(not(unix))]
+impl Hangup {
+    fn listen() -> Self {
+        Self
+    }
+
+    async fn recv(&mut self) -> Option<()> {
+        std::future::pending().await
+    }
+}
+
+fn reload(files: &TlsFiles, rustls: &RustlsConfig, loaded: &mut Vec<Option<SystemTime>>) {
+    let modified = files.modified();
+
+    match files.load() {
+        Ok(config) => {
+            rustls.reload_from_config(config);
+            *loaded = modified;
+            info!("TLS certificates reloaded");
+        },
+        Err(error) => warn!("Failed to reload TLS certificates, keeping the current ones: {}", error),
+    }
+}
+
+/// Answers every plain HTTP request with a permanent redirect to the same path at `public_url`.
+/// Shuts down with `handle`.
+pub fn spawn_redirect(host: &str, port: u16, public_url: &str, handle: Handle) -> std::io::Result<()> {
+    let listener = std::net::TcpListener::bind(format!("{host}:{port}"))?;
+    listener.set_nonblocking(true)?;
+    let router = redirect_router(public_url);
+
+    info!("Redirecting HTTP on port {} to {}", port, public_url);
+    tokio::spawn(async move {
+        if let Err(error) = axum_server::from_tcp(listener).handle(handle).serve(router.into_make_service()).await {
+            warn!("HTTP redirect listener stopped: {}", error);
+        }
+    });
+    Ok(())
+}
+
+/// Ignores the Host and forwarding headers, they would let anyone turn the redirect to another site.
+fn redirect_router(public_url: &str) -> Router {
+    let public_url: Arc<str> = public_url.trim_end_matches('/').into();
+
+    Router::new().fallback(move |uri: Uri| async move {
+        let path = uri.path_and_query().map_or("/", |path| path.as_str());
+        Redirect::permanent(&format!("{public_url}{path}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::Service;
+
+    use super::*;
+
+    async fn location(request: Request<Body>) -> String {
+        // Routers are always ready, no need to poll first
+        let response = redirect_router("https://api.example.com/").call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        response.headers()[header::LOCATION].to_str().unwrap().to_owned()
+    }
+
+    #[tokio::test]
+    async fn redirects_to_the_public_url_whatever_the_host() {
+        let request = Request::get("/api/v1/users?page=2")
+            .header(header::HOST, "evil.example.net")
+            .header("x-forwarded-host", "evil.example.net")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(location(request).await, "https://api.example.com/api/v1/users?page=2");
+
+        let request = Request::get("http://evil.example.net//evil.example.net/").body(Body::empty()).unwrap();
+        assert_eq!(location(request).await, "https://api.example.com//evil.example.net/");
+    }
+}
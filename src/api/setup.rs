@@ -7,7 +7,7 @@ use crate::api::v1::doc::ApiDoc;
 use super::common::middlewares::setup::setup_middlewares;
 
 
-pub async fn create_general_router(routers: Vec<Router>) -> Router {
+pub async fn create_general_router(routers: Vec<Router>, root_routers: Vec<Router>) -> Router {
     let mut sub_router = Router::new();
 
     for router in routers {
@@ -18,6 +18,10 @@ pub async fn create_general_router(routers: Vec<Router>) -> Router {
         .nest("/api/", sub_router)
         .merge(SwaggerUi::new("/api/docs").url("/api-docs/openapi.json", ApiDoc::openapi()));
 
+    for router in root_routers {
+        main_router = main_router.merge(router)
+    }
+
     main_router = setup_middlewares(main_router);
 
     main_router
@@ -1,14 +1,24 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use log::info;
+use log::{info, warn};
 
 use sea_orm::DatabaseConnection;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
 
+use crate::api::common::middlewares::rate_limit::{get_rate_limit_store, RateLimitStore};
 use crate::database::connection::{connection_options, make_connection};
 use crate::core::config::Config;
+use crate::services::gateway::get_gateway;
+use crate::services::mailer::{get_mailer, Mailer};
 use crate::services::security::{
+    cache::{get_token_cache, TokenCache},
+    cipher::{get_cipher, SecretCipher},
     hash::{get_argon2_default, Argon2Hasher},
     jwt::{get_jwt, JWT},
+    oidc::{get_oidc, OidcClient},
 };
 use migration::{Migrator, MigratorTrait};
 
@@ -17,21 +27,101 @@ pub struct AppState {
     pub hasher: Arc<Argon2Hasher>,
     pub config: Config,
     pub jwt: Arc<JWT>,
+    pub token_cache: Arc<TokenCache>,
+    pub rate_limit_store: Arc<dyn RateLimitStore>,
+    pub mailer: Arc<dyn Mailer>,
+    pub cipher: Arc<SecretCipher>,
+    pub oidc: Arc<OidcClient>,
+    /// Cancelled on SIGTERM or SIGINT, background tasks stop and the healthcheck fails
+    pub shutdown: CancellationToken,
+    /// Work requests leave running after they answer, like sending mails. Drained on shutdown
+    pub tasks: TaskTracker,
 }
 
-pub async fn run_migrations(connection: &DatabaseConnection) -> () {
+pub async fn run_migrations(connection: &DatabaseConnection) -> Result<(), anyhow::Error> {
     info!("Start db migrations... ");
-    Migrator::status(connection).await.expect("Check migration status failed");
-    Migrator::up(connection, None).await.expect("Migrations failed");
+    Migrator::status(connection).await.map_err(|error| anyhow::anyhow!("Check migration status failed: {error}"))?;
+    Migrator::up(connection, None).await.map_err(|error| anyhow::anyhow!("Migrations failed: {error}"))?;
+
+    Ok(())
 }
 
 
-pub async fn setup_dependencies(config: Config) -> Arc<AppState> {
+pub async fn setup_dependencies(config: Config) -> Result<Arc<AppState>, anyhow::Error> {
     info!("Setup dependencies... ");
-    let connection = make_connection(connection_options(config.db.clone())).await;
+    let connection = make_connection(connection_options(config.db.clone()))
+        .await
+        .map_err(|error| anyhow::anyhow!("Database connection failed: {error}"))?;
     let hasher = get_argon2_default();
-    let jwt = Arc::new(get_jwt(config.token.clone()));
-    run_migrations(&connection).await;
+    let jwt = get_jwt(config.token.clone())?;
+    let token_cache = get_token_cache(config.token.cache_ttl_seconds);
+    let rate_limit_store = get_rate_limit_store(&config.rate_limit, connection.clone())?;
+    let mailer = get_mailer(&config.mail)?;
+    let cipher = get_cipher(&config.mfa)?;
+    let oidc = get_oidc(&config.oidc)?;
+    run_migrations(&connection).await?;
+
+    let shutdown = CancellationToken::new();
+    let tasks = TaskTracker::new();
+
+    Ok(Arc::new(AppState { connection, hasher, config, jwt, token_cache, rate_limit_store, mailer, cipher, oidc, shutdown, tasks }))
+}
+
+/// Hard deletes users whose soft delete is older than the retention period, once per interval
+/// until shutdown. A purge that already started finishes first.
+pub fn spawn_user_purge(state: Arc<AppState>) -> Option<JoinHandle<()>> {
+    let config = state.config.purge.clone();
+
+    if config.interval_seconds == 0 {
+        info!("User purge is disabled");
+        return None;
+    }
+
+    let task = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_seconds));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = state.shutdown.cancelled() => break,
+            }
 
-    Arc::new(AppState { connection, hasher, config, jwt })
-}
\ No newline at end of file
+            match get_gateway(&*state.connection).user().purge(config.retention_seconds).await {
+                Ok(0) => {},
+                Ok(purged) => info!("Purged {} deleted users", purged),
+                Err(error) => warn!("Failed to purge deleted users: {}", error),
+            }
+        }
+    });
+    Some(task)
+}
+
+/// Deletes the rate limit windows that no limit looks at anymore, once per the longest window
+/// until shutdown. Requests only read the previous and the current window.
+pub fn spawn_rate_limit_cleanup(state: Arc<AppState>) -> Option<JoinHandle<()>> {
+    let config = &state.config.rate_limit;
+
+    if !config.enabled {
+        return None;
+    }
+    let window = config.auth.window_seconds.max(config.api.window_seconds).max(1);
+
+    let task = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(window as u64));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = state.shutdown.cancelled() => break,
+            }
+
+            let expired_before = chrono::Utc::now().timestamp() - 2 * window;
+            match state.rate_limit_store.delete_before(expired_before).await {
+                Ok(0) => {},
+                Ok(deleted) => info!("Deleted {} expired rate limit windows", deleted),
+                Err(error) => warn!("Failed to delete expired rate limit windows: {}", error),
+            }
+        }
+    });
+    Some(task)
+}
@@ -1,29 +1,73 @@
 use utoipa::{Modify, OpenApi};
-use utoipa::openapi::security::{HttpAuthScheme, SecurityScheme, HttpBuilder};
+use utoipa::openapi::security::{ApiKey as ApiKeyScheme, ApiKeyValue, HttpAuthScheme, SecurityScheme, HttpBuilder};
 
 
 use crate::common::error::AppErrorMessage;
 
 use crate::api::v1::endpoints::healthcheck::__path_healthcheck_endpoint;
+use crate::api::v1::endpoints::jwks::__path_jwks_endpoint;
+use crate::api::v1::endpoints::audit::__path_get_audit_events_endpoint;
+use crate::api::v1::endpoints::config::__path_get_config_endpoint;
+use crate::api::v1::endpoints::oidc::{__path_oidc_login_endpoint, __path_oidc_callback_endpoint};
 use crate::api::v1::endpoints::user::{
     __path_create_user_endpoint, 
     __path_get_user_by_id_endpoint, 
     __path_get_many_users_endpoint, 
     __path_update_user_endpoint,
     __path_delete_user_endpoint,
+    __path_restore_user_endpoint,
+    __path_change_user_role_endpoint,
     __path_get_me_endpoint,
 };
+use crate::api::v1::endpoints::api_key::{
+    __path_get_api_keys_endpoint,
+    __path_create_api_key_endpoint,
+    __path_delete_api_key_endpoint,
+};
+use crate::api::v1::endpoints::session::{
+    __path_get_my_sessions_endpoint,
+    __path_revoke_my_session_endpoint,
+    __path_revoke_my_sessions_endpoint,
+    __path_get_user_sessions_endpoint,
+    __path_revoke_user_session_endpoint,
+    __path_revoke_user_sessions_endpoint,
+};
+use crate::api::v1::endpoints::role::{
+    __path_get_many_roles_endpoint,
+    __path_create_role_endpoint,
+    __path_update_role_permissions_endpoint,
+};
+use crate::api::v1::endpoints::mfa::{
+    __path_enroll_mfa_endpoint,
+    __path_confirm_mfa_endpoint,
+    __path_verify_mfa_endpoint,
+};
 use crate::api::v1::endpoints::auth::{
     __path_login_endpoint,
     __path_logout_endpoint,
     __path_refresh_endpoint,
+    __path_verify_email_endpoint,
+    __path_resend_verification_endpoint,
+    __path_forgot_password_endpoint,
+    __path_reset_password_endpoint,
+};
+use crate::common::structs::requests::api_key::CreateApiKey;
+use crate::common::structs::requests::mfa::{MfaCode, VerifyMfa};
+use crate::common::structs::requests::role::{CreateRole, UpdateRolePermissions};
+use crate::common::structs::requests::user::{
+    CreateUser, DeleteUser, ForgotPassword, LoginUser, ResendVerification, ResetPassword, UpdateUser, UpdateUserRole, VerifyEmail
 };
-use crate::common::structs::requests::user::{CreateUser, DeleteUser, LoginUser, UpdateUser};
+use crate::common::structs::responses::api_key::{ApiKey, CreatedApiKey};
+use crate::common::structs::responses::audit::{AuditEvent, AuditEventData};
 use crate::common::structs::responses::healthcheck::HealthCheck;
+use crate::common::structs::responses::mfa::MfaEnrollment;
+use crate::common::structs::responses::session::ActiveSession;
 use crate::common::structs::responses::status::Status;
 use crate::common::structs::responses::token::{Token, TokenType};
+use crate::common::structs::responses::role::Role;
 use crate::common::structs::responses::user::{User, UserData};
-use crate::database::entity::user::Role;
+use crate::database::entity::audit_event::AuditAction;
+use crate::services::security::permission::Permission;
 
 
 struct SecurityAddon;
@@ -36,11 +80,16 @@ impl Modify for SecurityAddon {
                 .bearer_format("JWT")
                 .build()
         );
+        let api_key_scheme = SecurityScheme::ApiKey(ApiKeyScheme::Header(ApiKeyValue::new("X-API-Key")));
         if let Some(components) = &mut openapi.components {
             components.security_schemes.insert(
                 "jwt_token".to_string(),
                 security_scheme
             );
+            components.security_schemes.insert(
+                "api_key".to_string(),
+                api_key_scheme
+            );
         } else {
             openapi.components = Some(
                 utoipa::openapi::ComponentsBuilder::new()
@@ -48,6 +97,10 @@ impl Modify for SecurityAddon {
                         "jwt_token",
                         security_scheme
                     )
+                    .security_scheme(
+                        "api_key",
+                        api_key_scheme
+                    )
                     .build(),
             );
         }
@@ -61,18 +114,45 @@ impl Modify for SecurityAddon {
         login_endpoint,
         logout_endpoint,
         refresh_endpoint,
+        verify_email_endpoint,
+        resend_verification_endpoint,
+        forgot_password_endpoint,
+        reset_password_endpoint,
+        jwks_endpoint,
+        enroll_mfa_endpoint,
+        confirm_mfa_endpoint,
+        verify_mfa_endpoint,
+        oidc_login_endpoint,
+        oidc_callback_endpoint,
         create_user_endpoint,
         get_me_endpoint,
         get_many_users_endpoint,
         get_user_by_id_endpoint,
         update_user_endpoint,
         delete_user_endpoint,
+        restore_user_endpoint,
+        change_user_role_endpoint,
+        get_api_keys_endpoint,
+        create_api_key_endpoint,
+        delete_api_key_endpoint,
+        get_my_sessions_endpoint,
+        revoke_my_session_endpoint,
+        revoke_my_sessions_endpoint,
+        get_user_sessions_endpoint,
+        revoke_user_session_endpoint,
+        revoke_user_sessions_endpoint,
+        get_many_roles_endpoint,
+        create_role_endpoint,
+        update_role_permissions_endpoint,
+        get_audit_events_endpoint,
+        get_config_endpoint,
     ), 
     components(
         schemas(
             HealthCheck, 
             CreateUser, 
             UpdateUser,
+            UpdateUserRole,
             User, 
             AppErrorMessage, 
             Role, 
@@ -81,7 +161,24 @@ impl Modify for SecurityAddon {
             Token,
             Status,
             TokenType,
-            DeleteUser
+            DeleteUser,
+            Permission,
+            CreateRole,
+            UpdateRolePermissions,
+            VerifyEmail,
+            ResendVerification,
+            ForgotPassword,
+            ResetPassword,
+            MfaEnrollment,
+            MfaCode,
+            VerifyMfa,
+            CreateApiKey,
+            ApiKey,
+            CreatedApiKey,
+            ActiveSession,
+            AuditAction,
+            AuditEvent,
+            AuditEventData
         ),
     ),
     modifiers(&SecurityAddon)
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use uuid::Uuid;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::handlers::api_key::create::create_api_key;
+use crate::api::v1::handlers::api_key::delete::delete_api_key;
+use crate::api::v1::handlers::api_key::get::get_api_keys;
+use crate::api::v1::middlewares::auth::Session;
+use crate::common::structs::requests::api_key::CreateApiKey;
+use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+
+
+/// List your API keys
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/me/api-keys",
+    tag = "user",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Vec<ApiKey>
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn get_api_keys_endpoint(
+    _: Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    match get_api_keys(&state.connection, user).await {
+        Ok(keys) => (StatusCode::OK, Json(keys)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Create an API key
+///
+/// Creates a long-lived key for scripts and CI jobs, limited to the given scopes. Send it in the
+/// `X-API-Key` header or as `Authorization: ApiKey <key>`. The key is returned only once.
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/me/api-keys",
+    tag = "user",
+    request_body = CreateApiKey,
+    responses(
+        (
+            status = 201,
+            description = "API key created successfully",
+            body = CreatedApiKey
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Expiration date must be in the future", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API key scopes exceed your permissions", "details": {"scopes": ["roles:write"]}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn create_api_key_endpoint(
+    _: Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    audit: AuditContext,
+    Json(data): Json<CreateApiKey>,
+) -> impl IntoResponse {
+    match create_api_key(&state.connection, user, data, &audit).await {
+        Ok(key) => (StatusCode::CREATED, Json(key)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Revoke an API key
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/me/api-keys/{key_id}",
+    tag = "user",
+    params(
+        ("key_id" = Uuid, description = "Unique identifier of the API key")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "API key not found", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn delete_api_key_endpoint(
+    _: Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(key_id): Path<Uuid>,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match delete_api_key(&state.connection, user, key_id, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::handlers::audit::get::get_audit_events;
+use crate::api::v1::middlewares::permission::RequirePermission;
+use crate::common::structs::requests::audit::AuditQuery;
+use crate::common::structs::requests::pagination::Pagination;
+use crate::services::security::permission::AuditRead;
+
+
+/// Query the audit log
+///
+/// Returns recorded events, newest first. Every request carries an `X-Request-Id` response header
+/// that matches the `request_id` of the events it recorded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit",
+    tag = "audit",
+    params(
+        AuditQuery,
+        Pagination
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = AuditEventData
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Failed to deserialize query string: unknown variant `user.rename`", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "audit:read"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["audit:read"]),
+        ("api_key" = ["audit:read"])
+    )
+)]
+pub async fn get_audit_events_endpoint(
+    _: RequirePermission<AuditRead>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+    Query(pagination): Query<Pagination>,
+) -> impl IntoResponse {
+    let (offset, limit) = pagination.calculate_offset_and_limit();
+
+    match get_audit_events(&state.connection, query, Some(offset), Some(limit)).await {
+        Ok(data) => (StatusCode::OK, Json(data)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use axum::{extract::State, response::IntoResponse, Json};
+use std::net::SocketAddr;
+
+use axum::{extract::{ConnectInfo, State}, http::{header, HeaderMap, StatusCode}, response::IntoResponse, Extension, Json};
 use axum_extra::extract::CookieJar;
 
 
@@ -10,12 +12,28 @@ use crate::{
         handlers::auth::{
             login::login_handler, 
             logout::logout_handler, 
-            refresh::refresh_handler
-        }
-    }, common::structs::requests::user::LoginUser, 
+            password::{forgot_password_handler, reset_password_handler},
+            refresh::refresh_handler,
+            verify_email::{resend_verification_handler, verify_email_handler}
+        },
+        middlewares::auth::Session
+    }, 
+    common::structs::{
+        requests::user::{ForgotPassword, LoginUser, ResendVerification, ResetPassword, VerifyEmail}, 
+        responses::{status::Status, user::User}
+    }, 
+    services::audit::AuditContext,
 };
 
 
+pub fn device(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(255).collect())
+}
+
+
 #[utoipa::path(
     post,
     path = "/api/v1/auth/login",
@@ -24,7 +42,7 @@ use crate::{
     responses(
         (
             status = 200,
-            description = "Success",
+            description = "Success. Users with MFA get an `MFA_PENDING` token to complete at `/auth/mfa/verify`",
             body = Token
         ),
         (
@@ -33,12 +51,27 @@ use crate::{
             body = AppErrorMessage,
             example = json!({"message": "Password mismatch", "details": null})
         ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Email is not verified", "details": null})
+        ),
         (
             status = 404,
             description = "Not Found",
             body = AppErrorMessage,
             example = json!({"message": "User Not found", "details": null})
         ),
+        (
+            status = 429,
+            description = "Too Many Requests",
+            body = AppErrorMessage,
+            headers(
+                ("Retry-After" = i64, description = "Seconds until the next attempt is allowed")
+            ),
+            example = json!({"message": "Too many failed login attempts. Try again later", "details": {"retry_after": 30}})
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -49,9 +82,21 @@ use crate::{
 )]
 pub async fn login_endpoint(
     State(state): State<Arc<AppState>>, 
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    audit: AuditContext,
     Json(body): Json<LoginUser>
 ) -> impl IntoResponse {
-    match login_handler(&state.connection, &state.hasher, &state.jwt, body).await {
+    match login_handler(
+        &state.connection, 
+        &state.hasher, 
+        &state.jwt, 
+        body, 
+        device(&headers), 
+        &state.config.login, 
+        Some(address.ip()),
+        &audit
+    ).await {
         Ok(response) => response,
         Err(error) => error.into_response()
     }
@@ -91,8 +136,10 @@ pub async fn login_endpoint(
 pub async fn refresh_endpoint(
     cookie_jar: CookieJar,
     State(state): State<Arc<AppState>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    match refresh_handler(&state.jwt, cookie_jar).await {
+    match refresh_handler(&state.connection, &state.jwt, cookie_jar, device(&headers), Some(address.ip())).await {
         Ok(response) => response,
         Err(error) => error.into_response()
     }
@@ -115,6 +162,12 @@ pub async fn refresh_endpoint(
             body = AppErrorMessage,
             example = json!({"message": "Unauthorized", "details": null})
         ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -126,9 +179,180 @@ pub async fn refresh_endpoint(
         ("jwt_token" = [])
     )
 )]
-pub async fn logout_endpoint() -> impl IntoResponse {
-    match logout_handler().await {
+pub async fn logout_endpoint(
+    cookie_jar: CookieJar,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Session(claims): Session,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match logout_handler(&state.connection, &state.jwt, &state.token_cache, user, claims, cookie_jar, &audit).await {
         Ok(response) => response,
         Err(error) => error.into_response()
     }
-}
\ No newline at end of file
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify-email",
+    tag = "auth",
+    request_body = VerifyEmail,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Verification token was already used or has expired", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid token provided", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+)]
+pub async fn verify_email_endpoint(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(body): Json<VerifyEmail>,
+) -> impl IntoResponse {
+    match verify_email_handler(&state.connection, &state.jwt, body, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Sends a new verification mail if the email belongs to an unverified user.
+/// Succeeds either way, so the response does not reveal registered emails.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/resend-verification",
+    tag = "auth",
+    request_body = ResendVerification,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 429,
+            description = "Too Many Requests",
+            body = AppErrorMessage,
+            example = json!({"message": "Rate limit exceeded", "details": {"retry_after": 30}})
+        )
+    ),
+)]
+pub async fn resend_verification_endpoint(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ResendVerification>,
+) -> impl IntoResponse {
+    match resend_verification_handler(
+        &state.connection, 
+        &state.jwt, 
+        state.mailer.clone(), 
+        &state.tasks,
+        &state.config.mail, 
+        body
+    ).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+
+/// Mails a password reset link if the login exists and has an email to send it to.
+/// Succeeds either way, so the response does not reveal which logins exist.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/forgot",
+    tag = "auth",
+    request_body = ForgotPassword,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 429,
+            description = "Too Many Requests",
+            body = AppErrorMessage,
+            example = json!({"message": "Rate limit exceeded", "details": {"retry_after": 30}})
+        )
+    ),
+)]
+pub async fn forgot_password_endpoint(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ForgotPassword>,
+) -> impl IntoResponse {
+    match forgot_password_handler(state.connection.clone(), state.mailer.clone(), &state.tasks, state.config.mail.clone(), body).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Sets a new password with a reset token. Tokens issued before the reset stop working.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/reset",
+    tag = "auth",
+    request_body = ResetPassword,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid or expired reset token", "details": null})
+        ),
+        (
+            status = 422,
+            description = "Unprocessable Entity",
+            body = AppErrorMessage,
+            example = json!({
+                "message": "Password does not meet the policy", 
+                "details": {"violations": [{"rule": "min_length", "message": "Password must be at least 8 characters long"}]}
+            })
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+)]
+pub async fn reset_password_endpoint(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(body): Json<ResetPassword>,
+) -> impl IntoResponse {
+    match reset_password_handler(&state.connection, &state.hasher, &state.config.password, body, &audit).await {
+        Ok(user_id) => {
+            state.token_cache.invalidate_user(user_id);
+            (StatusCode::OK, Json(Status { status: true })).into_response()
+        },
+        Err(error) => error.into_response()
+    }
+}
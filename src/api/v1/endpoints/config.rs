@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::handlers::config::get::get_config;
+use crate::api::v1::middlewares::permission::RequirePermission;
+use crate::services::security::permission::ConfigRead;
+
+
+/// Get the effective configuration
+///
+/// Returns the configuration the server runs with, after merging the config file, environment
+/// variables and `--set` overrides. Secrets are replaced with `[redacted]`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/config",
+    tag = "admin",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Object,
+            example = json!({"db": {"host": "localhost", "port": 5432, "password": "[redacted]"}, "token": {"secret_key": "[redacted]"}})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "config:read"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["config:read"]),
+        ("api_key" = ["config:read"])
+    )
+)]
+pub async fn get_config_endpoint(
+    _: RequirePermission<ConfigRead>,
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match get_config(&state.config) {
+        Ok(data) => (StatusCode::OK, Json(data)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
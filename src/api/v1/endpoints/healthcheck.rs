@@ -1,7 +1,15 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 
+use crate::api::v1::dependencies::AppState;
 use crate::common::structs::responses::healthcheck::HealthCheck;
 
+/// Check the server health
+///
+/// Fails once the server is shutting down, so load balancers stop sending requests while open ones finish.
 #[utoipa::path(
     get, 
     path = "/api/v1/healthcheck",
@@ -11,9 +19,18 @@ use crate::common::structs::responses::healthcheck::HealthCheck;
             status = 200,
             description = "Healthcheck",
             body = HealthCheck
+        ),
+        (
+            status = 503,
+            description = "Shutting down",
+            body = HealthCheck,
+            example = json!({"ok": false})
         )
     )
 )]
-pub async fn healthcheck_endpoint() -> Json<HealthCheck> {
-    Json(HealthCheck { ok: true })
-}
\ No newline at end of file
+pub async fn healthcheck_endpoint(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthCheck>) {
+    match state.shutdown.is_cancelled() {
+        true => (StatusCode::SERVICE_UNAVAILABLE, Json(HealthCheck { ok: false })),
+        false => (StatusCode::OK, Json(HealthCheck { ok: true })),
+    }
+}
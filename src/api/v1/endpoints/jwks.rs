@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header::CACHE_CONTROL;
+use axum::response::IntoResponse;
+use axum::Json;
+
+use crate::api::v1::dependencies::AppState;
+
+
+/// Public keys the access tokens can be verified with, as a JSON Web Key Set.
+///
+/// Empty when tokens are signed with a symmetric algorithm.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "auth",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            content_type = "application/json",
+            example = json!({"keys": [{"use": "sig", "alg": "RS256", "kid": "2024-01", "kty": "RSA", "n": "0vx7agoebGcQSuu...", "e": "AQAB"}]})
+        )
+    )
+)]
+pub async fn jwks_endpoint(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    ([(CACHE_CONTROL, "public, max-age=300")], Json(state.jwt.jwks()))
+}
@@ -0,0 +1,201 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::endpoints::auth::device;
+use crate::api::v1::middlewares::auth::Session;
+use crate::api::v1::handlers::auth::mfa::{confirm_mfa_handler, enroll_mfa_handler, verify_mfa_handler};
+use crate::common::structs::requests::mfa::{MfaCode, VerifyMfa};
+use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+
+
+/// Start MFA enrollment
+///
+/// Generates a TOTP secret and recovery codes for the current user, replacing an unconfirmed enrollment.
+/// MFA is enabled after the secret is confirmed with `/auth/mfa/confirm`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/enroll",
+    tag = "auth",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = MfaEnrollment
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "MFA is already enabled", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn enroll_mfa_endpoint(
+    State(state): State<Arc<AppState>>,
+    _: Session,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    match enroll_mfa_handler(&state.connection, &state.hasher, &state.cipher, &state.config.mfa, user).await {
+        Ok(enrollment) => (StatusCode::OK, Json(enrollment)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Confirm MFA enrollment
+///
+/// Enables MFA for the current user with a code from the authenticator app.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/confirm",
+    tag = "auth",
+    request_body = MfaCode,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid code", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "MFA is already enabled", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn confirm_mfa_endpoint(
+    State(state): State<Arc<AppState>>,
+    _: Session,
+    Extension(user): Extension<User>,
+    audit: AuditContext,
+    Json(body): Json<MfaCode>,
+) -> impl IntoResponse {
+    match confirm_mfa_handler(&state.connection, &state.cipher, user, body, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Complete an MFA login
+///
+/// Exchanges the `MFA_PENDING` token returned by login and a TOTP or recovery code
+/// for an access token and a refresh cookie.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/verify",
+    tag = "auth",
+    request_body = VerifyMfa,
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Token
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid code", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid token", "details": null})
+        ),
+        (
+            status = 429,
+            description = "Too Many Requests",
+            body = AppErrorMessage,
+            headers(
+                ("Retry-After" = i64, description = "Seconds until the next attempt is allowed")
+            ),
+            example = json!({"message": "Too many failed login attempts. Try again later", "details": {"retry_after": 30}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+)]
+pub async fn verify_mfa_endpoint(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    audit: AuditContext,
+    Json(body): Json<VerifyMfa>,
+) -> impl IntoResponse {
+    match verify_mfa_handler(
+        &state.connection, 
+        &state.hasher, 
+        &state.jwt, 
+        &state.cipher, 
+        body, 
+        device(&headers), 
+        Some(address.ip()),
+        &state.config.login,
+        &audit
+    ).await {
+        Ok(response) => response,
+        Err(error) => error.into_response()
+    }
+}
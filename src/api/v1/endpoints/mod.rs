@@ -1,4 +1,12 @@
 pub mod healthcheck;
 pub mod user;
+pub mod role;
 pub mod auth;
 
+pub mod mfa;
+pub mod jwks;
+pub mod oidc;
+pub mod api_key;
+pub mod session;
+pub mod audit;
+pub mod config;
\ No newline at end of file
@@ -0,0 +1,136 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum_extra::extract::CookieJar;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::endpoints::auth::device;
+use crate::api::v1::handlers::auth::oidc::{oidc_callback_handler, oidc_login_handler};
+use crate::common::structs::requests::oidc::OidcCallback;
+use crate::services::audit::AuditContext;
+
+
+/// Log in with an OpenID Connect provider
+///
+/// Redirects to the provider's authorization endpoint using the authorization code flow with PKCE.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/{provider}/login",
+    tag = "auth",
+    params(
+        ("provider" = String, Path, description = "Name of a configured provider", example = "google")
+    ),
+    responses(
+        (
+            status = 303,
+            description = "Redirect to the provider",
+            headers(
+                ("Location" = String, description = "Authorization url of the provider"),
+                ("Set-Cookie" = String, description = "Encrypted login state checked by the callback")
+            )
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown identity provider", "details": {"provider": "google"}})
+        ),
+        (
+            status = 503,
+            description = "Service Unavailable",
+            body = AppErrorMessage,
+            example = json!({"message": "Identity provider is unavailable", "details": {"provider": "google"}})
+        )
+    )
+)]
+pub async fn oidc_login_endpoint(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+) -> impl IntoResponse {
+    match oidc_login_handler(&state.oidc, &state.cipher, provider).await {
+        Ok(response) => response,
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Complete an OpenID Connect login
+///
+/// Validates the provider's ID token and logs in the linked user. Unknown identities are linked
+/// to the user with the same verified email or get a new user when the provider is configured to.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/{provider}/callback",
+    tag = "auth",
+    params(
+        ("provider" = String, Path, description = "Name of a configured provider", example = "google"),
+        OidcCallback
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success. Users with MFA get an `MFA_PENDING` token to complete at `/auth/mfa/verify`",
+            body = Token
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Login state is invalid or expired. Start the login again", "details": null})
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Invalid ID token", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "No user is linked to this identity", "details": {"provider": "google"}})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "Email already belongs to another user", "details": {"email": "user@example.com"}})
+        ),
+        (
+            status = 503,
+            description = "Service Unavailable",
+            body = AppErrorMessage,
+            example = json!({"message": "Identity provider is unavailable", "details": {"provider": "google"}})
+        )
+    )
+)]
+pub async fn oidc_callback_endpoint(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    Query(callback): Query<OidcCallback>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    cookie_jar: CookieJar,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match oidc_callback_handler(
+        &state.connection,
+        &state.jwt,
+        &state.hasher,
+        &state.oidc,
+        &state.cipher,
+        cookie_jar,
+        provider,
+        callback,
+        device(&headers),
+        Some(address.ip()),
+        &state.config.login,
+        &audit
+    ).await {
+        Ok(response) => response,
+        Err(error) => error.into_response()
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use uuid::Uuid;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::handlers::role::create::create_role;
+use crate::api::v1::handlers::role::get::get_many_roles;
+use crate::api::v1::handlers::role::update::update_role_permissions;
+use crate::api::v1::middlewares::permission::RequirePermission;
+use crate::common::structs::requests::role::{CreateRole, UpdateRolePermissions};
+use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+use crate::services::security::permission::{RolesRead, RolesWrite};
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/roles",
+    tag = "role",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Vec<Role>
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "roles:read"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["roles:read"]),
+        ("api_key" = ["roles:read"])
+    )
+)]
+pub async fn get_many_roles_endpoint(
+    _: RequirePermission<RolesRead>,
+    State(state): State<Arc<AppState>>, 
+) -> impl IntoResponse {
+    match get_many_roles(&state.connection).await {
+        Ok(roles) => (StatusCode::OK, Json(roles)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/roles",
+    tag = "role",
+    request_body = CreateRole,
+    responses(
+        (
+            status = 201,
+            description = "Role created successfully",
+            body = Role
+        ),
+        (
+            status = 403,
+            description = "Forbidden, `roles:write` is missing or the role grants permissions you do not have",
+            body = AppErrorMessage,
+            example = json!({"message": "Role grants permissions you do not have", "details": {"permissions": ["config:read"]}})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "Role already exists", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["roles:write"]),
+        ("api_key" = ["roles:write"])
+    )
+)]
+pub async fn create_role_endpoint(
+    _: RequirePermission<RolesWrite>,
+    State(state): State<Arc<AppState>>, 
+    Extension(user): Extension<User>,
+    audit: AuditContext,
+    Json(data): Json<CreateRole>,
+) -> impl IntoResponse {
+    match create_role(&state.connection, user, data, &audit).await {
+        Ok(role) => (StatusCode::CREATED, Json(role)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/roles/{role_id}/permissions",
+    tag = "role",
+    params(
+        ("role_id" = Uuid, description = "Unique identifier of the role")
+    ),
+    request_body = UpdateRolePermissions,
+    responses(
+        (
+            status = 200,
+            description = "Role permissions updated successfully",
+            body = Role
+        ),
+        (
+            status = 403,
+            description = "Forbidden, `roles:write` is missing or the role grants permissions you do not have",
+            body = AppErrorMessage,
+            example = json!({"message": "Role grants permissions you do not have", "details": {"permissions": ["config:read"]}})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "Role not found", "details": null})
+        ),
+        (
+            status = 409,
+            description = "Conflict, the Admin role would lose permissions",
+            body = AppErrorMessage,
+            example = json!({"message": "Permissions cannot be removed from the Admin role", "details": {"permissions": ["roles:write"]}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["roles:write"]),
+        ("api_key" = ["roles:write"])
+    )
+)]
+pub async fn update_role_permissions_endpoint(
+    _: RequirePermission<RolesWrite>,
+    State(state): State<Arc<AppState>>, 
+    Extension(user): Extension<User>,
+    Path(role_id): Path<Uuid>,
+    audit: AuditContext,
+    Json(data): Json<UpdateRolePermissions>,
+) -> impl IntoResponse {
+    match update_role_permissions(&state.connection, user, role_id, data, &audit).await {
+        Ok(role) => {
+            state.token_cache.invalidate_users();
+            (StatusCode::OK, Json(role)).into_response()
+        },
+        Err(error) => error.into_response()
+    }
+}
@@ -0,0 +1,338 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_extra::extract::CookieJar;
+use uuid::Uuid;
+
+use crate::api::v1::dependencies::AppState;
+use crate::api::v1::handlers::session::delete::{revoke_session, revoke_sessions};
+use crate::api::v1::handlers::session::get::{current_session, get_sessions};
+use crate::api::v1::middlewares::auth::Session;
+use crate::api::v1::middlewares::permission::RequirePermission;
+use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+use crate::services::security::permission::{UsersRead, UsersWrite};
+
+
+/// List your sessions
+///
+/// Returns every device you are logged in on. The session making the request is marked as `current`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/me/sessions",
+    tag = "user",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Vec<ActiveSession>
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn get_my_sessions_endpoint(
+    Session(claims): Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    cookie_jar: CookieJar,
+) -> impl IntoResponse {
+    let current = match current_session(&state.connection, &state.jwt, &cookie_jar, &claims).await {
+        Ok(current) => current,
+        Err(error) => return error.into_response()
+    };
+
+    match get_sessions(&state.connection, user.id, current).await {
+        Ok(sessions) => (StatusCode::OK, Json(sessions)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Revoke one of your sessions
+///
+/// Its refresh token and the access tokens issued for it stop working at once.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/me/sessions/{session_id}",
+    tag = "user",
+    params(
+        ("session_id" = Uuid, description = "Unique identifier of the session")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "Session not found", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn revoke_my_session_endpoint(
+    _: Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(session_id): Path<Uuid>,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match revoke_session(&state.connection, &state.token_cache, user.id, session_id, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Log out everywhere
+///
+/// Revokes all of your sessions, including the current one, and every access token issued so far.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/me/sessions",
+    tag = "user",
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "API keys cannot be used for this endpoint", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn revoke_my_sessions_endpoint(
+    _: Session,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match revoke_sessions(&state.connection, &state.token_cache, user.id, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// List sessions of a user
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user_id}/sessions",
+    tag = "user",
+    params(
+        ("user_id" = Uuid, description = "Unique identifier of the user")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Vec<ActiveSession>
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:read"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["users:read"]),
+        ("api_key" = ["users:read"])
+    )
+)]
+pub async fn get_user_sessions_endpoint(
+    _: RequirePermission<UsersRead>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match get_sessions(&state.connection, user_id, None).await {
+        Ok(sessions) => (StatusCode::OK, Json(sessions)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Revoke a session of a user
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{user_id}/sessions/{session_id}",
+    tag = "user",
+    params(
+        ("user_id" = Uuid, description = "Unique identifier of the user"),
+        ("session_id" = Uuid, description = "Unique identifier of the session")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:write"}})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "Session not found", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["users:write"]),
+        ("api_key" = ["users:write"])
+    )
+)]
+pub async fn revoke_user_session_endpoint(
+    _: RequirePermission<UsersWrite>,
+    State(state): State<Arc<AppState>>,
+    Path((user_id, session_id)): Path<(Uuid, Uuid)>,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match revoke_session(&state.connection, &state.token_cache, user_id, session_id, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Log a user out everywhere
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{user_id}/sessions",
+    tag = "user",
+    params(
+        ("user_id" = Uuid, description = "Unique identifier of the user")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Success",
+            body = Status
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            body = AppErrorMessage,
+            example = json!({"message": "Unauthorized", "details": null})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:write"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["users:write"]),
+        ("api_key" = ["users:write"])
+    )
+)]
+pub async fn revoke_user_sessions_endpoint(
+    _: RequirePermission<UsersWrite>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    audit: AuditContext,
+) -> impl IntoResponse {
+    match revoke_sessions(&state.connection, &state.token_cache, user_id, &audit).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => error.into_response()
+    }
+}
@@ -1,20 +1,32 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use axum::extract::{Path, Query, State};
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::{Extension, Json};
+use axum_extra::extract::CookieJar;
+use log::warn;
 use uuid::Uuid;
 
 use crate::api::v1::dependencies::AppState;
+use crate::api::v1::endpoints::auth::device;
+use crate::api::v1::handlers::auth::refresh::keep_session;
 use crate::api::v1::handlers::user::delete::delete_user_handler;
+use crate::api::v1::handlers::user::restore::restore_user_handler;
+use crate::api::v1::handlers::user::role::change_user_role_handler;
 use crate::api::v1::handlers::user::update::update_user;
+use crate::api::v1::middlewares::auth::Session;
+use crate::api::v1::middlewares::permission::RequirePermission;
+use crate::common::error::AppError;
 use crate::common::structs::requests::pagination::Pagination;
-use crate::common::structs::requests::user::{CreateUser, DeleteUser, UpdateUser};
+use crate::common::structs::requests::user::{CreateUser, DeleteUser, UpdateUser, UpdateUserRole};
 
 use crate::api::v1::handlers::user::create::create_user;
 use crate::api::v1::handlers::user::get::{get_user, get_many_users};
 use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+use crate::services::security::permission::{RolesWrite, UsersDelete, UsersRead};
 
 #[utoipa::path(
     post,
@@ -39,6 +51,15 @@ use crate::common::structs::responses::user::User;
             body = AppErrorMessage,
             example = json!({"message": "User already exists", "details": null})
         ),
+        (
+            status = 422,
+            description = "Unprocessable Entity",
+            body = AppErrorMessage,
+            example = json!({
+                "message": "Password does not meet the policy", 
+                "details": {"violations": [{"rule": "min_length", "message": "Password must be at least 8 characters long"}]}
+            })
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -49,9 +70,10 @@ use crate::common::structs::responses::user::User;
 )]
 pub async fn create_user_endpoint(
     State(state): State<Arc<AppState>>, 
+    audit: AuditContext,
     Json(data): Json<CreateUser>,
 ) -> impl IntoResponse {
-    match create_user(&state.connection, data, &state.hasher).await {
+    match create_user(&state.connection, data, &state.hasher, &state.jwt, state.mailer.clone(), &state.tasks, &state.config, &audit).await {
         Ok(user) => (StatusCode::CREATED, Json(user)).into_response(),
         Err(error) => error.into_response(),
     }
@@ -70,6 +92,12 @@ pub async fn create_user_endpoint(
             description = "Successfully",
             body = UserData
         ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:read"}})
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -78,10 +106,12 @@ pub async fn create_user_endpoint(
         )
     ),
     security(
-        ("jwt_token" = [])
+        ("jwt_token" = ["users:read"]),
+        ("api_key" = ["users:read"])
     )
 )]
 pub async fn get_many_users_endpoint(
+    _: RequirePermission<UsersRead>,
     State(state): State<Arc<AppState>>, 
     Query(pagination): Query<Pagination>,
 ) -> impl IntoResponse {
@@ -107,6 +137,12 @@ pub async fn get_many_users_endpoint(
             description = "Success",
             body = User
         ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:read"}})
+        ),
         (
             status = 404,
             description = "Not Found",
@@ -121,10 +157,12 @@ pub async fn get_many_users_endpoint(
         )
     ),
     security(
-        ("jwt_token" = [])
+        ("jwt_token" = ["users:read"]),
+        ("api_key" = ["users:read"])
     )
 )]
 pub async fn get_user_by_id_endpoint(
+    _: RequirePermission<UsersRead>,
     State(state): State<Arc<AppState>>, 
     Path(user_id): Path<Uuid>
 ) -> impl IntoResponse {
@@ -136,6 +174,17 @@ pub async fn get_user_by_id_endpoint(
 
 
 
+/// Update a user
+///
+/// Updates the current user. Updating another user by `id` requires the `users:write` permission
+/// and every permission that user has.
+///
+/// Changing your own login, password or email requires `current_password`. A password change
+/// revokes every other session; the current one gets a new refresh cookie and has to refresh its access token.
+///
+/// Logins and emails can only be changed with an access token, API keys are rejected.
+///
+/// Roles can't be changed here, use `PUT /users/{id}/role`.
 #[utoipa::path(
     patch,
     path = "/api/v1/users",
@@ -153,12 +202,33 @@ pub async fn get_user_by_id_endpoint(
             body = AppErrorMessage,
             example = json!({"message": "Transaction failed", "details": null})
         ),
+        (
+            status = 403,
+            description = "Forbidden, a permission is missing, the user has permissions you do not have or an API key changes a login or email",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:write"}})
+        ),
         (
             status = 409,
             description = "Conflict",
             body = AppErrorMessage,
             example = json!({"message": "Login already exists", "details": null})
         ),
+        (
+            status = 422,
+            description = "Unprocessable Entity",
+            body = AppErrorMessage,
+            example = json!({
+                "message": "Password does not meet the policy", 
+                "details": {"violations": [{"rule": "common", "message": "Password is too common"}]}
+            })
+        ),
+        (
+            status = 429,
+            description = "Too Many Requests, the current password was wrong too many times",
+            body = AppErrorMessage,
+            example = json!({"message": "Too many failed login attempts. Try again later", "details": {"retry_after": 30}})
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -167,20 +237,137 @@ pub async fn get_user_by_id_endpoint(
         )
     ),
     security(
-        ("jwt_token" = [])
+        ("jwt_token" = []),
+        ("api_key" = [])
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_user_endpoint(
     State(state): State<Arc<AppState>>, 
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
     Extension(user): Extension<User>,
+    session: Result<Session, AppError>,
+    cookie_jar: CookieJar,
+    headers: HeaderMap,
+    audit: AuditContext,
     Json(data): Json<UpdateUser>,
 ) -> impl IntoResponse {
-    match update_user(&state.connection, user, data, &state.hasher).await {
-        Ok(user) => (StatusCode::OK, Json(user)).into_response(),
+    if data.login.is_some() || data.email.is_some() {
+        if let Err(error) = session {
+            return error.into_response();
+        }
+    }
+    let own_password = data.password.is_some() && data.id.is_none_or(|id| id == user.id);
+
+    match update_user(
+        &state.connection, 
+        user, 
+        data, 
+        &state.hasher, 
+        &state.jwt, 
+        state.mailer.clone(), 
+        &state.tasks,
+        &state.config,
+        &audit
+    ).await {
+        Ok(user) => {
+            state.token_cache.invalidate_user(user.id);
+            let mut response = (StatusCode::OK, Json(&user)).into_response();
+
+            if own_password {
+                match keep_session(&state.connection, &state.jwt, &cookie_jar, user.id, device(&headers), Some(address.ip())).await {
+                    Ok(Some(cookie)) => {
+                        response.headers_mut().insert(header::SET_COOKIE, cookie);
+                    },
+                    Ok(None) => {},
+                    Err(error) => warn!("Failed to keep the session of user {}: {}", user.id, error),
+                }
+            }
+
+            response
+        },
+        Err(error) => error.into_response()
+    }
+}
+
+/// Change the role of a user
+///
+/// Requires the `roles:write` permission, and the role may not grant permissions you don't have.
+/// The last user with the `Admin` role can't be demoted. The user's tokens are revoked.
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{user_id}/role",
+    tag = "user",
+    request_body = UpdateUserRole,
+    params(
+        ("user_id" = Uuid, description = "Unique identifier of the user")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Role changed successfully",
+            body = User
+        ),
+        (
+            status = 400,
+            description = "Bad Request",
+            body = AppErrorMessage,
+            example = json!({"message": "Role not found", "details": {"role": "Moderator"}})
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Role grants permissions you do not have", "details": {"permissions": ["audit:read"]}})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "User not found", "details": null})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "Cannot remove the last admin", "details": null})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["roles:write"]),
+        ("api_key" = ["roles:write"])
+    )
+)]
+pub async fn change_user_role_endpoint(
+    _: RequirePermission<RolesWrite>,
+    State(state): State<Arc<AppState>>, 
+    Extension(user): Extension<User>,
+    audit: AuditContext,
+    Path(user_id): Path<Uuid>,
+    Json(data): Json<UpdateUserRole>,
+) -> impl IntoResponse {
+    match change_user_role_handler(&state.connection, user, user_id, data, &audit).await {
+        Ok(user) => {
+            state.token_cache.invalidate_user(user.id);
+            (StatusCode::OK, Json(user)).into_response()
+        },
         Err(error) => error.into_response()
     }
 }
 
+/// Delete a user
+///
+/// Deletes the current user. Deleting another user by `id` requires the `users:delete` permission
+/// and every permission that user has.
+///
+/// Deleted users can be restored until they are purged after `USER_PURGE_RETENTION_SECONDS`.
+/// The last user with the `Admin` role can't be deleted.
 #[utoipa::path(
     delete,
     path = "/api/v1/users",
@@ -198,6 +385,18 @@ pub async fn update_user_endpoint(
             body = AppErrorMessage,
             example = json!({"message": "Transaction failed", "details": null})
         ),
+        (
+            status = 403,
+            description = "Forbidden, a permission is missing or the user has permissions you do not have",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:delete"}})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "Cannot remove the last admin", "details": null})
+        ),
         (
             status = 500,
             description = "Internal Server Error",
@@ -206,16 +405,83 @@ pub async fn update_user_endpoint(
         )
     ),
     security(
-        ("jwt_token" = [])
+        ("jwt_token" = []),
+        ("api_key" = [])
     )
 )]
 pub async fn delete_user_endpoint(
     State(state): State<Arc<AppState>>, 
     Extension(user): Extension<User>,
+    audit: AuditContext,
     Json(data): Json<DeleteUser>,
 ) -> impl IntoResponse {
-    match delete_user_handler(&state.connection, user, data).await {
-        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+    let target_id = data.id.unwrap_or(user.id);
+
+    match delete_user_handler(&state.connection, user, data, &audit).await {
+        Ok(status) => {
+            state.token_cache.invalidate_user(target_id);
+            (StatusCode::OK, Json(status)).into_response()
+        },
+        Err(error) => error.into_response()
+    }
+}
+
+
+/// Restore a deleted user
+///
+/// Brings back a user deleted through `DELETE /users` before it was purged. Its sessions and
+/// tokens from before the deletion stay revoked.
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{user_id}/restore",
+    tag = "user",
+    params(
+        ("user_id" = Uuid, description = "Unique identifier of the user")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "User restored successfully",
+            body = User
+        ),
+        (
+            status = 403,
+            description = "Forbidden",
+            body = AppErrorMessage,
+            example = json!({"message": "Permission denied", "details": {"required": "users:delete"}})
+        ),
+        (
+            status = 404,
+            description = "Not Found",
+            body = AppErrorMessage,
+            example = json!({"message": "Deleted user not found", "details": null})
+        ),
+        (
+            status = 409,
+            description = "Conflict",
+            body = AppErrorMessage,
+            example = json!({"message": "Login already exists", "details": {"login": "user"}})
+        ),
+        (
+            status = 500,
+            description = "Internal Server Error",
+            body = AppErrorMessage,
+            example = json!({"message": "Unknown", "details": null})
+        )
+    ),
+    security(
+        ("jwt_token" = ["users:delete"]),
+        ("api_key" = ["users:delete"])
+    )
+)]
+pub async fn restore_user_endpoint(
+    _: RequirePermission<UsersDelete>,
+    State(state): State<Arc<AppState>>, 
+    audit: AuditContext,
+    Path(user_id): Path<Uuid>
+) -> impl IntoResponse {
+    match restore_user_handler(&state.connection, user_id, &audit).await {
+        Ok(user) => (StatusCode::OK, Json(user)).into_response(),
         Err(error) => error.into_response()
     }
 }
@@ -251,7 +517,8 @@ pub async fn delete_user_endpoint(
         )
     ),
     security(
-        ("jwt_token" = [])
+        ("jwt_token" = []),
+        ("api_key" = [])
     )
 )]
 pub async fn get_me_endpoint(
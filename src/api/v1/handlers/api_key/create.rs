@@ -0,0 +1,55 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::api::common::helpers::try_transaction;
+use crate::common::{
+    error::{AppError, AppErrorMessage}, 
+    structs::{requests::api_key::CreateApiKey, responses::{api_key::CreatedApiKey, user::User}}
+};
+use crate::database::entity::audit_event::AuditAction;
+use crate::services::{audit::{diff, AuditContext}, gateway::get_gateway};
+
+
+pub async fn create_api_key(
+    connection: &DatabaseConnection,
+    user: User,
+    data: CreateApiKey,
+    audit: &AuditContext,
+) -> Result<CreatedApiKey, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let key = async {
+        let key = gateway.api_key().create(&user, data).await?;
+        let snapshot = json!({
+            "id": key.api_key.id,
+            "name": key.api_key.name,
+            "prefix": key.api_key.prefix,
+            "scopes": key.api_key.scopes,
+            "expires_at": key.api_key.expires_at,
+        });
+        gateway.audit().record(audit, AuditAction::ApiKeyCreate, Some(user.id), diff(None, Some(&snapshot))).await?;
+
+        Ok::<_, AppError>(key)
+    }.await;
+
+    match key {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to create an API key. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to create an API key. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::api::common::helpers::try_transaction;
+use crate::common::{error::{AppError, AppErrorMessage}, structs::responses::{status::Status, user::User}};
+use crate::database::entity::audit_event::AuditAction;
+use crate::services::{audit::AuditContext, gateway::get_gateway};
+
+
+pub async fn delete_api_key(
+    connection: &DatabaseConnection, user: User, key_id: Uuid, audit: &AuditContext
+) -> Result<Status, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let status = async {
+        let status = gateway.api_key().delete(user.id, key_id).await?;
+        let changes = json!({ "api_key": { "before": key_id, "after": null } });
+        gateway.audit().record(audit, AuditAction::ApiKeyDelete, Some(user.id), Some(changes)).await?;
+
+        Ok::<_, AppError>(status)
+    }.await;
+
+    match status {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to delete an API key. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to delete an API key. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use sea_orm::DatabaseConnection;
+
+use crate::common::{error::AppError, structs::responses::{api_key::ApiKey, user::User}};
+use crate::services::gateway::get_gateway;
+
+
+pub async fn get_api_keys(connection: &DatabaseConnection, user: User) -> Result<Vec<ApiKey>, AppError> {
+    get_gateway(connection).api_key().get_many(user.id).await
+}
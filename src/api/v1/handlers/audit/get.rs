@@ -0,0 +1,21 @@
+use sea_orm::DatabaseConnection;
+
+use crate::common::structs::requests::audit::AuditQuery;
+use crate::common::{error::AppError, structs::responses::audit::AuditEventData};
+use crate::database::repositories::audit_event::AuditFilter;
+use crate::services::gateway::get_gateway;
+
+
+pub async fn get_audit_events(
+    connection: &DatabaseConnection, query: AuditQuery, offset: Option<u64>, limit: Option<u64>
+) -> Result<AuditEventData, AppError> {
+    let filter = AuditFilter {
+        actor_id: query.actor_id,
+        target_id: query.target_id,
+        action: query.action,
+        from: query.from,
+        to: query.to,
+    };
+
+    get_gateway(connection).audit().get_many(filter, offset, limit).await
+}
@@ -1,34 +1,171 @@
-use axum::{body::Body, http::{header, HeaderValue, Response, StatusCode}, response::IntoResponse, Json};
-use axum_extra::extract::cookie::{Cookie, SameSite};
-use sea_orm::DatabaseConnection;
-use time::OffsetDateTime;
+use std::net::IpAddr;
+
+use axum::{body::Body, http::{header, Response, StatusCode}, response::IntoResponse, Json};
+use chrono::TimeDelta;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use uuid::Uuid;
 
 use crate::{
+    api::{common::helpers::try_transaction, v1::handlers::auth::refresh::refresh_cookie},
     common::{error::{AppError, AppErrorMessage}, 
-    structs::{requests::user::LoginUser, responses::token::TokenType}}, 
-    services::{gateway::get_gateway, security::jwt::JWT}
+    structs::{requests::user::LoginUser, responses::token::{CustomClaims, TokenType}}}, 
+    core::config::LoginConfig,
+    database::entity::{audit_event::AuditAction, login_attempt::AttemptScope, user::Model},
+    services::{
+        audit::AuditContext, 
+        gateway::get_gateway, 
+        security::{jwt::{EmbeddedClaim, JWT}, permission::Permission}
+    }
 };
 use crate::services::security::hash::Argon2Hasher;
 
 
 
+/// Custom claims of an access token for the session `sid`. The user's role
+/// is only looked up when the token embeds it.
+pub async fn access_claims(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    user_id: Uuid,
+    sid: Uuid,
+) -> Result<CustomClaims, AppError> {
+    let mut claims = CustomClaims { sid: Some(sid), ..Default::default() };
+
+    if jwt.embeds(EmbeddedClaim::Role) || jwt.embeds(EmbeddedClaim::Scope) {
+        let user = get_gateway(connection).user().get(user_id).await?;
+        let scope = user.permissions.iter().map(Permission::as_str).collect::<Vec<_>>().join(" ");
+
+        claims.role = Some(user.role);
+        claims.scope = Some(scope);
+    }
+
+    Ok(claims)
+}
+
+/// Starts a session for the user and issues its access and refresh tokens.
+pub async fn issue_session(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    user: &Model,
+    device: Option<String>,
+    ip: Option<IpAddr>,
+    audit: &AuditContext,
+) -> Result<Response<Body>, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let started = async {
+        let started = gateway
+            .refresh_token()
+            .start(user.id, user.token_version, device, ip.map(|ip| ip.to_string()), jwt)
+            .await?;
+        gateway.audit().record(&audit.as_actor(user.id), AuditAction::Login, Some(user.id), None).await?;
+
+        Ok::<_, AppError>(started)
+    }.await;
+
+    let (session_id, exp, refresh) = match started {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to start a session. Commit error".into())?;
+            result
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to start a session. Rollback error".into())?;
+            return Err(error);
+        }
+    };
+    let (_, access) = jwt.create_token(
+        user.id.to_string(), 
+        TokenType::ACCESS, 
+        Uuid::new_v4(), 
+        user.token_version, 
+        None, 
+        access_claims(connection, jwt, user.id, session_id).await?
+    )?;
+
+    let mut response = (StatusCode::OK, Json(access)).into_response();
+    response.headers_mut().insert(header::SET_COOKIE, refresh_cookie(refresh.token, exp)?);
+
+    Ok(response)
+}
+
+/// Finishes the login of an authenticated user. Users with MFA enabled get
+/// a short-lived pending token to exchange for a session with their code.
+pub async fn complete_login(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    user: &Model,
+    device: Option<String>,
+    ip: Option<IpAddr>,
+    login_config: &LoginConfig,
+    audit: &AuditContext,
+) -> Result<Response<Body>, AppError> {
+    if get_gateway(connection).mfa().is_enabled(user.id).await? {
+        let (_, pending) = jwt.create_token(
+            user.id.to_string(), 
+            TokenType::MFA_PENDING, 
+            Uuid::new_v4(), 
+            user.token_version, 
+            Some(TimeDelta::seconds(login_config.mfa_token_expire_seconds)),
+            CustomClaims::default()
+        )?;
+
+        return Ok((StatusCode::OK, Json(pending)).into_response());
+    }
+
+    issue_session(connection, jwt, user, device, ip, audit).await
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn login_handler(
     connection: &DatabaseConnection,
     hasher: &Argon2Hasher,
     jwt: &JWT,
     login_user: LoginUser,
+    device: Option<String>,
+    login_config: &LoginConfig,
+    ip: Option<IpAddr>,
+    audit: &AuditContext,
 ) -> Result<Response<Body>, AppError> {
     
-    let user = get_gateway(connection)
+    let gateway = get_gateway(connection);
+    let attempts = gateway.login_attempt();
+
+    let mut keys = vec![(AttemptScope::Login, login_user.login.to_lowercase())];
+    if let Some(ip) = ip {
+        keys.push((AttemptScope::Ip, ip.to_string()));
+    }
+
+    attempts.check(&keys).await?;
+
+    let user = gateway
         .user()
         .reader
         .get_by_login(login_user.login.into_string())
         .await?;
 
+    let verified = user
+        .as_ref()
+        .is_some_and(|user| hasher.verify_password(&user.password, &login_user.password));
+
+    if !verified {
+        for (scope, key) in &keys {
+            attempts.register_failure(*scope, key.clone(), login_config).await?;
+        }
+    }
 
     if let Some(user) = user {
-        let verify = hasher.verify_password(&user.password, &login_user.password);
-        if !verify {
+        if !verified {
+            gateway.audit().record(audit, AuditAction::LoginFailed, Some(user.id), None).await?;
             return Err(AppError::BadRequestError(
                 AppErrorMessage { 
                     message: "Invalid password".into(), 
@@ -36,52 +173,27 @@ pub async fn login_handler(
                 }
             ));
         }
-        let user_id = user.id.to_string();
-        let (_, access) = jwt.create_token(user_id.clone(), TokenType::ACCESS, None)?;
-        let (exp, refresh) = jwt.create_token(user_id, TokenType::REFRESH, None)?;
-
-
-        let cookie = Cookie::build(("refresh", refresh.token))
-            .expires(
-                OffsetDateTime::from_unix_timestamp(exp as i64)
-                .map_err(|_| {
-                    AppError::InternalServerError(
-                        AppErrorMessage {
-                            message: "Failed to set expires token date".into(),
-                            details: None
-                        }
-                    )
-                })?
-            )
-            .path("/")
-            .max_age(time::Duration::seconds(exp as i64))
-            .same_site(SameSite::Lax)
-            .http_only(true)
-            .secure(true)
-            .build();
-
-        let mut response = (StatusCode::OK, Json(access)).into_response();
-        response.headers_mut().insert(
-            header::SET_COOKIE, 
-            HeaderValue::from_str(&cookie.to_string()).map_err(|_| {
-                AppError::InternalServerError(
-                    AppErrorMessage {
-                        message: "Could not set cookie".into(),
-                        details: None
-                    }
-                )
-            })?
-        );
-
-        Ok(response)
+        let (scope, key) = keys.swap_remove(0);
+        attempts.reset(scope, key).await?;
+
+        if login_config.require_verified_email && user.email_verified_at.is_none() {
+            return Err(AppError::ForbiddenError(
+                AppErrorMessage { 
+                    message: "Email is not verified".into(), 
+                    details: None 
+                }
+            ));
+        }
+
+        complete_login(connection, jwt, &user, device, ip, login_config, audit).await
         
     } else {
-        return Err(AppError::NotFoundError(
+        Err(AppError::NotFoundError(
             AppErrorMessage {
                 message: "User not found".into(),
                 details: None
             }
-        ));
+        ))
     }
 
 }
\ No newline at end of file
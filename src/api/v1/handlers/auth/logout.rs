@@ -1,12 +1,85 @@
 use axum::{body::Body, http::{header, HeaderValue, Response, StatusCode}, response::IntoResponse, Json};
-use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::{cookie::{Cookie, SameSite}, CookieJar};
+use sea_orm::{DatabaseConnection, TransactionTrait};
 use time::OffsetDateTime;
 
-use crate::common::{error::{AppError, AppErrorMessage}, structs::responses::status::Status};
+use crate::{
+    api::common::helpers::try_transaction,
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::responses::{status::Status, token::{TokenClaims, TokenType}, user::User}
+    }, 
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway, security::{cache::TokenCache, jwt::JWT}}
+};
 
 
 
-pub async fn logout_handler() -> Result<Response<Body>, AppError> {
+pub async fn logout_handler(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    token_cache: &TokenCache,
+    user: User,
+    access_claims: TokenClaims,
+    cookie_jar: CookieJar,
+    audit: &AuditContext,
+) -> Result<Response<Body>, AppError> {
+    let claims = cookie_jar
+        .get("refresh")
+        .and_then(|cookie| jwt.verify_token(cookie.value().to_string()).ok())
+        .filter(|claims| claims._type == TokenType::REFRESH && claims.sub == user.id.to_string());
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let ended = async {
+        gateway.revoked_token().revoke(&access_claims).await?;
+
+        let refresh_session = match claims {
+            Some(claims) => gateway
+                .refresh_token()
+                .reader
+                .get(claims.jti)
+                .await?
+                .filter(|record| record.user_id == user.id)
+                .map(|record| record.family_id),
+            None => None
+        };
+
+        let session_id = refresh_session.or(access_claims.custom.sid);
+        if let Some(session_id) = session_id {
+            gateway.refresh_token().end_session(session_id).await?;
+        }
+        gateway.audit().record(audit, AuditAction::Logout, Some(user.id), None).await?;
+
+        Ok::<_, AppError>(session_id)
+    }.await;
+
+    let session_id = match ended {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to log out. Commit error".into())?;
+            result
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to log out. Rollback error".into())?;
+            return Err(error);
+        }
+    };
+
+    token_cache.set_revoked(access_claims.jti, true);
+    if let Some(session_id) = session_id {
+        token_cache.set_session_revoked(session_id, true);
+    }
+
     let cookie = Cookie::build(("refresh", ""))
         .expires(
             OffsetDateTime::from_unix_timestamp(-1)
@@ -0,0 +1,149 @@
+use std::net::IpAddr;
+
+use axum::{body::Body, http::Response};
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use uuid::Uuid;
+
+use crate::{
+    api::{common::helpers::try_transaction, v1::handlers::auth::login::issue_session},
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::{
+            requests::mfa::{MfaCode, VerifyMfa}, 
+            responses::{mfa::MfaEnrollment, status::Status, token::TokenType, user::User}
+        }
+    }, 
+    core::config::{LoginConfig, MfaConfig},
+    database::entity::{audit_event::AuditAction, login_attempt::AttemptScope},
+    services::{
+        audit::AuditContext, 
+        gateway::get_gateway, 
+        security::{cipher::SecretCipher, hash::Argon2Hasher, jwt::JWT}
+    }
+};
+
+
+
+pub async fn enroll_mfa_handler(
+    connection: &DatabaseConnection,
+    hasher: &Argon2Hasher,
+    cipher: &SecretCipher,
+    mfa_config: &MfaConfig,
+    user: User,
+) -> Result<MfaEnrollment, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let enrollment = get_gateway(&transaction)
+        .mfa()
+        .enroll(user.id, &user.login, &mfa_config.issuer, cipher, hasher)
+        .await;
+
+    match enrollment {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to enroll MFA. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to enroll MFA. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+pub async fn confirm_mfa_handler(
+    connection: &DatabaseConnection,
+    cipher: &SecretCipher,
+    user: User,
+    data: MfaCode,
+    audit: &AuditContext,
+) -> Result<Status, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let confirmed = async {
+        gateway.mfa().confirm(user.id, &data.code, cipher).await?;
+        gateway.audit().record(audit, AuditAction::MfaEnable, Some(user.id), None).await?;
+
+        Ok::<_, AppError>(Status { status: true })
+    }.await;
+
+    match confirmed {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to confirm MFA. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to confirm MFA. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+/// Exchanges an `MFA_PENDING` token and a valid code for access and refresh tokens.
+/// Wrong codes count as failed logins, so guessing codes leads to the same lockout.
+#[allow(clippy::too_many_arguments)]
+pub async fn verify_mfa_handler(
+    connection: &DatabaseConnection,
+    hasher: &Argon2Hasher,
+    jwt: &JWT,
+    cipher: &SecretCipher,
+    data: VerifyMfa,
+    device: Option<String>,
+    ip: Option<IpAddr>,
+    login_config: &LoginConfig,
+    audit: &AuditContext,
+) -> Result<Response<Body>, AppError> {
+    let invalid_token = || AppError::UnAuthorizedError(
+        AppErrorMessage {
+            message: "Invalid token".into(),
+            details: None
+        }
+    );
+
+    let claims = jwt.verify_token(data.token.into_string())?;
+
+    if claims._type != TokenType::MFA_PENDING {
+        return Err(invalid_token());
+    }
+
+    let gateway = get_gateway(connection);
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid_token())?;
+    let user = gateway
+        .user()
+        .reader
+        .get(user_id)
+        .await?
+        .filter(|user| claims.token_version >= user.token_version)
+        .ok_or_else(invalid_token)?;
+
+    let attempts = gateway.login_attempt();
+    let key = (AttemptScope::Login, user.login.to_lowercase());
+    attempts.check(std::slice::from_ref(&key)).await?;
+
+    if let Err(error) = gateway.mfa().verify(user.id, &data.code, cipher, hasher).await {
+        attempts.register_failure(key.0, key.1, login_config).await?;
+        gateway.audit().record(audit, AuditAction::LoginFailed, Some(user.id), None).await?;
+        return Err(error);
+    }
+    attempts.reset(key.0, key.1).await?;
+
+    issue_session(connection, jwt, &user, device, ip, audit).await
+}
@@ -1,3 +1,7 @@
 pub mod login;
 pub mod logout;
-pub mod refresh;
\ No newline at end of file
+pub mod refresh;
+pub mod verify_email;
+pub mod password;
+pub mod mfa;
+pub mod oidc;
\ No newline at end of file
@@ -0,0 +1,308 @@
+use std::net::IpAddr;
+
+use axum::{
+    body::Body, 
+    http::{header, HeaderValue, Response}, 
+    response::{IntoResponse, Redirect}
+};
+use axum_extra::extract::{cookie::{Cookie, SameSite}, CookieJar};
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::{
+    api::{common::helpers::try_transaction, v1::handlers::auth::login::complete_login},
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::requests::oidc::OidcCallback
+    }, 
+    core::config::LoginConfig,
+    services::{
+        audit::AuditContext,
+        gateway::get_gateway, 
+        security::{cipher::SecretCipher, hash::Argon2Hasher, jwt::JWT, oidc::{OidcClient, OidcLogin}}
+    }
+};
+
+
+const LOGIN_COOKIE: &str = "oidc_login";
+
+
+fn cookie_header(cookie: Cookie) -> Result<HeaderValue, AppError> {
+    cookie.to_string().parse::<HeaderValue>().map_err(|_| {
+        AppError::InternalServerError(
+            AppErrorMessage {
+                message: "Could not set cookie".into(),
+                details: None
+            }
+        )
+    })
+}
+
+fn invalid_login() -> AppError {
+    AppError::BadRequestError(
+        AppErrorMessage { 
+            message: "Login state is invalid or expired. Start the login again".into(), 
+            details: None 
+        }
+    )
+}
+
+/// Redirects to the provider. The state, nonce and PKCE verifier travel in an encrypted cookie until the callback.
+pub async fn oidc_login_handler(
+    oidc: &OidcClient,
+    cipher: &SecretCipher,
+    provider: String,
+) -> Result<Response<Body>, AppError> {
+    let (url, login) = oidc.authorize(&provider).await?;
+    let value = cipher.encrypt(&serde_json::to_vec(&login).map_err(anyhow::Error::from)?)?;
+
+    let cookie = Cookie::build((LOGIN_COOKIE, value))
+        .path(oidc.cookie_path())
+        .max_age(time::Duration::seconds(login.exp - Utc::now().timestamp()))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(true)
+        .build();
+
+    let mut response = Redirect::to(&url).into_response();
+    response.headers_mut().insert(header::SET_COOKIE, cookie_header(cookie)?);
+
+    Ok(response)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn oidc_callback_handler(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    hasher: &Argon2Hasher,
+    oidc: &OidcClient,
+    cipher: &SecretCipher,
+    cookie_jar: CookieJar,
+    provider: String,
+    callback: OidcCallback,
+    device: Option<String>,
+    ip: Option<IpAddr>,
+    login_config: &LoginConfig,
+    audit: &AuditContext,
+) -> Result<Response<Body>, AppError> {
+    if let Some(error) = callback.error {
+        return Err(AppError::UnAuthorizedError(
+            AppErrorMessage { 
+                message: "Identity provider denied the login".into(), 
+                details: json!({ "error": error, "error_description": callback.error_description }).into() 
+            }
+        ));
+    }
+
+    let login = cookie_jar
+        .get(LOGIN_COOKIE)
+        .and_then(|cookie| cipher.decrypt(cookie.value()).ok())
+        .and_then(|plain| serde_json::from_slice::<OidcLogin>(&plain).ok())
+        .filter(|login| login.provider == provider && login.exp > Utc::now().timestamp())
+        .filter(|login| callback.state.as_deref() == Some(login.state.as_str()))
+        .ok_or_else(invalid_login)?;
+
+    let code = callback.code.ok_or_else(|| {
+        AppError::BadRequestError(AppErrorMessage { message: "Authorization code is missing".into(), details: None })
+    })?;
+
+    let identity = oidc.authenticate(&provider, &code, &login).await?;
+    let config = oidc.provider_config(&provider)?;
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let resolved = get_gateway(&transaction)
+        .user_identity()
+        .resolve(identity, config, hasher)
+        .await;
+
+    let user = match resolved {
+        Ok(user) => {
+            try_transaction(transaction.commit().await, "Failed to link an identity. Commit error".into())?;
+            user
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to link an identity. Rollback error".into())?;
+            return Err(error);
+        }
+    };
+
+    let mut response = complete_login(connection, jwt, &user, device, ip, login_config, audit).await?;
+
+    let cookie = Cookie::build((LOGIN_COOKIE, ""))
+        .path(oidc.cookie_path())
+        .max_age(time::Duration::ZERO)
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(true)
+        .build();
+    response.headers_mut().append(header::SET_COOKIE, cookie_header(cookie)?);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use axum::http::StatusCode;
+    use base64::prelude::*;
+    use jsonwebtoken::Algorithm;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::core::config::{MfaConfig, Secret, TokenConfig};
+    use crate::database::entity::{audit_event::{self, AuditAction}, mfa_secret, refresh_token, session, user, user_identity};
+    use crate::services::security::{cipher::get_cipher, hash::get_argon2_default, oidc::{get_oidc, tests::{MockProvider, PROVIDER}}};
+
+    struct Login {
+        provider: MockProvider,
+        oidc: Arc<OidcClient>,
+        cipher: Arc<SecretCipher>,
+        cookie_jar: CookieJar,
+        state: String,
+        nonce: String,
+    }
+
+    fn jwt() -> JWT {
+        let secret = BASE64_STANDARD.encode("secret-for-tests-0123456789abcdef").into_boxed_str();
+        JWT::new(TokenConfig { algorithm: "HS256".into(), secret_key: Some(Secret::new(secret)), ..Default::default() }).unwrap()
+    }
+
+    /// Starts a login and keeps the cookie it sets, like the browser would until the callback.
+    async fn start_login() -> Login {
+        let provider = MockProvider::start().await;
+        let oidc = get_oidc(&provider.config()).unwrap();
+        let key = BASE64_STANDARD.encode([7u8; 32]).into_boxed_str();
+        let cipher = get_cipher(&MfaConfig { encryption_key: Some(Secret::new(key)), ..Default::default() }).unwrap();
+
+        let response = oidc_login_handler(&oidc, &cipher, PROVIDER.into()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        let params: HashMap<String, String> = url::Url::parse(location).unwrap().query_pairs().into_owned().collect();
+        let cookie = Cookie::parse(response.headers()[header::SET_COOKIE].to_str().unwrap().to_owned()).unwrap();
+        assert_eq!(cookie.name(), LOGIN_COOKIE);
+
+        Login {
+            provider,
+            oidc,
+            cipher,
+            cookie_jar: CookieJar::new().add(cookie),
+            state: params["state"].clone(),
+            nonce: params["nonce"].clone(),
+        }
+    }
+
+    async fn callback(login: &Login, connection: &DatabaseConnection, state: &str) -> Result<Response<Body>, AppError> {
+        oidc_callback_handler(
+            connection,
+            &jwt(),
+            &get_argon2_default(),
+            &login.oidc,
+            &login.cipher,
+            login.cookie_jar.clone(),
+            PROVIDER.into(),
+            OidcCallback { code: Some("code".into()), state: Some(state.into()), error: None, error_description: None },
+            None,
+            None,
+            &LoginConfig::default(),
+            &AuditContext::default(),
+        ).await
+    }
+
+    #[tokio::test]
+    async fn callback_logs_in_the_linked_user() {
+        let login = start_login().await;
+        login.provider.issue(&login.provider.claims(&login.nonce), Algorithm::RS256);
+
+        let now = Utc::now();
+        let user_id = Uuid::new_v4();
+        let user = user::Model {
+            id: user_id,
+            login: "alice".into(),
+            password: "".into(),
+            email: Some("alice@example.com".into()),
+            email_verified_at: Some(now),
+            role_id: Uuid::new_v4(),
+            token_version: 1,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+        let identity = user_identity::Model {
+            id: Uuid::new_v4(), user_id, provider: PROVIDER.into(), subject: "subject-1".into(), email: None, created_at: now
+        };
+        let refresh = refresh_token::Model {
+            id: Uuid::new_v4(), user_id, family_id: Uuid::new_v4(), device: None, expires_at: now, used_at: None, revoked_at: None, created_at: now
+        };
+        let session = session::Model {
+            id: refresh.family_id, user_id, device: None, ip: None, token_version: 1, expires_at: now, last_used_at: now, revoked_at: None, created_at: now
+        };
+        let event = audit_event::Model {
+            id: Uuid::new_v4(), actor_id: Some(user_id), action: AuditAction::Login, target_id: Some(user_id), changes: None,
+            ip: None, user_agent: None, request_id: None, created_at: now
+        };
+        // Identity and user are resolved, MFA is off, then the refresh token, session and audit event are stored
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[identity]])
+            .append_query_results([[user]])
+            .append_query_results([Vec::<mfa_secret::Model>::new()])
+            .append_query_results([[refresh]])
+            .append_query_results([[session]])
+            .append_query_results([[event]])
+            .into_connection();
+
+        let response = callback(&login, &connection, &login.state).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let cookies: Vec<&str> = response.headers().get_all(header::SET_COOKIE).iter().map(|value| value.to_str().unwrap()).collect();
+        assert!(cookies.iter().any(|cookie| cookie.starts_with("refresh=")));
+        assert!(cookies.iter().any(|cookie| cookie.starts_with(&format!("{LOGIN_COOKIE}=;"))));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let token: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(token["typ"], "ACCESS");
+        assert_eq!(jwt().verify_token(token["token"].as_str().unwrap().into()).unwrap().sub, user_id.to_string());
+
+        let log = format!("{:?}", connection.into_transaction_log());
+        assert!(log.contains("subject-1"));
+    }
+
+    #[tokio::test]
+    async fn callback_rejects_a_state_that_does_not_match() {
+        let login = start_login().await;
+        login.provider.issue(&login.provider.claims(&login.nonce), Algorithm::RS256);
+        let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        assert!(matches!(callback(&login, &connection, "other").await, Err(AppError::BadRequestError(_))));
+
+        let without_cookie = Login { cookie_jar: CookieJar::new(), ..login };
+        assert!(matches!(callback(&without_cookie, &connection, &without_cookie.state).await, Err(AppError::BadRequestError(_))));
+
+        // Rejected before anything reached the database
+        assert!(connection.into_transaction_log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn callback_rejects_an_id_token_for_another_login() {
+        let login = start_login().await;
+        login.provider.issue(&login.provider.claims("other"), Algorithm::RS256);
+        let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        assert!(matches!(callback(&login, &connection, &login.state).await, Err(AppError::UnAuthorizedError(_))));
+        assert!(connection.into_transaction_log().is_empty());
+    }
+}
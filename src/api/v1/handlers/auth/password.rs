@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use log::warn;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use tokio_util::task::TaskTracker;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    api::common::helpers::try_transaction,
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::{requests::user::{ForgotPassword, ResetPassword}, responses::status::Status}
+    }, 
+    core::config::{MailConfig, PasswordConfig},
+    database::{entity::audit_event::AuditAction, repositories::user::UpdateUser},
+    services::{
+        audit::{AuditContext, REDACTED}, 
+        gateway::get_gateway, 
+        mailer::{spawn_send, Mailer}, 
+        security::{hash::Argon2Hasher, password::check_password_policy}
+    }
+};
+
+
+
+/// Always succeeds for well-formed requests, so the response doesn't reveal which logins exist.
+/// The login is looked up in the background, so neither does the response time. The link goes
+/// to the user's email, or to the login itself when it is an email address, and is mailed once
+/// the token is committed.
+pub async fn forgot_password_handler(
+    connection: Arc<DatabaseConnection>,
+    mailer: Arc<dyn Mailer>,
+    tasks: &TaskTracker,
+    mail_config: MailConfig,
+    data: ForgotPassword,
+) -> Result<Status, AppError> {
+    let tracker = tasks.clone();
+    tasks.spawn(async move {
+        // Failures only happen for existing logins, so they are logged instead of answered
+        if let Err(error) = issue_password_reset(&connection, mailer, &tracker, &mail_config, data).await {
+            warn!("Failed to issue a password reset: {}", error);
+        }
+    });
+
+    Ok(Status { status: true })
+}
+
+async fn issue_password_reset(
+    connection: &DatabaseConnection,
+    mailer: Arc<dyn Mailer>,
+    tasks: &TaskTracker,
+    mail_config: &MailConfig,
+    data: ForgotPassword,
+) -> Result<(), AppError> {
+    let user = get_gateway(connection)
+        .user()
+        .reader
+        .get_by_login(data.login.into_string())
+        .await?;
+
+    let recipient = user.and_then(|user| {
+        let to = user.email.clone().or_else(|| {
+            user.login.parse::<lettre::Address>().ok().map(|_| user.login.clone())
+        })?;
+        Some((user.id, to))
+    });
+
+    let Some((user_id, to)) = recipient else {
+        return Ok(());
+    };
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let issued = get_gateway(&transaction)
+        .password_reset()
+        .issue(user_id, to, mail_config)
+        .await;
+
+    match issued {
+        Ok(mail) => {
+            try_transaction(transaction.commit().await, "Failed to issue a reset token. Commit error".into())?;
+            spawn_send(tasks, mailer, mail);
+            Ok(())
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to issue a reset token. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+/// Sets the new password and bumps the token version, so every token issued before stops working.
+pub async fn reset_password_handler(
+    connection: &DatabaseConnection,
+    hasher: &Argon2Hasher,
+    password_config: &PasswordConfig,
+    data: ResetPassword,
+    audit: &AuditContext,
+) -> Result<Uuid, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let reset = async {
+        let record = gateway.password_reset().redeem(&data.token).await?;
+        let users = gateway.user();
+        let login = users.reader.get(record.user_id).await?.map(|user| user.login).unwrap_or_default();
+        check_password_policy(&data.password, &login, password_config)?;
+
+        users.writer.update(
+            UpdateUser {
+                id: record.user_id,
+                login: None,
+                password: Some(hasher.hash_password(&data.password)?),
+                email: None,
+                email_verified_at: None,
+                role_id: None
+            }
+        ).await?;
+        users.writer.bump_token_version(record.user_id).await?;
+
+        let changes = json!({ "password": { "before": REDACTED, "after": REDACTED } });
+        gateway.audit().record(&audit.as_actor(record.user_id), AuditAction::PasswordReset, Some(record.user_id), Some(changes)).await?;
+
+        Ok::<_, AppError>(record.user_id)
+    }.await;
+
+    match reset {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to reset a password. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to reset a password. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
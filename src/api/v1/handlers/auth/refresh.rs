@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use axum::{
     body::Body, 
     http::{
@@ -6,22 +8,59 @@ use axum::{
     response::IntoResponse, Json
 };
 use axum_extra::extract::{cookie::{Cookie, SameSite}, CookieJar};
+use sea_orm::{DatabaseConnection, TransactionTrait};
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use crate::{
+    api::{common::helpers::try_transaction, v1::handlers::auth::login::access_claims},
     common::{
         error::{AppError, AppErrorMessage}, 
         structs::responses::token::TokenType
     }, 
-    services::security::jwt::JWT
+    services::{gateway::get_gateway, security::jwt::JWT}
 };
 
 
 
 
+/// Builds the `Set-Cookie` value carrying a refresh token that expires at `exp`.
+pub fn refresh_cookie(token: String, exp: usize) -> Result<HeaderValue, AppError> {
+    let cookie = Cookie::build(("refresh", token))
+        .expires(
+            OffsetDateTime::from_unix_timestamp(exp as i64)
+            .map_err(|_| {
+                AppError::InternalServerError(
+                    AppErrorMessage {
+                        message: "Failed to set expires token date".into(),
+                        details: None
+                    }
+                )
+            })?
+        )
+        .path("/")
+        .max_age(time::Duration::seconds(exp as i64))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(true)
+        .build();
+
+    cookie.to_string().parse::<HeaderValue>().map_err(|_| {
+        AppError::InternalServerError(
+            AppErrorMessage {
+                message: "Could not set cookie".into(),
+                details: None
+            }
+        )
+    })
+}
+
 pub async fn refresh_handler(
+    connection: &DatabaseConnection,
     jwt: &JWT,
     cookie_jar: CookieJar,
+    device: Option<String>,
+    ip: Option<IpAddr>,
 ) -> Result<Response<Body>, AppError> {
     let token = cookie_jar
         .get("refresh")
@@ -48,41 +87,140 @@ pub async fn refresh_handler(
         ));
     }
 
-    let (_, access) = jwt.create_token(claims.sub.clone(), TokenType::ACCESS, None)?;
-    let (exp, refresh) = jwt.create_token(claims.sub, TokenType::REFRESH, None)?;
+    let gateway = get_gateway(connection);
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| {
+            AppError::UnAuthorizedError(
+                AppErrorMessage { message: "Invalid token".into(), details: None}
+            )
+        })?;
+    let user = gateway
+        .user()
+        .reader
+        .get(user_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::UnAuthorizedError(
+                AppErrorMessage { message: "Invalid token".into(), details: None}
+            )
+        })?;
+
+    let record = gateway.refresh_token().check(&claims, user.token_version).await?;
+    let family_id = record.family_id;
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let rotated = get_gateway(&transaction)
+        .refresh_token()
+        .rotate(record, user.token_version, device, ip.map(|ip| ip.to_string()), jwt)
+        .await;
+
+    let (exp, refresh) = match rotated {
+        Ok(Some(result)) => {
+            try_transaction(transaction.commit().await, "Failed to refresh a token. Commit error".into())?;
+            result
+        },
+        Ok(None) => {
+            try_transaction(transaction.commit().await, "Failed to revoke a session. Commit error".into())?;
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage { message: "Token was revoked. Try to login again".into(), details: None }
+            ));
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to refresh a token. Rollback error".into())?;
+            return Err(error);
+        }
+    };
+
+    let (_, access) = jwt.create_token(
+        claims.sub, 
+        TokenType::ACCESS, 
+        Uuid::new_v4(), 
+        user.token_version, 
+        None, 
+        access_claims(connection, jwt, user.id, family_id).await?
+    )?;
 
-    let cookie = Cookie::build(("refresh", refresh.token))
-        .expires(
-            OffsetDateTime::from_unix_timestamp(exp as i64)
-            .map_err(|_| {
-                AppError::InternalServerError(
-                    AppErrorMessage {
-                        message: "Failed to set expires token date".into(),
-                        details: None
-                    }
-                )
-            })?
-        )
-        .path("/")
-        .max_age(time::Duration::seconds(exp as i64))
-        .same_site(SameSite::Lax)
-        .http_only(true)
-        .secure(true)
-        .build();
-    
     let mut response = (StatusCode::OK, Json(access)).into_response();
-    response.headers_mut().insert(
-        header::SET_COOKIE,
-        cookie.to_string().parse::<HeaderValue>().map_err( |_|
-            AppError::InternalServerError(
-                AppErrorMessage {
-                    message: "Could not set cookie".into(),
-                    details: None
-                }
-            )
-        )?
-    );
+    response.headers_mut().insert(header::SET_COOKIE, refresh_cookie(refresh.token, exp)?);
 
     Ok(response)
 
-}
\ No newline at end of file
+}
+
+/// Moves the session behind the request's refresh cookie onto the user's current `token_version`,
+/// so it survives a change that revoked every other session. The client has to refresh its access token.
+pub async fn keep_session(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    cookie_jar: &CookieJar,
+    user_id: Uuid,
+    device: Option<String>,
+    ip: Option<IpAddr>,
+) -> Result<Option<HeaderValue>, AppError> {
+    let claims = cookie_jar
+        .get("refresh")
+        .and_then(|cookie| jwt.verify_token(cookie.value().to_string()).ok())
+        .filter(|claims| claims._type == TokenType::REFRESH && claims.sub == user_id.to_string());
+
+    let Some(claims) = claims else {
+        return Ok(None);
+    };
+
+    let gateway = get_gateway(connection);
+    let record = gateway
+        .refresh_token()
+        .reader
+        .get(claims.jti)
+        .await?
+        .filter(|record| record.user_id == user_id && record.is_active());
+    let user = gateway.user().reader.get(user_id).await?;
+
+    let (Some(record), Some(user)) = (record, user) else {
+        return Ok(None);
+    };
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let rotated = get_gateway(&transaction)
+        .refresh_token()
+        .rotate(record, user.token_version, device, ip.map(|ip| ip.to_string()), jwt)
+        .await;
+
+    let (exp, refresh) = match rotated {
+        Ok(Some(result)) => {
+            try_transaction(transaction.commit().await, "Failed to keep a session. Commit error".into())?;
+            result
+        },
+        Ok(None) => {
+            try_transaction(transaction.commit().await, "Failed to revoke a session. Commit error".into())?;
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage { message: "Token was revoked. Try to login again".into(), details: None }
+            ));
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to keep a session. Rollback error".into())?;
+            return Err(error);
+        }
+    };
+
+    Ok(Some(refresh_cookie(refresh.token, exp)?))
+}
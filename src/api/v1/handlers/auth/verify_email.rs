@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use log::warn;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use tokio_util::task::TaskTracker;
+
+use crate::{
+    api::common::helpers::try_transaction,
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::{requests::user::{ResendVerification, VerifyEmail}, responses::status::Status}
+    }, 
+    core::config::MailConfig,
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway, mailer::{spawn_send, Mailer}, security::jwt::JWT}
+};
+
+
+
+pub async fn verify_email_handler(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    data: VerifyEmail,
+    audit: &AuditContext,
+) -> Result<Status, AppError> {
+    let claims = jwt.verify_token(data.token.into_string())?;
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let verified = async {
+        let record = gateway.email_verification().redeem(&claims).await?;
+        let rows = gateway.user().writer.verify_email(record.user_id, record.email).await?;
+
+        if rows == 0 {
+            return Err(AppError::BadRequestError(
+                AppErrorMessage {
+                    message: "Email was changed after the token was issued".into(),
+                    details: None
+                }
+            ));
+        }
+        gateway.audit().record(&audit.as_actor(record.user_id), AuditAction::EmailVerify, Some(record.user_id), None).await?;
+
+        Ok(Status { status: true })
+    }.await;
+
+    match verified {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to verify an email. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to verify an email. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+/// Always succeeds for well-formed requests, so the response doesn't reveal which emails are registered.
+/// The link is mailed in the background once the token is committed.
+pub async fn resend_verification_handler(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    mailer: Arc<dyn Mailer>,
+    tasks: &TaskTracker,
+    mail_config: &MailConfig,
+    data: ResendVerification,
+) -> Result<Status, AppError> {
+    // Failures only happen for registered emails, so they are logged instead of answered
+    if let Err(error) = issue_verification(connection, jwt, mailer, tasks, mail_config, data).await {
+        warn!("Failed to resend a verification: {}", error);
+    }
+
+    Ok(Status { status: true })
+}
+
+async fn issue_verification(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    mailer: Arc<dyn Mailer>,
+    tasks: &TaskTracker,
+    mail_config: &MailConfig,
+    data: ResendVerification,
+) -> Result<(), AppError> {
+    let email = data.email.trim().to_lowercase();
+    let user = get_gateway(connection)
+        .user()
+        .reader
+        .get_by_email(email.clone())
+        .await?
+        .filter(|user| user.email_verified_at.is_none());
+
+    let Some(user) = user else {
+        return Ok(());
+    };
+
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let issued = get_gateway(&transaction)
+        .email_verification()
+        .issue(user.id, email, jwt, mail_config)
+        .await;
+
+    match issued {
+        Ok(mail) => {
+            try_transaction(transaction.commit().await, "Failed to resend a verification. Commit error".into())?;
+            spawn_send(tasks, mailer, mail);
+            Ok(())
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to resend a verification. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
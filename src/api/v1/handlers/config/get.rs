@@ -0,0 +1,13 @@
+use serde_json::Value;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::Config;
+
+
+/// Secret fields serialize as `[redacted]`, so the result is safe to hand out.
+pub fn get_config(config: &Config) -> Result<Value, AppError> {
+    serde_json::to_value(config).map_err(|error| AppError::InternalServerError(AppErrorMessage {
+        message: "Failed to serialize configuration".into(),
+        details: Some(error.to_string().into())
+    }))
+}
@@ -1,2 +1,7 @@
 pub mod user;
-pub mod auth;
\ No newline at end of file
+pub mod role;
+pub mod auth;
+pub mod api_key;
+pub mod session;
+pub mod audit;
+pub mod config;
\ No newline at end of file
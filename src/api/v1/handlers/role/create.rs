@@ -0,0 +1,50 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::{
+    api::common::helpers::try_transaction, 
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::{requests::role::CreateRole, responses::{role::Role, user::User}}
+    }, 
+    database::entity::audit_event::AuditAction,
+    services::{audit::{diff, AuditContext}, gateway::get_gateway}
+};
+
+
+
+pub async fn create_role(
+    connection: &DatabaseConnection, 
+    actor: User,
+    data: CreateRole, 
+    audit: &AuditContext,
+) -> Result<Role, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| AppError::BadRequestError(
+            AppErrorMessage { 
+                message: "Failed to open transaction".into(), 
+                details: None 
+            }))?;
+    let gateway = get_gateway(&transaction);
+
+    let role = async {
+        let role = gateway.role().create(data, &actor.permissions).await?;
+        let changes = diff(None, Some(&json!({ "name": role.name, "permissions": role.permissions })));
+        gateway.audit().record(audit, AuditAction::RoleCreate, Some(role.id), changes).await?;
+
+        Ok::<_, AppError>(role)
+    }.await;
+
+    match role {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to create a role. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to create a role. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
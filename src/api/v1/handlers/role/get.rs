@@ -0,0 +1,11 @@
+use sea_orm::DatabaseConnection;
+
+use crate::common::{error::AppError, structs::responses::role::Role};
+use crate::services::gateway::get_gateway;
+
+
+pub async fn get_many_roles(connection: &DatabaseConnection) -> Result<Vec<Role>, AppError> {
+    let gw = get_gateway(connection);
+
+    gw.role().get_many().await
+}
@@ -0,0 +1,49 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::api::common::helpers::try_transaction;
+use crate::common::error::AppErrorMessage;
+use crate::common::structs::requests::role::UpdateRolePermissions;
+use crate::common::{error::AppError, structs::responses::{role::Role, user::User}};
+use crate::database::entity::audit_event::AuditAction;
+use crate::services::{audit::AuditContext, gateway::get_gateway};
+
+
+pub async fn update_role_permissions(
+    connection: &DatabaseConnection, actor: User, role_id: Uuid, data: UpdateRolePermissions, audit: &AuditContext
+) -> Result<Role, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gw = get_gateway(&transaction);
+
+    let role = async {
+        let (before, role) = gw.role().update_permissions(role_id, data, &actor.permissions).await?;
+
+        if before != role.permissions {
+            let changes = json!({ "permissions": { "before": before, "after": role.permissions } });
+            gw.audit().record(audit, AuditAction::RolePermissionsChange, Some(role_id), Some(changes)).await?;
+        }
+
+        Ok::<_, AppError>(role)
+    }.await;
+
+    match role {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to update a role. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to update a role. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    api::common::helpers::try_transaction, 
+    common::{error::{AppError, AppErrorMessage}, structs::responses::status::Status}, 
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway, security::cache::TokenCache}
+};
+
+
+pub async fn revoke_session(
+    connection: &DatabaseConnection,
+    token_cache: &TokenCache,
+    user_id: Uuid,
+    session_id: Uuid,
+    audit: &AuditContext,
+) -> Result<Status, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let gateway = get_gateway(&transaction);
+
+    let status = async {
+        let status = gateway.session().revoke(user_id, session_id).await?;
+        let changes = json!({ "session": { "before": session_id, "after": null } });
+        gateway.audit().record(audit, AuditAction::SessionRevoke, Some(user_id), Some(changes)).await?;
+
+        Ok::<_, AppError>(status)
+    }.await;
+
+    match status {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to revoke a session. Commit error".into())?;
+            token_cache.set_session_revoked(session_id, true);
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to revoke a session. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+/// Logs the user out on every device, including the one making the request.
+pub async fn revoke_sessions(
+    connection: &DatabaseConnection,
+    token_cache: &TokenCache,
+    user_id: Uuid,
+    audit: &AuditContext,
+) -> Result<Status, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let gateway = get_gateway(&transaction);
+
+    let status = async {
+        let status = gateway.session().revoke_all(user_id).await?;
+        gateway.audit().record(audit, AuditAction::SessionRevokeAll, Some(user_id), None).await?;
+
+        Ok::<_, AppError>(status)
+    }.await;
+
+    match status {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to revoke sessions. Commit error".into())?;
+            token_cache.invalidate_user(user_id);
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to revoke sessions. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
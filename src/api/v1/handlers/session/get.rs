@@ -0,0 +1,43 @@
+use axum_extra::extract::CookieJar;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::common::{
+    error::AppError, 
+    structs::responses::{session::ActiveSession, token::{TokenClaims, TokenType}}
+};
+use crate::services::{gateway::get_gateway, security::jwt::JWT};
+
+
+/// Session of the request, from the `sid` claim or else from the refresh cookie.
+pub async fn current_session(
+    connection: &DatabaseConnection,
+    jwt: &JWT,
+    cookie_jar: &CookieJar,
+    claims: &TokenClaims,
+) -> Result<Option<Uuid>, AppError> {
+    if claims.custom.sid.is_some() {
+        return Ok(claims.custom.sid);
+    }
+
+    let refresh = cookie_jar
+        .get("refresh")
+        .and_then(|cookie| jwt.verify_token(cookie.value().to_string()).ok())
+        .filter(|refresh| refresh._type == TokenType::REFRESH && refresh.sub == claims.sub);
+
+    let Some(refresh) = refresh else {
+        return Ok(None);
+    };
+
+    let record = get_gateway(connection).refresh_token().reader.get(refresh.jti).await?;
+
+    Ok(record.map(|record| record.family_id))
+}
+
+pub async fn get_sessions(
+    connection: &DatabaseConnection,
+    user_id: Uuid,
+    current: Option<Uuid>,
+) -> Result<Vec<ActiveSession>, AppError> {
+    get_gateway(connection).session().get_many(user_id, current).await
+}
@@ -1,19 +1,42 @@
+use std::sync::Arc;
+
 use sea_orm::{DatabaseConnection, TransactionTrait};
+use tokio_util::task::TaskTracker;
 
 use crate::{
     api::common::helpers::try_transaction, 
     common::{error::{AppError, AppErrorMessage}, structs::requests::user::CreateUser}, 
-    services::{gateway::get_gateway, security::hash::Argon2Hasher}
+    core::config::Config,
+    database::entity::audit_event::AuditAction,
+    services::{
+        audit::{diff, user_snapshot, AuditContext}, 
+        gateway::get_gateway, mailer::{spawn_send, Mailer}, security::{hash::Argon2Hasher, jwt::JWT}
+    }
 };
 use crate::common::structs::responses::user::User;
 
 
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_user(
     connection: &DatabaseConnection, 
     data: CreateUser, 
-    hasher: &Argon2Hasher
+    hasher: &Argon2Hasher,
+    jwt: &JWT,
+    mailer: Arc<dyn Mailer>,
+    tasks: &TaskTracker,
+    config: &Config,
+    audit: &AuditContext,
 ) -> Result<User, AppError> {
+    if config.login.require_verified_email && data.email.is_none() {
+        return Err(AppError::BadRequestError(
+            AppErrorMessage { 
+                message: "Email is required".into(), 
+                details: None 
+            }
+        ));
+    }
+
     let transaction = connection
         .begin()
         .await
@@ -24,11 +47,24 @@ pub async fn create_user(
             }))?;
     let gateway = get_gateway(&transaction);
 
-    let user = gateway.user().create(data, hasher).await;
+    let user = async {
+        let user = gateway.user().create(data, hasher, &config.password).await?;
+        gateway.audit().record(audit, AuditAction::UserCreate, Some(user.id), diff(None, Some(&user_snapshot(&user)))).await?;
+
+        let mail = match user.email.clone() {
+            Some(email) => Some(gateway.email_verification().issue(user.id, email, jwt, &config.mail).await?),
+            None => None,
+        };
+
+        Ok::<_, AppError>((user, mail))
+    }.await;
 
     match user {
-        Ok(result) => {
+        Ok((result, mail)) => {
             try_transaction(transaction.commit().await, "Failed to create a user. Commit error".into())?;
+            if let Some(mail) = mail {
+                spawn_send(tasks, mailer, mail);
+            }
             Ok(result)
         },
         Err(error) => {
@@ -36,4 +72,4 @@ pub async fn create_user(
             Err(error)
         }
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,8 @@
 use sea_orm::{DatabaseConnection, TransactionTrait};
-use uuid::Uuid;
+use serde_json::json;
 
 use crate::{
-    api::common::helpers::try_transaction, 
+    api::{common::helpers::try_transaction, v1::middlewares::permission::check_permission}, 
     common::{
         error::{
             AppError, AppErrorMessage
@@ -13,7 +13,8 @@ use crate::{
             status::Status, user::User
         }
     }}, 
-    database::entity::user::Role, services::gateway::get_gateway
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway, security::permission::Permission}
 };
 
 
@@ -21,14 +22,16 @@ pub async fn delete_user_handler(
     connection: &DatabaseConnection,
     user: User,
     body: DeleteUser,
+    audit: &AuditContext,
 ) -> Result<Status, AppError> {
-    let user_id: Uuid;
-
-    if user.role == Role::Admin {
-        user_id = body.id.or(Some(user.id)).expect("User id did not provided");
-    } else {
-        user_id = user.id;
-    }
+    let user_id = match body.id {
+        Some(id) if id != user.id => {
+            check_permission(&user, Permission::UsersDelete)?;
+            get_gateway(connection).user().ensure_outranks(id, &user.permissions).await?;
+            id
+        },
+        _ => user.id
+    };
 
     let transaction = connection
         .begin()
@@ -41,7 +44,15 @@ pub async fn delete_user_handler(
                 })
         })?;
     
-    let status = get_gateway(connection).user().delete(user_id).await;
+    let gateway = get_gateway(&transaction);
+
+    let status = async {
+        let status = gateway.user().delete(user_id).await?;
+        let changes = json!({ "deleted": { "before": false, "after": true } });
+        gateway.audit().record(audit, AuditAction::UserDelete, Some(user_id), Some(changes)).await?;
+
+        Ok::<_, AppError>(status)
+    }.await;
     
     match status {
         Ok(result) => {
@@ -1,4 +1,6 @@
 pub mod create;
 pub mod get;
 pub mod update;
-pub mod delete;
\ No newline at end of file
+pub mod delete;
+pub mod restore;
+pub mod role;
\ No newline at end of file
@@ -0,0 +1,49 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    api::common::helpers::try_transaction, 
+    common::{error::{AppError, AppErrorMessage}, structs::responses::user::User}, 
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway}
+};
+
+
+pub async fn restore_user_handler(
+    connection: &DatabaseConnection,
+    user_id: Uuid,
+    audit: &AuditContext,
+) -> Result<User, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+
+    let gateway = get_gateway(&transaction);
+
+    let user = async {
+        let user = gateway.user().restore(user_id).await?;
+        let changes = json!({ "deleted": { "before": true, "after": false } });
+        gateway.audit().record(audit, AuditAction::UserRestore, Some(user_id), Some(changes)).await?;
+
+        Ok::<_, AppError>(user)
+    }.await;
+
+    match user {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to restore a user. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to restore a user. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
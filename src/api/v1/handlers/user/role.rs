@@ -0,0 +1,56 @@
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    api::common::helpers::try_transaction, 
+    common::{
+        error::{AppError, AppErrorMessage}, 
+        structs::{requests::user::UpdateUserRole, responses::user::User}
+    }, 
+    database::entity::audit_event::AuditAction,
+    services::{audit::AuditContext, gateway::get_gateway}
+};
+
+
+pub async fn change_user_role_handler(
+    connection: &DatabaseConnection,
+    actor: User,
+    user_id: Uuid,
+    data: UpdateUserRole,
+    audit: &AuditContext,
+) -> Result<User, AppError> {
+    let transaction = connection
+        .begin()
+        .await
+        .map_err(|_| {
+            AppError::BadRequestError(
+                AppErrorMessage { 
+                    message: "Failed to open transaction".into(), 
+                    details: None 
+                })
+        })?;
+    let gateway = get_gateway(&transaction);
+
+    let user = async {
+        let (before, after) = gateway.user().change_role(user_id, &data.role, &actor.permissions).await?;
+
+        if before.role != after.role {
+            let changes = json!({ "role": { "before": before.role, "after": after.role } });
+            gateway.audit().record(audit, AuditAction::UserRoleChange, Some(user_id), Some(changes)).await?;
+        }
+
+        Ok::<_, AppError>(after)
+    }.await;
+
+    match user {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to change a role. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to change a role. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
@@ -1,24 +1,66 @@
+use std::sync::Arc;
+
 use sea_orm::{DatabaseConnection, TransactionTrait};
-use uuid::Uuid;
+use tokio_util::task::TaskTracker;
+use serde_json::json;
 
 use crate::api::common::helpers::try_transaction;
 use crate::common::error::AppErrorMessage;
 use crate::common::structs::requests::user::UpdateUser;
 use crate::common::{error::AppError, structs::responses::user::User};
-use crate::database::entity::user::Role;
+use crate::api::v1::middlewares::permission::check_permission;
+use crate::core::config::Config;
+use crate::database::entity::{audit_event::AuditAction, login_attempt::AttemptScope};
+use crate::services::audit::{diff, user_snapshot, AuditContext, REDACTED};
 use crate::services::gateway::get_gateway;
+use crate::services::mailer::{spawn_send, Mailer};
 use crate::services::security::hash::Argon2Hasher;
+use crate::services::security::jwt::JWT;
+use crate::services::security::permission::Permission;
 
 
+#[allow(clippy::too_many_arguments)]
 pub async fn update_user(
-    connection: &DatabaseConnection, user: User, data: UpdateUser, hasher: &Argon2Hasher
+    connection: &DatabaseConnection, 
+    user: User, 
+    data: UpdateUser, 
+    hasher: &Argon2Hasher, 
+    jwt: &JWT, 
+    mailer: Arc<dyn Mailer>, 
+    tasks: &TaskTracker,
+    config: &Config,
+    audit: &AuditContext,
 ) -> Result<User, AppError> {
-    let user_id: Uuid;
+    let user_id = match data.id {
+        Some(id) if id != user.id => {
+            check_permission(&user, Permission::UsersWrite)?;
+            get_gateway(connection).user().ensure_outranks(id, &user.permissions).await?;
+            id
+        },
+        _ => user.id
+    };
+
+    // Counted against the login's lockout, or a stolen token could guess the password here unthrottled
+    if user_id == user.id && (data.login.is_some() || data.password.is_some() || data.email.is_some()) {
+        let gateway = get_gateway(connection);
+        let attempts = gateway.login_attempt();
+        let key = user.login.to_lowercase();
+        attempts.check(&[(AttemptScope::Login, key.clone())]).await?;
 
-    if user.role == Role::Admin {
-        user_id = data.id.or(Some(user.id)).expect("User id did not provided");
-    } else {
-        user_id = user.id;
+        let verified = gateway
+            .user()
+            .verify_current_password(user.id, data.current_password.as_deref(), hasher)
+            .await;
+
+        match verified {
+            Ok(()) => attempts.reset(AttemptScope::Login, key).await?,
+            Err(error) => {
+                if data.current_password.is_some() {
+                    attempts.register_failure(AttemptScope::Login, key, &config.login).await?;
+                }
+                return Err(error);
+            }
+        }
     }
 
     let transaction = connection
@@ -33,11 +75,33 @@ pub async fn update_user(
         })?;
     let gw = get_gateway(&transaction);
 
-    let user = gw.user().update(user_id, data, hasher).await;
+    let email_changed = data.email.is_some();
+    let password_changed = data.password.is_some();
+
+    let user = async {
+        let before = gw.user().get(user_id).await?;
+        let user = gw.user().update(user_id, data, hasher, &config.password).await?;
+
+        let mut changes = diff(Some(&user_snapshot(&before)), Some(&user_snapshot(&user)));
+        if password_changed {
+            changes.get_or_insert_with(|| json!({}))["password"] = json!({ "before": REDACTED, "after": REDACTED });
+        }
+        gw.audit().record(audit, AuditAction::UserUpdate, Some(user.id), changes).await?;
+
+        let mail = match (email_changed, user.email.clone()) {
+            (true, Some(email)) => Some(gw.email_verification().issue(user.id, email, jwt, &config.mail).await?),
+            _ => None,
+        };
+
+        Ok::<_, AppError>((user, mail))
+    }.await;
 
     match user {
-        Ok(result) => {
+        Ok((result, mail)) => {
             try_transaction(transaction.commit().await, "Failed to update a user. Commit error".into())?;
+            if let Some(mail) = mail {
+                spawn_send(tasks, mailer, mail);
+            }
             Ok(result)
         },
         Err(error) => {
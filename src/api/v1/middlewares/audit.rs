@@ -0,0 +1,31 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use axum::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+
+use crate::api::common::middlewares::request_id::RequestId;
+use crate::api::v1::endpoints::auth::device;
+use crate::common::structs::responses::user::User;
+use crate::services::audit::AuditContext;
+
+
+/// Collects the audit context of a request. The actor is the `User` the `auth` middleware
+/// put into the extensions, so it stays empty on public routes.
+#[async_trait]
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(AuditContext {
+            actor_id: parts.extensions.get::<User>().map(|user| user.id),
+            ip: parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip().to_string()),
+            user_agent: device(&parts.headers),
+            request_id: parts.extensions.get::<RequestId>().map(|id| id.0.clone()),
+        })
+    }
+}
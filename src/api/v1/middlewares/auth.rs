@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
-use axum::extract::State;
-use axum::http::header;
+use axum::async_trait;
+use axum::extract::{FromRequestParts, State};
+use axum::http::{header, request::Parts};
 use axum::{
     extract::Request, 
     middleware::Next, 
@@ -12,10 +13,79 @@ use uuid::Uuid;
 
 use crate::api::v1::dependencies::AppState;
 use crate::common::error::{AppError, AppErrorMessage};
-use crate::common::structs::responses::token::TokenType;
+use crate::common::structs::responses::token::{TokenClaims, TokenType};
+use crate::common::structs::responses::user::User;
+use crate::services::api_key::scopes;
 use crate::services::gateway::get_gateway;
 
 
+/// Claims of the access token the request was authenticated with. Rejects requests
+/// authenticated with an API key, for endpoints that manage the user's session or credentials.
+pub struct Session(pub TokenClaims);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Session
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<TokenClaims>()
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| {
+                AppError::ForbiddenError(
+                    AppErrorMessage {
+                        message: "API keys cannot be used for this endpoint".into(),
+                        details: None
+                    }
+                )
+            })
+    }
+}
+
+/// Key sent in the `X-API-Key` header or as `Authorization: ApiKey <key>`.
+fn api_key(request: &Request) -> Option<String> {
+    request.headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            request.headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|auth_header| auth_header.to_str().ok())
+                .and_then(|auth_value| auth_value.strip_prefix("ApiKey "))
+        })
+        .map(|key| key.trim().to_owned())
+}
+
+/// Current user with its token version, from the token cache when possible.
+async fn load_user(state: &AppState, user_id: Uuid) -> Result<(i32, User), AppError> {
+    if let Some(cached) = state.token_cache.user(user_id) {
+        return Ok(cached);
+    }
+
+    let service = get_gateway(&*state.connection).user();
+    let model = service
+        .reader
+        .get(user_id)
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| AppError::UnAuthorizedError(
+            AppErrorMessage {
+                message: "Unauthorized".into(),
+                details: None
+            }
+        ))?;
+    let token_version = model.token_version;
+    let user = service.to_response(model).await?;
+    state.token_cache.set_user(token_version, user.clone());
+
+    Ok((token_version, user))
+}
 
 pub async fn auth(
     cookie_jar: CookieJar, 
@@ -23,6 +93,17 @@ pub async fn auth(
     mut request: Request, 
     next: Next
 ) -> Result<Response, AppError> {
+    if let Some(key) = api_key(&request) {
+        let model = get_gateway(&*state.connection).api_key().authenticate(&key).await?;
+        let (_, mut user) = load_user(&state, model.user_id).await?;
+        let scopes = scopes(&model);
+        user.permissions.retain(|permission| scopes.contains(permission));
+
+        request.extensions_mut().insert(user);
+
+        return Ok(next.run(request).await);
+    }
+
     let token = cookie_jar
         .get("access")
         .map(|cookie| cookie.value().to_string())
@@ -30,13 +111,8 @@ pub async fn auth(
             request.headers()
                 .get(header::AUTHORIZATION)
                 .and_then(|auth_header| auth_header.to_str().ok())
-                .and_then(|auth_value| {
-                    if auth_value.starts_with("Bearer ") {
-                        Some(auth_value[7..].to_owned())
-                    } else {
-                        None
-                    }
-                })
+                .and_then(|auth_value| auth_value.strip_prefix("Bearer "))
+                .map(|token| token.to_owned())
         }).ok_or_else(
             || {
                 AppError::UnAuthorizedError(
@@ -65,18 +141,42 @@ pub async fn auth(
                 AppErrorMessage { message: "Invalid token".into(), details: None}
             )
         })?;
-    let user = get_gateway(&*state.connection)
-        .user()
-        .get(user_id)
-        .await
-        .map_err(|_| AppError::UnAuthorizedError(
+    let gateway = get_gateway(&*state.connection);
+
+    let mut revoked = match state.token_cache.is_revoked(claims.jti) {
+        Some(revoked) => revoked,
+        None => {
+            let revoked = gateway.revoked_token().is_revoked(claims.jti).await?;
+            state.token_cache.set_revoked(claims.jti, revoked);
+            revoked
+        }
+    };
+
+    if let Some(sid) = claims.custom.sid {
+        revoked |= match state.token_cache.is_session_revoked(sid) {
+            Some(revoked) => revoked,
+            None => {
+                let session = gateway.session().reader.get(sid).await?;
+                let revoked = session.is_none_or(|session| session.revoked_at.is_some());
+                state.token_cache.set_session_revoked(sid, revoked);
+                revoked
+            }
+        };
+    }
+
+    let (token_version, user) = load_user(&state, user_id).await?;
+
+    if revoked || claims.token_version < token_version {
+        return Err(AppError::UnAuthorizedError(
             AppErrorMessage {
-                message: "Unauthorized".into(),
+                message: "Token was revoked. Try to login again".into(),
                 details: None
             }
-        ))?;
+        ));
+    }
 
     request.extensions_mut().insert(user);
+    request.extensions_mut().insert(claims);
 
     Ok(next.run(request).await)
 
@@ -0,0 +1,55 @@
+use std::marker::PhantomData;
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use serde_json::json;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::user::User;
+use crate::services::security::permission::{Permission, RequiredPermission};
+
+
+/// Guards a handler behind a permission. Must run after the `auth` middleware,
+/// which puts the current `User` into the request extensions.
+pub struct RequirePermission<P: RequiredPermission>(PhantomData<P>);
+
+pub fn check_permission(user: &User, permission: Permission) -> Result<(), AppError> {
+    if user.permissions.contains(&permission) {
+        return Ok(());
+    }
+
+    Err(AppError::ForbiddenError(
+        AppErrorMessage {
+            message: "Permission denied".into(),
+            details: json!({ "required": permission }).into()
+        }
+    ))
+}
+
+#[async_trait]
+impl<P, S> FromRequestParts<S> for RequirePermission<P>
+where
+    P: RequiredPermission,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<User>()
+            .ok_or_else(|| {
+                AppError::UnAuthorizedError(
+                    AppErrorMessage {
+                        message: "Unauthorized".into(),
+                        details: None
+                    }
+                )
+            })?;
+
+        check_permission(user, P::PERMISSION)?;
+
+        Ok(Self(PhantomData))
+    }
+}
@@ -1,51 +1,125 @@
+use std::sync::Arc;
+
 use log::info;
 
-use axum::{middleware, routing::{delete, get, post}, Router};
+use axum::{middleware, routing::{delete, get, post, put}, Router};
 
 use crate::{
+    api::common::middlewares::rate_limit::{rate_limit, RateLimiter},
     api::v1::{
-        dependencies::setup_dependencies, 
+        dependencies::AppState, 
         endpoints::{
             auth::{
-                login_endpoint, logout_endpoint, refresh_endpoint
+                login_endpoint, logout_endpoint, refresh_endpoint, 
+                resend_verification_endpoint, verify_email_endpoint,
+                forgot_password_endpoint, reset_password_endpoint
             }, 
+        audit::get_audit_events_endpoint,
+        config::get_config_endpoint,
+        api_key::{create_api_key_endpoint, delete_api_key_endpoint, get_api_keys_endpoint},
         healthcheck::healthcheck_endpoint, 
+        jwks::jwks_endpoint,
+        mfa::{confirm_mfa_endpoint, enroll_mfa_endpoint, verify_mfa_endpoint},
+        oidc::{oidc_callback_endpoint, oidc_login_endpoint},
+        session::{
+            get_my_sessions_endpoint, get_user_sessions_endpoint, revoke_my_session_endpoint,
+            revoke_my_sessions_endpoint, revoke_user_session_endpoint, revoke_user_sessions_endpoint
+        },
+        role::{create_role_endpoint, get_many_roles_endpoint, update_role_permissions_endpoint},
         user::{
             delete_user_endpoint, get_me_endpoint, update_user_endpoint
         }}, 
         middlewares::auth::auth
-    }, 
-    core::config::Config
+    }
+};
+use crate::api::v1::endpoints::user::{
+    create_user_endpoint, get_user_by_id_endpoint, get_many_users_endpoint, restore_user_endpoint,
+    change_user_role_endpoint
 };
-use crate::api::v1::endpoints::user::{create_user_endpoint, get_user_by_id_endpoint, get_many_users_endpoint};
 
-pub async fn create_v1_router(config: Config) -> Router {
+pub fn create_v1_router(state: Arc<AppState>) -> Result<Router, anyhow::Error> {
     info!("Creating v1 router... ");
-    let state = setup_dependencies(config).await;
     let auth_middleware = middleware::from_fn_with_state(state.clone(), auth);
+    let limits = &state.config.rate_limit;
+    let auth_limit = middleware::from_fn_with_state(
+        RateLimiter::new("auth", limits, &limits.auth, state.rate_limit_store.clone())?, 
+        rate_limit
+    );
+    let api_limit = middleware::from_fn_with_state(
+        RateLimiter::new("api", limits, &limits.api, state.rate_limit_store.clone())?, 
+        rate_limit
+    );
     let router = Router::new()
        .route(
         "/healthcheck",
         get(healthcheck_endpoint)
         )
        .route("/users", 
-        post(create_user_endpoint)
+        post(create_user_endpoint).route_layer(auth_limit.clone())
         )
        .route("/users", 
-        get(get_many_users_endpoint).patch(update_user_endpoint).route_layer(auth_middleware.clone())
+        get(get_many_users_endpoint).patch(update_user_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
         )
        .route("/users/:user_id", 
-        get(get_user_by_id_endpoint).route_layer(auth_middleware.clone())
+        get(get_user_by_id_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/:user_id/role", 
+        put(change_user_role_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/:user_id/restore", 
+        post(restore_user_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users", delete(delete_user_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/users/me", get(get_me_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/users/me/api-keys", 
+        get(get_api_keys_endpoint).post(create_api_key_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/me/api-keys/:key_id", 
+        delete(delete_api_key_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/me/sessions", 
+        get(get_my_sessions_endpoint).delete(revoke_my_sessions_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
         )
-       .route("/users", delete(delete_user_endpoint).route_layer(auth_middleware.clone()))
-       .route("/users/me", get(get_me_endpoint).route_layer(auth_middleware.clone()))
-       .route("/auth/login", post(login_endpoint))
-       .route("/auth/refresh", post(refresh_endpoint))
-       .route("/auth/logout", post(logout_endpoint).route_layer(auth_middleware.clone()))
+       .route("/users/me/sessions/:session_id", 
+        delete(revoke_my_session_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/:user_id/sessions", 
+        get(get_user_sessions_endpoint).delete(revoke_user_sessions_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/users/:user_id/sessions/:session_id", 
+        delete(revoke_user_session_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/roles", 
+        get(get_many_roles_endpoint).post(create_role_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/roles/:role_id/permissions", 
+        put(update_role_permissions_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone())
+        )
+       .route("/audit", get(get_audit_events_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/admin/config", get(get_config_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/auth/login", post(login_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/refresh", post(refresh_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/verify-email", post(verify_email_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/resend-verification", post(resend_verification_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/password/forgot", post(forgot_password_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/password/reset", post(reset_password_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/mfa/enroll", post(enroll_mfa_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/auth/mfa/confirm", post(confirm_mfa_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
+       .route("/auth/mfa/verify", post(verify_mfa_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/oidc/:provider/login", get(oidc_login_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/oidc/:provider/callback", get(oidc_callback_endpoint).route_layer(auth_limit.clone()))
+       .route("/auth/logout", post(logout_endpoint).route_layer(api_limit.clone()).route_layer(auth_middleware.clone()))
        .with_state(state);
 
     
 
-    Router::new().nest("/v1", router)
+    Ok(Router::new().nest("/v1", router))
     
-}
\ No newline at end of file
+}
+
+/// Routes served from the root, outside of the versioned API.
+pub fn create_well_known_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/.well-known/jwks.json", get(jwks_endpoint))
+        .with_state(state)
+}
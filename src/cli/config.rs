@@ -0,0 +1,17 @@
+use std::error::Error;
+
+use crate::api::common::tls::TlsFiles;
+use crate::core::config::Config;
+use crate::services::security::jwt::JWT;
+
+
+/// Loading the configuration already validated it, this also reads the token keys and TLS files.
+pub fn check(config: Config) -> Result<(), Box<dyn Error>> {
+    if let Some(files) = TlsFiles::from_config(&config.server) {
+        files.load()?;
+    }
+    JWT::new(config.token)?;
+
+    println!("Configuration is valid");
+    Ok(())
+}
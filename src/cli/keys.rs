@@ -0,0 +1,47 @@
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+use jsonwebtoken::Algorithm;
+
+use crate::core::config::Config;
+use crate::services::security::keys::{generate, remove, KeyError, KeyRing};
+
+
+/// Adds a new signing key next to the active one and retires old keys, or creates the first key
+/// of an empty directory. Switching `JWT_ACTIVE_KID` to the new key is left to the operator,
+/// after every instance has loaded its public key.
+pub fn rotate_keys(config: Config, retire: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let dir = config.token.keys_dir.as_deref().ok_or("rotate-keys needs JWT_KEYS_DIR to be set")?;
+    let dir = Path::new(dir);
+    let algorithm = Algorithm::from_str(&config.token.algorithm)
+        .map_err(|_| format!("ALGORITHM {} is not supported", config.token.algorithm))?;
+    let active = match KeyRing::from_dir(algorithm, dir, config.token.active_key_id.as_deref()) {
+        Ok(ring) => Some(ring.kid),
+        Err(KeyError::NoActiveKey(0)) if config.token.active_key_id.is_none() => None,
+        Err(error) => return Err(error.into()),
+    };
+
+    for kid in retire {
+        if active.as_deref() == Some(kid.as_str()) {
+            return Err(format!("Key {kid} is active and can't be retired").into());
+        }
+        match remove(dir, &kid)? {
+            0 => return Err(format!("Key {kid} was not found in JWT_KEYS_DIR").into()),
+            _ => println!("Retired key {kid}"),
+        }
+    }
+
+    let kid = generate(algorithm, dir)?;
+    KeyRing::from_dir(algorithm, dir, Some(&kid))?;
+
+    println!("Generated key {kid}");
+    let Some(active) = active else {
+        return Ok(());
+    };
+    if config.token.active_key_id.is_none() {
+        println!("Set JWT_ACTIVE_KID={active} now, the directory holds several signing keys");
+    }
+    println!("Set JWT_ACTIVE_KID={kid} once every instance has been restarted with its public key");
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use migration::{Migrator, MigratorTrait};
+
+use crate::core::config::Config;
+use crate::database::connection::{connection_options, make_connection};
+
+use super::MigrateAction;
+
+
+pub async fn migrate(config: Config, action: MigrateAction) -> Result<(), Box<dyn Error>> {
+    let connection = make_connection(connection_options(config.db)).await?;
+
+    match action {
+        MigrateAction::Up { steps } => Migrator::up(&*connection, steps).await?,
+        MigrateAction::Down { steps } => Migrator::down(&*connection, Some(steps)).await?,
+        MigrateAction::Status => {
+            Migrator::install(&*connection).await?;
+            for migration in Migrator::get_migration_with_status(&*connection).await? {
+                println!("{}\t{}", migration.status(), migration.name());
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+pub mod config;
+pub mod keys;
+pub mod migrate;
+pub mod user;
+
+
+/// Runs the API server or one of the maintenance commands. Every command reads the same
+/// configuration as the server: defaults, the config file, environment variables and `--set` flags,
+/// later ones winning.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    /// TOML or YAML config file
+    #[arg(long, short, global = true, env = "CONFIG_FILE", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Overrides a config key, e.g. `--set server.port=8081`, can be repeated
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Runs migrations and starts the server, the default without a command
+    Serve,
+    /// Validates the configuration without starting the server
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Applies, reverts or lists database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Creates a user with the Admin role, the password is read from stdin
+    CreateAdmin {
+        #[arg(long)]
+        login: String,
+        /// Taken as verified
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Sets a new password for a user and ends their sessions, the password is read from stdin
+    ResetPassword {
+        #[arg(long)]
+        login: String,
+    },
+    /// Generates the next token signing key into JWT_KEYS_DIR, 2048 bits for the RSA algorithms
+    /// and a random secret for the HS ones
+    RotateKeys {
+        /// Removes an old key once the tokens it signed have expired, can be repeated
+        #[arg(long, value_name = "KID")]
+        retire: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Reports every invalid or missing key and checks that the token keys can be read
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum MigrateAction {
+    /// Applies pending migrations
+    Up {
+        /// Only applies this many, all of them when not set
+        #[arg(long)]
+        steps: Option<u32>,
+    },
+    /// Reverts applied migrations
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// Lists migrations and whether they are applied
+    Status,
+}
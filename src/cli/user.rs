@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::api::common::helpers::try_transaction;
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::requests::user::{CreateUser, UpdateUser};
+use crate::common::structs::responses::user::User;
+use crate::core::config::Config;
+use crate::database::connection::{connection_options, make_connection};
+use crate::database::entity::audit_event::AuditAction;
+use crate::database::entity::role::ADMIN_ROLE_ID;
+use crate::services::audit::{diff, user_snapshot, AuditContext, REDACTED};
+use crate::services::gateway::get_gateway;
+use crate::services::security::hash::{get_argon2_default, Argon2Hasher};
+
+
+/// Reads the password from stdin, so that it stays out of the shell history and the process list.
+fn read_password() -> Result<String, io::Error> {
+    if io::stdin().is_terminal() {
+        eprint!("Password: ");
+        io::stderr().flush()?;
+    }
+
+    let mut password = String::new();
+    io::stdin().lock().read_line(&mut password)?;
+
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// The error with its details, which tell e.g. which password rules were broken.
+fn describe(error: AppError) -> Box<dyn Error> {
+    match error.message().and_then(|message| message.details.as_ref()) {
+        Some(details) => format!("{error}: {details}").into(),
+        None => error.into(),
+    }
+}
+
+async fn begin_transaction(connection: &DatabaseConnection) -> Result<sea_orm::DatabaseTransaction, AppError> {
+    connection
+        .begin()
+        .await
+        .map_err(|_| AppError::BadRequestError(
+            AppErrorMessage { 
+                message: "Failed to open transaction".into(), 
+                details: None 
+            }))
+}
+
+/// Creates an admin, which the API can't do for a fresh database. The email is taken as verified
+/// since the operator vouches for it. The audit event has no actor.
+pub async fn create_admin(config: Config, login: String, email: Option<String>) -> Result<(), Box<dyn Error>> {
+    let password = read_password()?;
+    let connection = make_connection(connection_options(config.db.clone())).await?;
+    let hasher = get_argon2_default();
+
+    let user = create(&connection, &hasher, &config, CreateUser { 
+        login: login.into(), 
+        password: password.into(), 
+        email: email.map(String::into_boxed_str) 
+    }).await.map_err(describe)?;
+
+    println!("Created admin {} ({})", user.login, user.id);
+    Ok(())
+}
+
+async fn create(
+    connection: &DatabaseConnection, hasher: &Argon2Hasher, config: &Config, data: CreateUser
+) -> Result<User, AppError> {
+    let transaction = begin_transaction(connection).await?;
+    let gateway = get_gateway(&transaction);
+
+    let user = async {
+        let users = gateway.user();
+        let mut user = users.create_with_role(data, Some(ADMIN_ROLE_ID), hasher, &config.password).await?;
+
+        if let Some(email) = user.email.clone() {
+            users.writer.verify_email(user.id, email).await?;
+            user = users.get(user.id).await?;
+        }
+        gateway.audit().record(&AuditContext::default(), AuditAction::UserCreate, Some(user.id), diff(None, Some(&user_snapshot(&user)))).await?;
+
+        Ok::<_, AppError>(user)
+    }.await;
+
+    match user {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to create an admin. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to create an admin. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
+
+/// Sets the password of a live user. Bumping the token version, which the update does for
+/// password changes, ends every session of the user.
+pub async fn reset_password(config: Config, login: String) -> Result<(), Box<dyn Error>> {
+    let password = read_password()?;
+    let connection = make_connection(connection_options(config.db.clone())).await?;
+    let hasher = get_argon2_default();
+
+    let user = reset(&connection, &hasher, &config, login, password).await.map_err(describe)?;
+
+    println!("Reset the password of {} ({})", user.login, user.id);
+    Ok(())
+}
+
+async fn reset(
+    connection: &DatabaseConnection, hasher: &Argon2Hasher, config: &Config, login: String, password: String
+) -> Result<User, AppError> {
+    let transaction = begin_transaction(connection).await?;
+    let gateway = get_gateway(&transaction);
+
+    let user = async {
+        let users = gateway.user();
+        let user = users.reader.get_by_login(login.clone()).await?.ok_or_else(|| {
+            AppError::NotFoundError(AppErrorMessage { 
+                message: "User not found".into(), 
+                details: json!({ "login": login }).into() 
+            })
+        })?;
+
+        let user = users.update(
+            user.id, 
+            UpdateUser { id: None, login: None, password: Some(password), email: None, current_password: None, role: None }, 
+            hasher, 
+            &config.password
+        ).await?;
+
+        let changes = json!({ "password": { "before": REDACTED, "after": REDACTED } });
+        gateway.audit().record(&AuditContext::default(), AuditAction::PasswordReset, Some(user.id), Some(changes)).await?;
+
+        Ok::<_, AppError>(user)
+    }.await;
+
+    match user {
+        Ok(result) => {
+            try_transaction(transaction.commit().await, "Failed to reset a password. Commit error".into())?;
+            Ok(result)
+        },
+        Err(error) => {
+            try_transaction(transaction.rollback().await, "Failed to reset a password. Rollback error".into())?;
+            Err(error)
+        }
+    }
+}
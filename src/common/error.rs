@@ -2,7 +2,7 @@
 
 use axum::{
     response::{IntoResponse, Response},
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     Json,
 };
 use serde::Serialize;
@@ -17,6 +17,7 @@ pub struct AppErrorMessage {
 }
 
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum AppError {
     UnAuthorizedError(AppErrorMessage),
@@ -51,6 +52,19 @@ impl std::fmt::Display for AppError {
     }
 }
 
+impl AppError {
+    /// Message and details the API answers with, `None` for unknown errors.
+    pub fn message(&self) -> Option<&AppErrorMessage> {
+        match self {
+            AppError::UnAuthorizedError(msg) | AppError::ConflictError(msg) | AppError::ForbiddenError(msg) |
+            AppError::NotFoundError(msg) | AppError::BadRequestError(msg) | AppError::TooManyRequestsError(msg) |
+            AppError::ServiceUnavailableError(msg) | AppError::ServiceNotImplementedError(msg) |
+            AppError::UnprocessableEntityError(msg) | AppError::InternalServerError(msg) => Some(msg),
+            AppError::UnknownError(_) => None,
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
@@ -68,6 +82,18 @@ impl IntoResponse for AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, AppErrorMessage { message: "Unknown".into(), details: None })
             }
         };
-        (status, Json(message)).into_response()
+        let retry_after = message.details
+            .as_ref()
+            .and_then(|details| details.get("retry_after"))
+            .and_then(|value| value.as_i64())
+            .filter(|_| status == StatusCode::TOO_MANY_REQUESTS);
+
+        let mut response = (status, Json(message)).into_response();
+
+        if let Some(seconds) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        response
     }
 }
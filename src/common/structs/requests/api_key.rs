@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::services::security::permission::Permission;
+
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiKey {
+    #[schema(example = "CI deploy")]
+    pub name: Box<str>,
+    /// Permissions the key is limited to. Each must be one of your own
+    #[serde(default)]
+    pub scopes: Vec<Permission>,
+    /// The key never expires when not set
+    #[schema(example = "2024-05-15T13:45:30Z", format = "date-time")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::database::entity::audit_event::AuditAction;
+
+
+#[derive(Deserialize, IntoParams)]
+pub struct AuditQuery {
+    /// Only events of this user
+    #[param(nullable = true, value_type = Option<String>, format = Uuid)]
+    pub actor_id: Option<Uuid>,
+    /// Only events performed on this user
+    #[param(nullable = true, value_type = Option<String>, format = Uuid)]
+    pub target_id: Option<Uuid>,
+    #[param(nullable = true, inline)]
+    pub action: Option<AuditAction>,
+    /// Only events at or after this time
+    #[param(nullable = true, example = "2023-05-15T00:00:00Z")]
+    pub from: Option<DateTime<Utc>>,
+    /// Only events before this time
+    #[param(nullable = true, example = "2023-05-16T00:00:00Z")]
+    pub to: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+
+#[derive(Deserialize, ToSchema)]
+pub struct MfaCode {
+    #[schema(example = "123456")]
+    pub code: Box<str>
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyMfa {
+    /// The `MFA_PENDING` token returned by login
+    pub token: Box<str>,
+    /// A code from the authenticator app or an unused recovery code
+    #[schema(example = "123456")]
+    pub code: Box<str>
+}
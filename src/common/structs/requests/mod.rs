@@ -1,2 +1,7 @@
 pub mod user;
-pub mod pagination;
\ No newline at end of file
+pub mod role;
+pub mod pagination;
+pub mod mfa;
+pub mod oidc;
+pub mod api_key;
+pub mod audit;
\ No newline at end of file
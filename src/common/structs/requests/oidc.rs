@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+
+/// Query the identity provider redirects back with.
+#[derive(Deserialize, IntoParams)]
+pub struct OidcCallback {
+    #[param(nullable = true)]
+    pub code: Option<String>,
+    #[param(nullable = true)]
+    pub state: Option<String>,
+    /// Set instead of `code` when the login failed or was denied
+    #[param(nullable = true, example = "access_denied")]
+    pub error: Option<String>,
+    #[param(nullable = true)]
+    pub error_description: Option<String>,
+}
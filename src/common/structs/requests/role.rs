@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::services::security::permission::Permission;
+
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateRole {
+    pub name: Box<str>,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateRolePermissions {
+    pub permissions: Vec<Permission>,
+}
@@ -3,13 +3,12 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 
-use crate::database::entity::user::Role;
-
-
 #[derive(Deserialize, ToSchema)]
 pub struct CreateUser {
     pub login: Box<str>,
     pub password: Box<str>,
+    #[schema(example = "user@example.com")]
+    pub email: Option<Box<str>>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -29,6 +28,42 @@ pub struct UpdateUser {
     pub id: Option<Uuid>,
     pub login: Option<String>,
     pub password: Option<String>,
-    pub role: Option<Role>
+    #[schema(example = "user@example.com")]
+    pub email: Option<String>,
+    /// Required to change your own login, password or email
+    pub current_password: Option<String>,
+    /// Not accepted here, roles are changed through `PUT /users/{id}/role`
+    #[schema(example = json!(null))]
+    pub role: Option<String>
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateUserRole {
+    #[schema(example = "Admin")]
+    pub role: Box<str>
+}
+
+
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyEmail {
+    pub token: Box<str>
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct ResendVerification {
+    #[schema(example = "user@example.com")]
+    pub email: Box<str>
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ForgotPassword {
+    #[schema(example = "user@example.com")]
+    pub login: Box<str>
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ResetPassword {
+    pub token: Box<str>,
+    pub password: Box<str>
+}
\ No newline at end of file
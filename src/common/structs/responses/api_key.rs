@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::services::security::permission::Permission;
+
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiKey {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub id: Uuid,
+    #[schema(example = "CI deploy")]
+    pub name: String,
+    /// Start of the key, to tell keys apart
+    #[schema(example = "ak_3f9c2a71b04e")]
+    pub prefix: String,
+    pub scopes: Vec<Permission>,
+    #[schema(example = "2024-05-15T13:45:30Z", format = "date-time")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[schema(example = "2023-05-16T08:12:03Z", format = "date-time")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
+    pub created_at: DateTime<Utc>
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedApiKey {
+    /// The key to send in the `X-API-Key` header. It is shown only once
+    #[schema(example = "ak_3f9c2a71b04e_Vq6yYl0rYx8m3dJ1dW9b3tC5kUoN2aGfZ4sEwQpHjKc")]
+    pub key: String,
+    pub api_key: ApiKey
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::database::entity::audit_event::AuditAction;
+
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditEvent {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub id: Uuid,
+    /// User who acted, empty for anonymous requests
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub actor_id: Option<Uuid>,
+    pub action: AuditAction,
+    /// User the action was performed on
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub target_id: Option<Uuid>,
+    #[schema(value_type = Option<Object>, example = json!({"role": {"before": "User", "after": "Admin"}}))]
+    pub changes: Option<Value>,
+    #[schema(example = "203.0.113.7")]
+    pub ip: Option<String>,
+    #[schema(example = "Mozilla/5.0 (X11; Linux x86_64)")]
+    pub user_agent: Option<String>,
+    #[schema(example = "6f1c2a0e-4b7d-4d2f-9a55-0c3c1f5e8b21")]
+    pub request_id: Option<String>,
+    #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
+    pub created_at: DateTime<Utc>
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditEventData {
+    pub total: u64,
+    pub data: Vec<AuditEvent>
+}
@@ -0,0 +1,14 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+
+#[derive(Serialize, ToSchema)]
+pub struct MfaEnrollment {
+    #[schema(example = "otpauth://totp/axum_api_example:user?secret=JBSWY3DPEHPK3PXP&issuer=axum_api_example")]
+    pub otpauth_uri: String,
+    #[schema(example = "JBSWY3DPEHPK3PXP")]
+    pub secret: String,
+    /// Single-use codes that replace a TOTP code. They are shown only once
+    #[schema(example = json!(["k3m9p-x7q2r"]))]
+    pub recovery_codes: Vec<String>
+}
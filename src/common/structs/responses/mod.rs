@@ -1,4 +1,9 @@
 pub mod healthcheck;
 pub mod user;
+pub mod role;
 pub mod token;
-pub mod status;
\ No newline at end of file
+pub mod status;
+pub mod mfa;
+pub mod api_key;
+pub mod session;
+pub mod audit;
\ No newline at end of file
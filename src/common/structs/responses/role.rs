@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::services::security::permission::Permission;
+
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct Role {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub id: Uuid,
+    #[schema(example = "Moderator")]
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
+    pub created_at: DateTime<Utc>
+}
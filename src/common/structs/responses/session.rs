@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+
+#[derive(Serialize, ToSchema)]
+pub struct ActiveSession {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
+    pub id: Uuid,
+    /// User agent of the client that logged in or last refreshed the session
+    #[schema(example = "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0")]
+    pub device: Option<String>,
+    #[schema(example = "203.0.113.7")]
+    pub ip: Option<String>,
+    /// Whether the request was made from this session
+    pub current: bool,
+    #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(example = "2023-05-16T08:12:03Z", format = "date-time")]
+    pub last_used_at: DateTime<Utc>,
+    #[schema(example = "2023-05-23T08:12:03Z", format = "date-time")]
+    pub expires_at: DateTime<Utc>
+}
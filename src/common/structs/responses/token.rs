@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 
+#[allow(clippy::upper_case_acronyms, non_camel_case_types)]
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, ToSchema)]
 pub enum TokenType {
     ACCESS,
-    REFRESH
+    REFRESH,
+    EMAIL_VERIFICATION,
+    MFA_PENDING
 }
 
 
@@ -15,10 +19,35 @@ pub struct Token {
     pub token: String
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Claims added to access tokens so that other services can authorize requests without
+/// calling back into the API. `sid` is always set, `role` and `scope` when named in `JWT_EMBED_CLAIMS`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomClaims {
+    /// Name of the user's role.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Permissions of the user's role, separated by spaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Refresh token family the token was issued for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub _type: TokenType,
     pub sub: String,
+    pub jti: Uuid,
+    pub token_version: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
     pub iat: usize,
+    #[serde(default)]
+    pub nbf: usize,
     pub exp: usize,
+    #[serde(flatten)]
+    pub custom: CustomClaims,
 }
\ No newline at end of file
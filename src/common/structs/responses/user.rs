@@ -3,7 +3,7 @@ use serde::Serialize;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::database::entity::user::Role;
+use crate::services::security::permission::Permission;
 
 
 #[derive(Clone, Serialize, ToSchema)]
@@ -11,7 +11,13 @@ pub struct User {
     #[schema(example = "550e8400-e29b-41d4-a716-446655440000", format = "Uuid")]
     pub id: Uuid,
     pub login: String,
-    pub role: Role,
+    #[schema(example = "user@example.com")]
+    pub email: Option<String>,
+    #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
+    pub email_verified_at: Option<DateTime<Utc>>,
+    #[schema(example = "User")]
+    pub role: String,
+    pub permissions: Vec<Permission>,
     #[schema(example = "2023-05-15T13:45:30Z", format = "date-time")]
     pub created_at: DateTime<Utc>
 }
@@ -0,0 +1,232 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Cross-origin policy. Lists are comma separated, `*` alone allows everything.
+///
+/// Origins are exact (`https://app.example.com`), wildcard subdomain patterns
+/// (`https://*.example.com`, any depth) or regular expressions matched against the whole
+/// origin (`regex:https://pr-\d+\.example\.com`), which cannot contain commas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorsConfig {
+    pub allowed_origins: Box<str>,
+    pub allowed_methods: Box<str>,
+    pub allowed_headers: Box<str>,
+    pub exposed_headers: Box<str>,
+    /// How long browsers may cache a preflight response, not sent when 0
+    pub max_age_seconds: u64,
+    pub allow_credentials: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: "".into(),
+            allowed_methods: "GET,POST,PUT,PATCH,DELETE,OPTIONS".into(),
+            allowed_headers: "authorization,accept,content-type,x-api-key,x-request-id".into(),
+            exposed_headers: "x-process-time,x-request-id".into(),
+            max_age_seconds: 600,
+            allow_credentials: false,
+        }
+    }
+}
+
+/// A parsed list setting, `Any` for `*`.
+#[derive(Debug, Clone)]
+pub enum CorsList<T> {
+    Any,
+    Only(Vec<T>),
+}
+
+#[derive(Debug, Clone)]
+pub enum CorsOrigin {
+    Exact(HeaderValue),
+    Pattern(Regex),
+}
+
+impl CorsOrigin {
+    fn parse(value: &str) -> Result<Self, String> {
+        if let Some(pattern) = value.strip_prefix("regex:") {
+            // Anchored, an unanchored match would accept https://app.example.com.evil.net
+            return Regex::new(&format!("^(?:{pattern})$"))
+                .map(CorsOrigin::Pattern)
+                // The message draws a caret under the problem over several lines, the last one says what it is
+                .map_err(|error| format!("{value} is not a valid regex, {}", error.to_string().lines().last().unwrap_or_default()));
+        }
+
+        let Some((scheme, host)) = value.split_once("://") else {
+            return Err(format!("{value} is not an origin, expected scheme://host[:port]"));
+        };
+        if scheme.is_empty() || host.is_empty() || host.contains('/') {
+            return Err(format!("{value} is not an origin, expected scheme://host[:port] without a path"));
+        }
+
+        if let Some(domain) = host.strip_prefix("*.") {
+            if domain.is_empty() || domain.contains('*') {
+                return Err(format!("{value} is not a valid pattern, expected scheme://*.domain[:port]"));
+            }
+            let pattern = format!(r"{}://[a-z0-9-]+(\.[a-z0-9-]+)*\.{}", regex::escape(scheme), regex::escape(domain));
+            return Regex::new(&format!("^(?:{})$", pattern.to_lowercase()))
+                .map(CorsOrigin::Pattern)
+                .map_err(|error| format!("{value} is not a valid pattern: {error}"));
+        }
+        if host.contains('*') {
+            return Err(format!("{value} is not a valid pattern, only a leading *. subdomain is supported"));
+        }
+
+        HeaderValue::from_str(&value.to_lowercase())
+            .map(CorsOrigin::Exact)
+            .map_err(|_| format!("{value} is not a valid header value"))
+    }
+
+    pub fn matches(&self, origin: &HeaderValue) -> bool {
+        match self {
+            CorsOrigin::Exact(allowed) => allowed.as_bytes().eq_ignore_ascii_case(origin.as_bytes()),
+            CorsOrigin::Pattern(pattern) => origin.to_str().is_ok_and(|origin| pattern.is_match(origin)),
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn origins(&self) -> CorsList<CorsOrigin> {
+        parse_list(&self.allowed_origins, CorsOrigin::parse).0
+    }
+
+    pub fn methods(&self) -> CorsList<Method> {
+        parse_list(&self.allowed_methods, parse_method).0
+    }
+
+    pub fn allowed_headers(&self) -> CorsList<HeaderName> {
+        parse_list(&self.allowed_headers, parse_header).0
+    }
+
+    pub fn exposed_headers(&self) -> CorsList<HeaderName> {
+        parse_list(&self.exposed_headers, parse_header).0
+    }
+
+    pub(super) fn validate(&self, errors: &mut Vec<String>) {
+        self.validate_list(errors, "cors.allowed_origins", &self.allowed_origins, CorsOrigin::parse);
+        self.validate_list(errors, "cors.allowed_methods", &self.allowed_methods, parse_method);
+        self.validate_list(errors, "cors.allowed_headers", &self.allowed_headers, parse_header);
+        self.validate_list(errors, "cors.exposed_headers", &self.exposed_headers, parse_header);
+    }
+
+    fn validate_list<T>(&self, errors: &mut Vec<String>, key: &str, value: &str, parse: impl Fn(&str) -> Result<T, String>) {
+        let (list, problems) = parse_list(value, parse);
+        errors.extend(problems.into_iter().map(|problem| format!("{key}: {problem}")));
+
+        // Browsers ignore a wildcard on credentialed requests, tower-http refuses it outright
+        if matches!(list, CorsList::Any) && self.allow_credentials {
+            errors.push(format!("{key}: * cannot be used with cors.allow_credentials, list the values instead"));
+        }
+    }
+}
+
+/// Splits a comma separated list, returning the entries that parse and a problem for each that does not.
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Result<T, String>) -> (CorsList<T>, Vec<String>) {
+    let entries: Vec<&str> = value.split(',').map(str::trim).filter(|entry| !entry.is_empty()).collect();
+
+    if entries.contains(&"*") {
+        let problems = match entries.len() {
+            1 => vec![],
+            _ => vec!["* must be the only entry".into()],
+        };
+        return (CorsList::Any, problems);
+    }
+
+    let mut problems = Vec::new();
+    let list = entries
+        .into_iter()
+        .filter_map(|entry| parse(entry).map_err(|problem| problems.push(problem)).ok())
+        .collect();
+    (CorsList::Only(list), problems)
+}
+
+fn parse_method(value: &str) -> Result<Method, String> {
+    Method::from_bytes(value.to_uppercase().as_bytes()).map_err(|_| format!("{value} is not a valid method"))
+}
+
+fn parse_header(value: &str) -> Result<HeaderName, String> {
+    HeaderName::from_bytes(value.as_bytes()).map_err(|_| format!("{value} is not a valid header name"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(allowed: &str) -> CorsOrigin {
+        CorsOrigin::parse(allowed).unwrap()
+    }
+
+    fn matches(allowed: &str, origin_value: &str) -> bool {
+        origin(allowed).matches(&HeaderValue::from_str(origin_value).unwrap())
+    }
+
+    fn errors(config: CorsConfig) -> Vec<String> {
+        let mut errors = Vec::new();
+        config.validate(&mut errors);
+        errors
+    }
+
+    #[test]
+    fn exact_origins_match_only_themselves() {
+        assert!(matches("https://app.example.com", "https://app.example.com"));
+        assert!(matches("https://App.Example.com", "https://app.example.com"));
+        assert!(!matches("https://app.example.com", "http://app.example.com"));
+        assert!(!matches("https://app.example.com", "https://app.example.com:8443"));
+        assert!(!matches("https://app.example.com", "https://app.example.com.evil.net"));
+    }
+
+    #[test]
+    fn wildcard_origins_match_subdomains_only() {
+        assert!(matches("https://*.example.com", "https://app.example.com"));
+        assert!(matches("https://*.example.com", "https://a.b.example.com"));
+        assert!(!matches("https://*.example.com", "https://example.com"));
+        assert!(!matches("https://*.example.com", "http://app.example.com"));
+        assert!(!matches("https://*.example.com", "https://app.example.com.evil.net"));
+        assert!(!matches("https://*.example.com", "https://evil.net/.example.com"));
+        assert!(!matches("https://*.example.com", "https://appexample.com"));
+    }
+
+    #[test]
+    fn regex_origins_match_the_whole_origin() {
+        let allowed = r"regex:https://pr-\d+\.example\.com";
+        assert!(matches(allowed, "https://pr-12.example.com"));
+        assert!(!matches(allowed, "https://pr-1.example.com.evil.net"));
+        assert!(!matches(allowed, "https://evil.net?https://pr-1.example.com"));
+        assert!(!matches(r"regex:https://a\.com|https://b\.com", "https://b.com.evil.net"));
+        assert!(matches(r"regex:^https://pr-\d+\.example\.com$", "https://pr-3.example.com"));
+    }
+
+    #[test]
+    fn invalid_origins_are_rejected() {
+        assert!(CorsOrigin::parse("https://app.example.com/").is_err());
+        assert!(CorsOrigin::parse("app.example.com").is_err());
+        assert!(CorsOrigin::parse("https://app.*.com").is_err());
+        assert!(CorsOrigin::parse("regex:(").is_err());
+    }
+
+    #[test]
+    fn wildcards_are_rejected_with_credentials() {
+        let config = CorsConfig { allowed_origins: "*".into(), allow_credentials: true, ..Default::default() };
+        assert_eq!(errors(config), ["cors.allowed_origins: * cannot be used with cors.allow_credentials, list the values instead"]);
+
+        let config = CorsConfig { allowed_headers: "*".into(), allow_credentials: true, ..Default::default() };
+        assert_eq!(errors(config), ["cors.allowed_headers: * cannot be used with cors.allow_credentials, list the values instead"]);
+
+        let config = CorsConfig { allowed_origins: "*".into(), allowed_methods: "*".into(), ..Default::default() };
+        assert!(errors(config).is_empty());
+
+        let config = CorsConfig {
+            allowed_origins: "https://*.example.com".into(), allow_credentials: true, ..Default::default()
+        };
+        assert!(errors(config).is_empty());
+    }
+
+    #[test]
+    fn wildcard_must_be_alone() {
+        let config = CorsConfig { allowed_origins: "*,https://app.example.com".into(), ..Default::default() };
+        assert_eq!(errors(config), ["cors.allowed_origins: * must be the only entry"]);
+    }
+}
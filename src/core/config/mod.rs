@@ -0,0 +1,805 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use base64::prelude::*;
+use jsonwebtoken::Algorithm;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+mod cors;
+mod secret;
+mod sources;
+
+pub use cors::{CorsConfig, CorsList, CorsOrigin};
+pub use secret::{Secret, REDACTED};
+
+/// Every problem found while loading the configuration, as `key: problem` lines.
+#[derive(Debug, Error)]
+#[error("Invalid configuration:\n  {}", .0.join("\n  "))]
+pub struct ConfigError(pub Vec<String>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DBConfig {
+    #[serde(serialize_with = "secret::serialize_uri")]
+    uri: Box<str>,
+    name: Box<str>,
+    host: Option<Box<str>>,
+    user: Option<Box<str>>,
+    port: Option<u16>,
+    password: Option<Secret<Box<str>>>,
+    max_connections: u32,
+    min_connections: u32,
+    connect_timeout: u64,
+    acquire_timeout: u64,
+    idle_timeout: u64,
+    max_lifetime: u64,
+    sqlx_logging: bool,
+    sqlx_logging_level: usize
+
+}
+
+impl Default for DBConfig {
+    fn default() -> Self {
+        DBConfig {
+            uri: "".into(),
+            name: "".into(),
+            host: None,
+            user: None,
+            password: None,
+            port: None,
+            max_connections: 100,
+            min_connections: 5,
+            connect_timeout: 30,
+            acquire_timeout: 30,
+            idle_timeout: 600,
+            max_lifetime: 1800,
+            sqlx_logging: true,
+            sqlx_logging_level: 3
+        }
+    }
+}
+
+impl DBConfig {
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        require(errors, "db.uri", &self.uri);
+        require(errors, "db.name", &self.name);
+
+        if !self.uri.contains("sqlite") {
+            for (placeholder, key, set) in [
+                ("{user}", "db.user", self.user.is_some()),
+                ("{password}", "db.password", self.password.is_some()),
+                ("{host}", "db.host", self.host.is_some()),
+                ("{port}", "db.port", self.port.is_some()),
+            ] {
+                if self.uri.contains(placeholder) && !set {
+                    errors.push(missing(key));
+                }
+            }
+        }
+        if self.min_connections > self.max_connections {
+            errors.push("db.min_connections: must not be greater than db.max_connections".into());
+        }
+        if self.sqlx_logging_level > 5 {
+            errors.push("db.sqlx_logging_level: must be between 0 (off) and 5 (trace)".into());
+        }
+    }
+
+    pub fn url(&self) -> String {
+
+        if self.uri.contains("sqlite") {
+            self.uri.replace("{name}", &self.name)
+        } else {
+            self.uri
+                .replace("{user}", self.user.as_deref().unwrap_or_default())
+                .replace("{password}", self.password.as_ref().map(|password| password.expose().as_ref()).unwrap_or_default())
+                .replace("{host}", self.host.as_deref().unwrap_or_default())
+                .replace("{port}", &self.port.map(|port| port.to_string()).unwrap_or_default())
+                .replace("{name}", &self.name)
+        }
+    }
+
+    pub fn max_connections(&self) -> u32 {
+        self.max_connections
+    }
+
+    pub fn min_connections(&self) -> u32 {
+        self.min_connections
+    }
+
+    pub fn connect_timeout(&self) -> u64 {
+        self.connect_timeout
+    }
+
+    pub fn acquire_timeout(&self) -> u64 {
+        self.acquire_timeout
+    }
+
+    pub fn idle_timeout(&self) -> u64 {
+        self.idle_timeout
+    }
+
+    pub fn max_lifetime(&self) -> u64 {
+        self.max_lifetime
+    }
+
+    pub fn sqlx_logging(&self) -> bool {
+        self.sqlx_logging
+    }
+
+    pub fn sqlx_logging_level(&self) -> LevelFilter {
+        match self.sqlx_logging_level {
+            0 => LevelFilter::Off,
+            1 => LevelFilter::Error,
+            2 => LevelFilter::Warn,
+            3 => LevelFilter::Info,
+            4 => LevelFilter::Debug,
+            5 => LevelFilter::Trace,
+            _ => LevelFilter::Info,
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    pub algorithm: Box<str>,
+    /// Not needed with `keys_dir`
+    pub secret_key: Option<Secret<Box<str>>>,
+    /// The secret key is used when not set, for HMAC algorithms
+    pub public_key: Option<Box<str>>,
+    pub keys_dir: Option<Box<str>>,
+    pub active_key_id: Option<Box<str>>,
+    pub issuer: Option<Box<str>>,
+    pub audience: Option<Box<str>>,
+    pub leeway_seconds: u64,
+    pub embed_claims: Box<str>,
+    pub access_token_expire_seconds: i64,
+    pub refresh_token_expire_seconds: i64,
+    pub cache_ttl_seconds: u64
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: "".into(),
+            secret_key: None,
+            public_key: None,
+            keys_dir: None,
+            active_key_id: None,
+            issuer: None,
+            audience: None,
+            leeway_seconds: 60,
+            embed_claims: "".into(),
+            access_token_expire_seconds: 1800,
+            refresh_token_expire_seconds: 604800,
+            cache_ttl_seconds: 30,
+        }
+    }
+}
+
+impl TokenConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if require(errors, "token.algorithm", &self.algorithm) && Algorithm::from_str(&self.algorithm).is_err() {
+            errors.push(format!("token.algorithm: {} is not supported", self.algorithm));
+        }
+        if self.keys_dir.is_none() && self.secret_key.as_ref().is_none_or(|key| key.expose().trim().is_empty()) {
+            errors.push(missing("token.secret_key"));
+        }
+        positive(errors, "token.access_token_expire_seconds", self.access_token_expire_seconds);
+        positive(errors, "token.refresh_token_expire_seconds", self.refresh_token_expire_seconds);
+    }
+}
+
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    host: Box<str>,
+    port: u16,
+    /// PEM certificate chain, HTTPS is served when set together with `tls_key_path`
+    tls_cert_path: Option<Box<str>>,
+    tls_key_path: Option<Box<str>>,
+    /// PEM CA bundle, clients must present a certificate it signed when set
+    tls_client_ca_path: Option<Box<str>>,
+    /// How often the TLS files are checked for changes, 0 only reloads them on SIGHUP
+    tls_reload_interval_seconds: u64,
+    /// Plain HTTP port that redirects to HTTPS
+    http_redirect_port: Option<u16>,
+    /// HTTPS origin clients reach the server at, e.g. `https://api.example.com`, where plain HTTP is redirected to
+    public_url: Option<Box<str>>,
+    /// How long the healthcheck fails before connections are drained, for load balancers to notice
+    shutdown_delay_seconds: u64,
+    /// How long open connections may take to finish on shutdown before they are closed
+    shutdown_timeout_seconds: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: "0.0.0.0".into(),
+            port: 8080,
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_client_ca_path: None,
+            tls_reload_interval_seconds: 60,
+            http_redirect_port: None,
+            public_url: None,
+            shutdown_delay_seconds: 0,
+            shutdown_timeout_seconds: 30,
+        }
+    }
+}
+
+impl ServerConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        match (&self.tls_cert_path, &self.tls_key_path) {
+            (Some(_), None) => errors.push(missing("server.tls_key_path")),
+            (None, Some(_)) => errors.push(missing("server.tls_cert_path")),
+            _ => {},
+        }
+        if !self.tls_enabled() {
+            if self.tls_client_ca_path.is_some() {
+                errors.push("server.tls_client_ca_path: needs server.tls_cert_path and server.tls_key_path".into());
+            }
+            if self.http_redirect_port.is_some() {
+                errors.push("server.http_redirect_port: needs server.tls_cert_path and server.tls_key_path".into());
+            }
+        }
+        if self.http_redirect_port == Some(self.port) {
+            errors.push("server.http_redirect_port: must differ from server.port".into());
+        }
+        // Redirects go to a configured origin, the Host header is up to the client
+        match &self.public_url {
+            Some(public_url) => {
+                let valid = url::Url::parse(public_url).is_ok_and(|url| {
+                    url.scheme() == "https" && url.host().is_some() && url.path() == "/" && url.query().is_none()
+                });
+                if !valid {
+                    errors.push(format!("server.public_url: must be an https origin like https://api.example.com, got {public_url}"));
+                }
+            },
+            None if self.http_redirect_port.is_some() => errors.push(missing("server.public_url")),
+            None => {},
+        }
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn tls_enabled(&self) -> bool {
+        self.tls_cert_path.is_some() && self.tls_key_path.is_some()
+    }
+
+    pub fn tls_cert_path(&self) -> Option<&str> {
+        self.tls_cert_path.as_deref()
+    }
+
+    pub fn tls_key_path(&self) -> Option<&str> {
+        self.tls_key_path.as_deref()
+    }
+
+    pub fn tls_client_ca_path(&self) -> Option<&str> {
+        self.tls_client_ca_path.as_deref()
+    }
+
+    pub fn tls_reload_interval_seconds(&self) -> u64 {
+        self.tls_reload_interval_seconds
+    }
+
+    pub fn http_redirect_port(&self) -> Option<u16> {
+        self.http_redirect_port
+    }
+
+    pub fn public_url(&self) -> Option<&str> {
+        self.public_url.as_deref()
+    }
+
+    pub fn shutdown_delay(&self) -> Duration {
+        Duration::from_secs(self.shutdown_delay_seconds)
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_seconds)
+    }
+
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginConfig {
+    pub max_attempts: u32,
+    pub ip_max_attempts: u32,
+    pub attempt_window_seconds: i64,
+    pub lockout_seconds: i64,
+    pub lockout_max_seconds: i64,
+    pub require_verified_email: bool,
+    pub mfa_token_expire_seconds: i64,
+}
+
+impl Default for LoginConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            ip_max_attempts: 20,
+            attempt_window_seconds: 900,
+            lockout_seconds: 30,
+            lockout_max_seconds: 3600,
+            require_verified_email: false,
+            mfa_token_expire_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MfaConfig {
+    pub issuer: Box<str>,
+    pub encryption_key: Option<Secret<Box<str>>>,
+}
+
+impl Default for MfaConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "axum_api_example".into(),
+            encryption_key: None,
+        }
+    }
+}
+
+impl MfaConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        // Independent of token.secret_key, rotating the signing key must not make MFA secrets unreadable
+        let Some(key) = &self.encryption_key else {
+            return errors.push(missing("mfa.encryption_key"));
+        };
+
+        match BASE64_STANDARD.decode(key.expose().as_bytes()) {
+            Ok(key) if key.len() == 32 => {},
+            Ok(_) => errors.push("mfa.encryption_key: must be 32 bytes long".into()),
+            Err(_) => errors.push("mfa.encryption_key: must be base64 encoded".into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PasswordConfig {
+    pub min_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PurgeConfig {
+    pub retention_seconds: i64,
+    pub interval_seconds: u64,
+}
+
+impl Default for PurgeConfig {
+    fn default() -> Self {
+        Self {
+            retention_seconds: 2592000,
+            interval_seconds: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MailConfig {
+    pub transport: Box<str>,
+    pub from: Box<str>,
+    pub smtp_host: Option<Box<str>>,
+    pub smtp_port: Option<u16>,
+    pub smtp_tls: Box<str>,
+    pub smtp_username: Option<Box<str>>,
+    pub smtp_password: Option<Secret<Box<str>>>,
+    pub file_path: Box<str>,
+    pub verification_url: Box<str>,
+    pub verification_expire_seconds: i64,
+    pub password_reset_url: Box<str>,
+    pub password_reset_expire_seconds: i64,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: "file".into(),
+            from: "noreply@localhost".into(),
+            smtp_host: None,
+            smtp_port: None,
+            smtp_tls: "starttls".into(),
+            smtp_username: None,
+            smtp_password: None,
+            file_path: "mail.log".into(),
+            verification_url: "http://localhost:8080/verify-email".into(),
+            verification_expire_seconds: 86400,
+            password_reset_url: "http://localhost:8080/reset-password".into(),
+            password_reset_expire_seconds: 3600,
+        }
+    }
+}
+
+impl MailConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        one_of(errors, "mail.transport", &self.transport, &["file", "smtp"]);
+        one_of(errors, "mail.smtp_tls", &self.smtp_tls, &["tls", "starttls", "none"]);
+
+        if self.from.parse::<lettre::message::Mailbox>().is_err() {
+            errors.push(format!("mail.from: {} is not a valid mailbox", self.from));
+        }
+        if self.transport.as_ref() == "smtp" && self.smtp_host.is_none() {
+            errors.push(missing("mail.smtp_host"));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitGroupConfig {
+    pub requests: u64,
+    pub window_seconds: i64,
+    pub key: Box<str>,
+}
+
+impl RateLimitGroupConfig {
+    fn new(requests: u64, window_seconds: i64, key: &str) -> Self {
+        Self { requests, window_seconds, key: key.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub store: Box<str>,
+    pub auth: RateLimitGroupConfig,
+    pub api: RateLimitGroupConfig,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            store: "memory".into(),
+            auth: RateLimitGroupConfig::new(10, 60, "ip"),
+            api: RateLimitGroupConfig::new(100, 60, "user"),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        one_of(errors, "rate_limit.store", &self.store, &["memory", "postgres"]);
+        one_of(errors, "rate_limit.auth.key", &self.auth.key, &["ip", "user", "route"]);
+        one_of(errors, "rate_limit.api.key", &self.api.key, &["ip", "user", "route"]);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcProviderConfig {
+    pub issuer: Box<str>,
+    pub client_id: Box<str>,
+    pub client_secret: Option<Secret<Box<str>>>,
+    pub scopes: Box<str>,
+    pub auto_provision: bool,
+    pub link_by_email: bool,
+}
+
+impl Default for OidcProviderConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "".into(),
+            client_id: "".into(),
+            client_secret: None,
+            scopes: "openid email profile".into(),
+            auto_provision: false,
+            link_by_email: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OidcConfig {
+    pub providers: HashMap<Box<str>, OidcProviderConfig>,
+    pub redirect_url: Box<str>,
+    pub login_expire_seconds: i64,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            providers: HashMap::new(),
+            redirect_url: "http://localhost:8080/api/v1/auth/oidc".into(),
+            login_expire_seconds: 600,
+        }
+    }
+}
+
+impl OidcConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        let mut names: Vec<&Box<str>> = self.providers.keys().collect();
+        names.sort();
+
+        for name in names {
+            let provider = &self.providers[name];
+            require(errors, &format!("oidc.providers.{name}.issuer"), &provider.issuer);
+            require(errors, &format!("oidc.providers.{name}.client_id"), &provider.client_id);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub db: DBConfig,
+    pub server: ServerConfig,
+    pub cors: CorsConfig,
+    pub token: TokenConfig,
+    pub login: LoginConfig,
+    pub rate_limit: RateLimitConfig,
+    pub mail: MailConfig,
+    pub password: PasswordConfig,
+    pub purge: PurgeConfig,
+    pub mfa: MfaConfig,
+    pub oidc: OidcConfig,
+}
+
+impl Config {
+    /// Layers the defaults, the TOML or YAML `file`, environment variables and `key=value`
+    /// overrides, later ones winning. Reports every invalid or missing key at once.
+    pub fn load(file: Option<&Path>, overrides: &[String]) -> Result<Self, ConfigError> {
+        let mut errors = Vec::new();
+        let defaults = serde_json::to_value(Config::default()).expect("Default config is not serializable");
+
+        let mut tree = defaults.clone();
+        if let Some(file) = file {
+            sources::merge(&mut tree, sources::file(file, &mut errors));
+        }
+        let env = sources::env(&tree, &mut errors);
+        sources::merge(&mut tree, env);
+        sources::merge(&mut tree, sources::overrides(overrides, &mut errors));
+
+        let Some(mut config) = Self::deserialize(tree, &defaults, &mut errors) else {
+            return Err(ConfigError(errors));
+        };
+        config.normalize();
+        config.validate(&mut errors);
+
+        // A value that failed to parse falls back to its default, which may be reported again
+        let mut reported = std::collections::HashSet::new();
+        errors.retain(|error| reported.insert(error.split(':').next().unwrap_or_default().to_string()));
+
+        match errors.is_empty() {
+            true => Ok(config),
+            false => Err(ConfigError(errors)),
+        }
+    }
+
+    /// Reports a value of the wrong type and puts back its default, until the rest deserializes.
+    fn deserialize(mut tree: Value, defaults: &Value, errors: &mut Vec<String>) -> Option<Self> {
+        loop {
+            let error = match serde_path_to_error::deserialize(&tree) {
+                Ok(config) => return Some(config),
+                Err(error) => error,
+            };
+            let path = error.path().to_string();
+            errors.push(format!("{path}: {}", error.inner()));
+
+            if !sources::reset(&mut tree, defaults, &path) {
+                return None;
+            }
+        }
+    }
+
+    fn normalize(&mut self) {
+        self.oidc.redirect_url = self.oidc.redirect_url.trim_end_matches('/').into();
+        self.oidc.providers = std::mem::take(&mut self.oidc.providers)
+            .into_iter()
+            .map(|(name, provider)| (name.to_lowercase().into_boxed_str(), provider))
+            .collect();
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.db.validate(errors);
+        self.server.validate(errors);
+        self.token.validate(errors);
+        self.cors.validate(errors);
+        self.mail.validate(errors);
+        self.rate_limit.validate(errors);
+        self.mfa.validate(errors);
+        self.oidc.validate(errors);
+    }
+}
+
+fn missing(key: &str) -> String {
+    match sources::env_var(key) {
+        Some(var) => format!("{key}: must be set, e.g. with {var}"),
+        None => format!("{key}: must be set"),
+    }
+}
+
+/// Reports an empty value as missing. Returns whether it is set.
+fn require(errors: &mut Vec<String>, key: &str, value: &str) -> bool {
+    if value.trim().is_empty() {
+        errors.push(missing(key));
+        return false;
+    }
+    true
+}
+
+fn positive(errors: &mut Vec<String>, key: &str, value: i64) {
+    if value <= 0 {
+        errors.push(format!("{key}: must be positive"));
+    }
+}
+
+fn one_of(errors: &mut Vec<String>, key: &str, value: &str, allowed: &[&str]) {
+    if !allowed.contains(&value) {
+        errors.push(format!("{key}: must be {}, got {value}", allowed.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Held by every test that loads a configuration, since the loader reads the whole
+    /// environment and some tests set variables in it.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    const REQUIRED: &str = r#"
+        [db]
+        uri = "postgres://{user}:{password}@{host}:{port}/{name}"
+        name = "app"
+        user = "app"
+        password = "app"
+        host = "localhost"
+        port = 5432
+
+        [token]
+        algorithm = "HS256"
+        secret_key = "c2VjcmV0"
+
+        [mfa]
+        encryption_key = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+    "#;
+
+    /// Writes `content` to a temporary file with the given extension.
+    fn file(extension: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("config-{}.{extension}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn load(content: &str, overrides: &[&str]) -> Result<Config, ConfigError> {
+        let path = file("toml", content);
+        let overrides: Vec<String> = overrides.iter().map(|entry| entry.to_string()).collect();
+        let config = Config::load(Some(&path), &overrides);
+        std::fs::remove_file(path).unwrap();
+        config
+    }
+
+    fn errors(content: &str, overrides: &[&str]) -> Vec<String> {
+        load(content, overrides).expect_err("configuration must be rejected").0
+    }
+
+    #[test]
+    fn later_sources_win() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let file = format!("{REQUIRED}\n[server]\nhost = \"127.0.0.1\"\nport = 8001\n");
+
+        let config = load(REQUIRED, &[]).unwrap();
+        assert_eq!((config.server.host(), config.server.port()), ("0.0.0.0", 8080));
+
+        let config = load(&file, &[]).unwrap();
+        assert_eq!((config.server.host(), config.server.port()), ("127.0.0.1", 8001));
+
+        std::env::set_var("SERVER_PORT", "8002");
+        let from_env = load(&file, &[]);
+        let from_set = load(&file, &["server.port=8003"]);
+        std::env::remove_var("SERVER_PORT");
+
+        let config = from_env.unwrap();
+        assert_eq!((config.server.host(), config.server.port()), ("127.0.0.1", 8002));
+        let config = from_set.unwrap();
+        assert_eq!((config.server.host(), config.server.port()), ("127.0.0.1", 8003));
+    }
+
+    #[test]
+    fn yaml_files_are_read_like_toml() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let path = file("yaml", "db:\n  uri: sqlite://{name}.db\n  name: app\ntoken:\n  algorithm: HS256\n  secret_key: c2VjcmV0\nmfa:\n  encryption_key: AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\n");
+        let config = Config::load(Some(&path), &[]);
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(config.unwrap().db.url(), "sqlite://app.db");
+    }
+
+    #[test]
+    fn unknown_keys_are_reported() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let errors = errors(&format!("{REQUIRED}\n[server]\nprot = 8001\n"), &["server.hots=localhost"]);
+
+        assert!(errors.iter().any(|error| error.starts_with("server.prot: unknown field `prot`")), "{errors:?}");
+        assert!(errors.contains(&"server.hots: unknown key (--set)".to_string()), "{errors:?}");
+    }
+
+    #[test]
+    fn type_errors_name_their_path() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let file = format!("{REQUIRED}\n[rate_limit.auth]\nrequests = \"many\"\n");
+
+        std::env::set_var("RATE_LIMIT_API_REQUESTS", "lots");
+        let errors = errors(&file, &["server.port=http"]);
+        std::env::remove_var("RATE_LIMIT_API_REQUESTS");
+
+        assert!(errors.iter().any(|error| error.starts_with("rate_limit.auth.requests: invalid type: string \"many\"")), "{errors:?}");
+        assert!(errors.contains(&"rate_limit.api.requests: expected an integer, got \"lots\" (RATE_LIMIT_API_REQUESTS)".to_string()), "{errors:?}");
+        assert!(errors.contains(&"server.port: expected an integer, got \"http\" (--set)".to_string()), "{errors:?}");
+    }
+
+    #[test]
+    fn every_invalid_key_is_reported() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let file = r#"
+            [db]
+            uri = "postgres://{user}@localhost/{name}"
+            min_connections = 10
+            max_connections = 5
+
+            [token]
+            algorithm = "HS1024"
+
+            [rate_limit]
+            store = "redis"
+        "#;
+
+        let errors = errors(file, &[]);
+
+        for expected in [
+            "db.name: must be set, e.g. with DB_NAME",
+            "db.user: must be set, e.g. with DB_USER",
+            "db.min_connections: must not be greater than db.max_connections",
+            "token.algorithm: HS1024 is not supported",
+            "token.secret_key: must be set, e.g. with SECRET_KEY",
+            "rate_limit.store: must be memory, postgres, got redis",
+            "mfa.encryption_key: must be set, e.g. with MFA_ENCRYPTION_KEY",
+        ] {
+            assert!(errors.contains(&expected.to_string()), "{expected:?} not in {errors:?}");
+        }
+    }
+}
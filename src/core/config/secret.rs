@@ -0,0 +1,136 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Stands in for values that must not be shown, like passwords and keys.
+pub const REDACTED: &str = "[redacted]";
+
+
+/// A configuration value that must not end up in logs or responses. `Debug`, `Display` and
+/// `Serialize` all give `REDACTED`, only `expose` gives the value.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// Serializes a connection URI with the password of its userinfo and any `password` query
+/// parameter replaced by `REDACTED`. `{password}` placeholders are kept, they hold nothing.
+pub fn serialize_uri<S: Serializer>(uri: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&redact_uri(uri))
+}
+
+fn redact_uri(uri: &str) -> String {
+    let Some(scheme_end) = uri.find("://").map(|index| index + 3) else {
+        return uri.to_owned();
+    };
+    let (scheme, rest) = uri.split_at(scheme_end);
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, rest) = rest.split_at(authority_end);
+
+    let mut redacted = String::from(scheme);
+    match authority.rsplit_once('@') {
+        Some((userinfo, host)) => {
+            match userinfo.split_once(':') {
+                Some((user, password)) if !password.is_empty() => {
+                    redacted.push_str(user);
+                    redacted.push(':');
+                    redacted.push_str(redact_password(password));
+                }
+                _ => redacted.push_str(userinfo),
+            }
+            redacted.push('@');
+            redacted.push_str(host);
+        }
+        None => redacted.push_str(authority),
+    }
+
+    match rest.split_once('?') {
+        Some((path, query)) => {
+            let (query, fragment) = query.split_once('#').map_or((query, None), |(query, fragment)| (query, Some(fragment)));
+            let query = query
+                .split('&')
+                .map(|pair| match pair.split_once('=') {
+                    Some((key, value)) if key.eq_ignore_ascii_case("password") => format!("{key}={}", redact_password(value)),
+                    _ => pair.to_owned(),
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            redacted.push_str(path);
+            redacted.push('?');
+            redacted.push_str(&query);
+            if let Some(fragment) = fragment {
+                redacted.push('#');
+                redacted.push_str(fragment);
+            }
+        }
+        None => redacted.push_str(rest),
+    }
+
+    redacted
+}
+
+fn redact_password(password: &str) -> &str {
+    if password == "{password}" { password } else { REDACTED }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwords_in_uris_are_redacted() {
+        assert_eq!(redact_uri("postgres://app:hunter2@db:5432/app"), "postgres://app:[redacted]@db:5432/app");
+        assert_eq!(redact_uri("postgres://a:b:c@d@db/app"), "postgres://a:[redacted]@db/app");
+        assert_eq!(
+            redact_uri("postgres://db/app?user=app&PASSWORD=hunter2&sslmode=require"),
+            "postgres://db/app?user=app&PASSWORD=[redacted]&sslmode=require"
+        );
+        assert_eq!(redact_uri("postgres://app:s3cret@db?password=x#f"), "postgres://app:[redacted]@db?password=[redacted]#f");
+    }
+
+    #[test]
+    fn uris_without_a_password_are_kept() {
+        for uri in [
+            "postgres://{user}:{password}@{host}:{port}/{name}",
+            "postgres://app@db/app",
+            "postgres://app:@db/app",
+            "sqlite://data/{name}.db?mode=rwc",
+            "not a uri",
+        ] {
+            assert_eq!(redact_uri(uri), uri);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::env::var;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Str,
+    Int,
+    Bool,
+}
+
+/// Environment variables and the keys they set.
+const ENV_VARS: &[(&str, &str, Kind)] = &[
+    ("DB_URI", "db.uri", Kind::Str),
+    ("DB_NAME", "db.name", Kind::Str),
+    ("DB_HOST", "db.host", Kind::Str),
+    ("DB_USER", "db.user", Kind::Str),
+    ("DB_PASSWORD", "db.password", Kind::Str),
+    ("DB_PORT", "db.port", Kind::Int),
+    ("DB_MAX_CONNECTIONS", "db.max_connections", Kind::Int),
+    ("DB_MIN_CONNECTIONS", "db.min_connections", Kind::Int),
+    ("DB_CONNECTION_TIMEOUT", "db.connect_timeout", Kind::Int),
+    ("DB_ACQUIRE_TIMEOUT", "db.acquire_timeout", Kind::Int),
+    ("DB_IDLE_TIMEOUT", "db.idle_timeout", Kind::Int),
+    ("DB_MAX_LIFETIME", "db.max_lifetime", Kind::Int),
+    ("DB_SQLX_LOGGING", "db.sqlx_logging", Kind::Bool),
+    ("DB_SQLX_LOGGING_LEVEL", "db.sqlx_logging_level", Kind::Int),
+    ("SERVER_HOST", "server.host", Kind::Str),
+    ("SERVER_PORT", "server.port", Kind::Int),
+    ("SERVER_TLS_CERT_PATH", "server.tls_cert_path", Kind::Str),
+    ("SERVER_TLS_KEY_PATH", "server.tls_key_path", Kind::Str),
+    ("SERVER_TLS_CLIENT_CA_PATH", "server.tls_client_ca_path", Kind::Str),
+    ("SERVER_TLS_RELOAD_INTERVAL_SECONDS", "server.tls_reload_interval_seconds", Kind::Int),
+    ("SERVER_HTTP_REDIRECT_PORT", "server.http_redirect_port", Kind::Int),
+    ("SERVER_PUBLIC_URL", "server.public_url", Kind::Str),
+    ("SERVER_SHUTDOWN_DELAY_SECONDS", "server.shutdown_delay_seconds", Kind::Int),
+    ("SERVER_SHUTDOWN_TIMEOUT_SECONDS", "server.shutdown_timeout_seconds", Kind::Int),
+    ("CORS_ALLOWED_ORIGINS", "cors.allowed_origins", Kind::Str),
+    ("CORS_ALLOWED_METHODS", "cors.allowed_methods", Kind::Str),
+    ("CORS_ALLOWED_HEADERS", "cors.allowed_headers", Kind::Str),
+    ("CORS_EXPOSED_HEADERS", "cors.exposed_headers", Kind::Str),
+    ("CORS_MAX_AGE_SECONDS", "cors.max_age_seconds", Kind::Int),
+    ("CORS_ALLOW_CREDENTIALS", "cors.allow_credentials", Kind::Bool),
+    ("ALGORITHM", "token.algorithm", Kind::Str),
+    ("SECRET_KEY", "token.secret_key", Kind::Str),
+    ("PUBLIC_KEY", "token.public_key", Kind::Str),
+    ("JWT_KEYS_DIR", "token.keys_dir", Kind::Str),
+    ("JWT_ACTIVE_KID", "token.active_key_id", Kind::Str),
+    ("JWT_ISSUER", "token.issuer", Kind::Str),
+    ("JWT_AUDIENCE", "token.audience", Kind::Str),
+    ("JWT_LEEWAY_SECONDS", "token.leeway_seconds", Kind::Int),
+    ("JWT_EMBED_CLAIMS", "token.embed_claims", Kind::Str),
+    ("ACCESS_TOKEN_EXPIRE_SECONDS", "token.access_token_expire_seconds", Kind::Int),
+    ("REFRESH_TOKEN_EXPIRE_SECONDS", "token.refresh_token_expire_seconds", Kind::Int),
+    ("TOKEN_CACHE_TTL_SECONDS", "token.cache_ttl_seconds", Kind::Int),
+    ("LOGIN_MAX_ATTEMPTS", "login.max_attempts", Kind::Int),
+    ("LOGIN_IP_MAX_ATTEMPTS", "login.ip_max_attempts", Kind::Int),
+    ("LOGIN_ATTEMPT_WINDOW_SECONDS", "login.attempt_window_seconds", Kind::Int),
+    ("LOGIN_LOCKOUT_SECONDS", "login.lockout_seconds", Kind::Int),
+    ("LOGIN_LOCKOUT_MAX_SECONDS", "login.lockout_max_seconds", Kind::Int),
+    ("LOGIN_REQUIRE_VERIFIED_EMAIL", "login.require_verified_email", Kind::Bool),
+    ("LOGIN_MFA_TOKEN_EXPIRE_SECONDS", "login.mfa_token_expire_seconds", Kind::Int),
+    ("MFA_ISSUER", "mfa.issuer", Kind::Str),
+    ("MFA_ENCRYPTION_KEY", "mfa.encryption_key", Kind::Str),
+    ("PASSWORD_MIN_LENGTH", "password.min_length", Kind::Int),
+    ("PASSWORD_REQUIRE_LOWERCASE", "password.require_lowercase", Kind::Bool),
+    ("PASSWORD_REQUIRE_UPPERCASE", "password.require_uppercase", Kind::Bool),
+    ("PASSWORD_REQUIRE_DIGIT", "password.require_digit", Kind::Bool),
+    ("PASSWORD_REQUIRE_SYMBOL", "password.require_symbol", Kind::Bool),
+    ("USER_PURGE_RETENTION_SECONDS", "purge.retention_seconds", Kind::Int),
+    ("USER_PURGE_INTERVAL_SECONDS", "purge.interval_seconds", Kind::Int),
+    ("MAIL_TRANSPORT", "mail.transport", Kind::Str),
+    ("MAIL_FROM", "mail.from", Kind::Str),
+    ("SMTP_HOST", "mail.smtp_host", Kind::Str),
+    ("SMTP_PORT", "mail.smtp_port", Kind::Int),
+    ("SMTP_TLS", "mail.smtp_tls", Kind::Str),
+    ("SMTP_USERNAME", "mail.smtp_username", Kind::Str),
+    ("SMTP_PASSWORD", "mail.smtp_password", Kind::Str),
+    ("MAIL_FILE_PATH", "mail.file_path", Kind::Str),
+    ("EMAIL_VERIFICATION_URL", "mail.verification_url", Kind::Str),
+    ("EMAIL_VERIFICATION_EXPIRE_SECONDS", "mail.verification_expire_seconds", Kind::Int),
+    ("PASSWORD_RESET_URL", "mail.password_reset_url", Kind::Str),
+    ("PASSWORD_RESET_EXPIRE_SECONDS", "mail.password_reset_expire_seconds", Kind::Int),
+    ("RATE_LIMIT_ENABLED", "rate_limit.enabled", Kind::Bool),
+    ("RATE_LIMIT_STORE", "rate_limit.store", Kind::Str),
+    ("RATE_LIMIT_AUTH_REQUESTS", "rate_limit.auth.requests", Kind::Int),
+    ("RATE_LIMIT_AUTH_WINDOW_SECONDS", "rate_limit.auth.window_seconds", Kind::Int),
+    ("RATE_LIMIT_AUTH_KEY", "rate_limit.auth.key", Kind::Str),
+    ("RATE_LIMIT_API_REQUESTS", "rate_limit.api.requests", Kind::Int),
+    ("RATE_LIMIT_API_WINDOW_SECONDS", "rate_limit.api.window_seconds", Kind::Int),
+    ("RATE_LIMIT_API_KEY", "rate_limit.api.key", Kind::Str),
+    ("OIDC_REDIRECT_URL", "oidc.redirect_url", Kind::Str),
+    ("OIDC_LOGIN_EXPIRE_SECONDS", "oidc.login_expire_seconds", Kind::Int),
+];
+
+/// Variables of each OIDC provider after the `OIDC_<NAME>_` prefix, and the keys they set.
+const OIDC_PROVIDER_VARS: &[(&str, &str, Kind)] = &[
+    ("ISSUER", "issuer", Kind::Str),
+    ("CLIENT_ID", "client_id", Kind::Str),
+    ("CLIENT_SECRET", "client_secret", Kind::Str),
+    ("SCOPES", "scopes", Kind::Str),
+    ("AUTO_PROVISION", "auto_provision", Kind::Bool),
+    ("LINK_BY_EMAIL", "link_by_email", Kind::Bool),
+];
+
+/// Keys that can be read from a file instead, with `<KEY>_FILE` variables or `<key>_file` keys
+/// like Docker and Kubernetes secrets are mounted. `client_secret` of every OIDC provider too.
+const FILE_KEYS: &[&str] = &["db.password", "token.secret_key", "token.public_key", "mfa.encryption_key", "mail.smtp_password"];
+
+fn provider_prefix(name: &str) -> String {
+    format!("OIDC_{}", name.to_uppercase().replace('-', "_"))
+}
+
+/// Name and field of an `oidc.providers.<name>.<field>` key.
+fn provider_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("oidc.providers.").and_then(|rest| rest.rsplit_once('.'))
+}
+
+/// The variable that sets `key`, for error messages.
+pub fn env_var(key: &str) -> Option<String> {
+    if let Some((name, field)) = provider_key(key) {
+        return OIDC_PROVIDER_VARS
+            .iter()
+            .find(|(_, provider_field, _)| *provider_field == field)
+            .map(|(suffix, _, _)| format!("{}_{suffix}", provider_prefix(name)));
+    }
+
+    ENV_VARS.iter().find(|(_, env_key, _)| *env_key == key).map(|(name, _, _)| name.to_string())
+}
+
+fn kind(key: &str) -> Option<Kind> {
+    if key.strip_suffix("_file").is_some_and(is_file_key) {
+        return Some(Kind::Str);
+    }
+    if let Some((_, field)) = provider_key(key) {
+        return OIDC_PROVIDER_VARS.iter().find(|(_, provider_field, _)| *provider_field == field).map(|(_, _, kind)| *kind);
+    }
+
+    ENV_VARS.iter().find(|(_, env_key, _)| *env_key == key).map(|(_, _, kind)| *kind)
+}
+
+fn is_file_key(key: &str) -> bool {
+    FILE_KEYS.contains(&key) || provider_key(key).is_some_and(|(_, field)| field == "client_secret")
+}
+
+/// Reads a secret, without the line break editors and `echo` leave at the end.
+fn read_secret(path: &str) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map(|secret| secret.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|error| format!("{path}: {error}"))
+}
+
+/// Replaces `<key>_file` entries of `layer` with the content of the file they name.
+/// `source` tells where an entry came from, for error messages.
+fn read_secret_files(layer: &mut Value, errors: &mut Vec<String>, source: impl Fn(&str) -> String) {
+    let providers = layer
+        .pointer("/oidc/providers")
+        .and_then(Value::as_object)
+        .map(|providers| providers.keys().map(|name| format!("oidc.providers.{name}.client_secret")).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    for key in FILE_KEYS.iter().map(|key| key.to_string()).chain(providers) {
+        let Some((parent, field)) = key.rsplit_once('.') else { continue };
+        let Some(map) = layer.pointer_mut(&format!("/{}", parent.replace('.', "/"))).and_then(Value::as_object_mut) else {
+            continue;
+        };
+        let Some(path) = map.remove(&format!("{field}_file")) else { continue };
+
+        if map.contains_key(field) {
+            errors.push(format!("{key}: set either the value or a file ({})", source(&key)));
+            continue;
+        }
+        match path.as_str().map(read_secret) {
+            Some(Ok(secret)) => {
+                map.insert(field.into(), Value::String(secret));
+            },
+            Some(Err(error)) => errors.push(format!("{key}: {error} ({})", source(&key))),
+            None => errors.push(format!("{key}_file: expected a path ({})", source(&key))),
+        }
+    }
+}
+
+fn parse(kind: Kind, value: &str) -> Result<Value, String> {
+    match kind {
+        Kind::Str => Ok(Value::String(value.into())),
+        Kind::Int => value.trim().parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("expected an integer, got {value:?}")),
+        Kind::Bool => value.trim().parse::<bool>()
+            .map(Value::from)
+            .map_err(|_| format!("expected true or false, got {value:?}")),
+    }
+}
+
+fn set(tree: &mut Value, key: &str, value: Value) {
+    let mut node = tree;
+    for part in key.split('.') {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = &mut node[part];
+    }
+    *node = value;
+}
+
+/// Merges `layer` into `base`, its values replace the ones of `base` except for tables, which get merged.
+pub fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Puts the default of `path` back, or removes the key when it has none. Returns false
+/// when the path can't be followed, so the value can't be fixed.
+pub fn reset(tree: &mut Value, defaults: &Value, path: &str) -> bool {
+    let parts: Vec<&str> = path.split('.').filter(|part| !part.is_empty()).collect();
+    let Some((last, parents)) = parts.split_last() else {
+        return false;
+    };
+
+    let mut node = tree;
+    for part in parents {
+        match node.get_mut(*part) {
+            Some(child) => node = child,
+            None => return false,
+        }
+    }
+    let Some(map) = node.as_object_mut() else {
+        return false;
+    };
+
+    match defaults.pointer(&format!("/{}", parts.join("/"))) {
+        Some(default) => map.insert(last.to_string(), default.clone()).is_some(),
+        None => map.remove(*last).is_some(),
+    }
+}
+
+/// Reads a TOML or YAML file, told apart by its extension.
+pub fn file(path: &Path, errors: &mut Vec<String>) -> Value {
+    let name = path.display();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) => {
+            errors.push(format!("{name}: {error}"));
+            return Value::Object(Map::new());
+        },
+    };
+
+    let value = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str::<Value>(&content).map_err(|error| error.message().to_string()),
+        Some("yaml" | "yml") => serde_yaml::from_str::<Value>(&content).map_err(|error| error.to_string()),
+        _ => Err("must be a .toml, .yaml or .yml file".into()),
+    };
+
+    match value {
+        Ok(mut value @ Value::Object(_)) => {
+            read_secret_files(&mut value, errors, |_| name.to_string());
+            value
+        },
+        Ok(_) => {
+            errors.push(format!("{name}: must hold a table of sections"));
+            Value::Object(Map::new())
+        },
+        Err(error) => {
+            errors.push(format!("{name}: {error}"));
+            Value::Object(Map::new())
+        },
+    }
+}
+
+/// Reads the known variables. `OIDC_PROVIDERS` adds providers to the ones `tree` already has,
+/// and `OIDC_<NAME>_*` variables configure each of them.
+pub fn env(tree: &Value, errors: &mut Vec<String>) -> Value {
+    let mut layer = Value::Object(Map::new());
+
+    for (name, key, kind) in ENV_VARS {
+        read(&mut layer, errors, name, key, *kind);
+    }
+
+    let mut providers: Vec<String> = tree
+        .pointer("/oidc/providers")
+        .and_then(Value::as_object)
+        .map(|providers| providers.keys().cloned().collect())
+        .unwrap_or_default();
+    providers.extend(
+        var("OIDC_PROVIDERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_lowercase)
+    );
+    providers.sort();
+    providers.dedup();
+
+    for name in providers {
+        set(&mut layer, &format!("oidc.providers.{name}"), Value::Object(Map::new()));
+        let prefix = provider_prefix(&name);
+        for (suffix, field, kind) in OIDC_PROVIDER_VARS {
+            read(&mut layer, errors, &format!("{prefix}_{suffix}"), &format!("oidc.providers.{name}.{field}"), *kind);
+        }
+    }
+
+    read_secret_files(&mut layer, errors, |key| format!("{}_FILE", env_var(key).unwrap_or_default()));
+    layer
+}
+
+fn read(layer: &mut Value, errors: &mut Vec<String>, name: &str, key: &str, kind: Kind) {
+    if is_file_key(key) {
+        if let Ok(path) = var(format!("{name}_FILE")) {
+            set(layer, &format!("{key}_file"), Value::String(path));
+        }
+    }
+    let Ok(value) = var(name) else { return };
+
+    match parse(kind, &value) {
+        Ok(value) => set(layer, key, value),
+        Err(error) => errors.push(format!("{key}: {error} ({name})")),
+    }
+}
+
+/// Reads `key=value` overrides of the command line.
+pub fn overrides(overrides: &[String], errors: &mut Vec<String>) -> Value {
+    let mut layer = Value::Object(Map::new());
+
+    for entry in overrides {
+        let Some((key, value)) = entry.split_once('=') else {
+            errors.push(format!("--set {entry}: expected key=value"));
+            continue;
+        };
+        let key = key.trim();
+        match kind(key).map(|kind| parse(kind, value)) {
+            Some(Ok(value)) => set(&mut layer, key, value),
+            Some(Err(error)) => errors.push(format!("{key}: {error} (--set)")),
+            None => errors.push(format!("{key}: unknown key (--set)")),
+        }
+    }
+
+    read_secret_files(&mut layer, errors, |_| "--set".into());
+    layer
+}
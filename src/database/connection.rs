@@ -1,6 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
 
 use crate::core::config::DBConfig;
 
@@ -22,10 +22,16 @@ pub fn connection_options(config: DBConfig) -> ConnectOptions {
 }
 
 
-pub async fn make_connection(options: ConnectOptions) -> Arc<DatabaseConnection> {
-    let connection = Database::connect(options).await.unwrap();
+pub async fn make_connection(options: ConnectOptions) -> Result<Arc<DatabaseConnection>, DbErr> {
+    let connection = Database::connect(options).await?;
 
-    Arc::new(connection)
+    Ok(Arc::new(connection))
 }
 
-
+/// Waits for the connections in use to be returned, then closes the pool, which clones share.
+pub async fn close_connection(connection: &DatabaseConnection) -> Result<(), DbErr> {
+    match connection {
+        DatabaseConnection::SqlxPostgresPoolConnection(pool) => pool.clone().close().await,
+        _ => Ok(()),
+    }
+}
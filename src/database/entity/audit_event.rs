@@ -0,0 +1,98 @@
+use sea_orm::{entity::prelude::*, ActiveValue};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use chrono::{Utc, DateTime};
+
+
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(64))")]
+pub enum AuditAction {
+    #[sea_orm(string_value = "user.create")]
+    #[serde(rename = "user.create")]
+    UserCreate,
+    #[sea_orm(string_value = "user.update")]
+    #[serde(rename = "user.update")]
+    UserUpdate,
+    #[sea_orm(string_value = "user.delete")]
+    #[serde(rename = "user.delete")]
+    UserDelete,
+    #[sea_orm(string_value = "user.restore")]
+    #[serde(rename = "user.restore")]
+    UserRestore,
+    #[sea_orm(string_value = "user.role_change")]
+    #[serde(rename = "user.role_change")]
+    UserRoleChange,
+    #[sea_orm(string_value = "auth.login")]
+    #[serde(rename = "auth.login")]
+    Login,
+    #[sea_orm(string_value = "auth.login_failed")]
+    #[serde(rename = "auth.login_failed")]
+    LoginFailed,
+    #[sea_orm(string_value = "auth.logout")]
+    #[serde(rename = "auth.logout")]
+    Logout,
+    #[sea_orm(string_value = "auth.email_verify")]
+    #[serde(rename = "auth.email_verify")]
+    EmailVerify,
+    #[sea_orm(string_value = "auth.password_reset")]
+    #[serde(rename = "auth.password_reset")]
+    PasswordReset,
+    #[sea_orm(string_value = "auth.mfa_enable")]
+    #[serde(rename = "auth.mfa_enable")]
+    MfaEnable,
+    #[sea_orm(string_value = "role.create")]
+    #[serde(rename = "role.create")]
+    RoleCreate,
+    #[sea_orm(string_value = "role.permissions_change")]
+    #[serde(rename = "role.permissions_change")]
+    RolePermissionsChange,
+    #[sea_orm(string_value = "api_key.create")]
+    #[serde(rename = "api_key.create")]
+    ApiKeyCreate,
+    #[sea_orm(string_value = "api_key.delete")]
+    #[serde(rename = "api_key.delete")]
+    ApiKeyDelete,
+    #[sea_orm(string_value = "session.revoke")]
+    #[serde(rename = "session.revoke")]
+    SessionRevoke,
+    #[sea_orm(string_value = "session.revoke_all")]
+    #[serde(rename = "session.revoke_all")]
+    SessionRevokeAll,
+}
+
+/// One row of the append-only audit log. `actor_id` is who acted and `target_id`
+/// the user acted on, or the role for `role.*` events; neither is a foreign key,
+/// so events outlive purged users.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_event")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub target_id: Option<Uuid>,
+    /// Changed fields as `{"field": {"before": .., "after": ..}}`
+    pub changes: Option<Json>,
+    #[sea_orm(column_type = "String(Some(45))", nullable)]
+    pub ip: Option<String>,
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub user_agent: Option<String>,
+    #[sea_orm(column_type = "String(Some(64))", nullable)]
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            created_at: ActiveValue::Set(Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
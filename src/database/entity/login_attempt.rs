@@ -0,0 +1,40 @@
+use sea_orm::{entity::prelude::*, ActiveValue};
+use uuid::Uuid;
+use chrono::{Utc, DateTime};
+
+
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum AttemptScope {
+    #[sea_orm(string_value = "login")]
+    Login,
+    #[sea_orm(string_value = "ip")]
+    Ip,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "login_attempt")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub scope: AttemptScope,
+    #[sea_orm(column_type = "String(Some(255))")]
+    pub key: String,
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            updated_at: ActiveValue::Set(Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
@@ -1 +1,16 @@
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod role;
+pub mod permission;
+pub mod role_permission;
+pub mod refresh_token;
+pub mod revoked_token;
+pub mod login_attempt;
+pub mod rate_limit;
+pub mod email_verification;
+pub mod password_reset;
+pub mod mfa_secret;
+pub mod mfa_recovery_code;
+pub mod user_identity;
+pub mod api_key;
+pub mod session;
+pub mod audit_event;
\ No newline at end of file
@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "permission")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_type = "String(Some(64))", unique)]
+    pub name: String,
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::role_permission::Entity")]
+    RolePermission,
+}
+
+impl Related<super::role::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::role_permission::Relation::Role.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::role_permission::Relation::Permission.def().rev())
+    }
+}
+
+
+impl ActiveModelBehavior for ActiveModel {}
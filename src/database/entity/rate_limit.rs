@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "rate_limit")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false, column_type = "String(Some(255))")]
+    pub key: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub window_start: i64,
+    pub hits: i64,
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -2,20 +2,11 @@ use std::pin::Pin;
 use std::future::Future;
 
 use sea_orm::{entity::prelude::*, ActiveValue};
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
 use uuid::Uuid;
 use chrono::{Utc, DateTime};
 
+use super::role::USER_ROLE_ID;
 
-#[derive(Clone, Debug, EnumIter, PartialEq, DeriveActiveEnum, Deserialize, Serialize, ToSchema)]
-#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "role")]
-pub enum Role {
-    #[sea_orm(string_value = "Admin")]
-    Admin,
-    #[sea_orm(string_value = "User")]
-    User,
-}
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "user")]
@@ -26,13 +17,31 @@ pub struct Model {
     pub login: String,
     #[sea_orm(column_type = "String(Some(255))")]
     pub password: String,
-    pub role: Role,
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub email: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub role_id: Uuid,
+    pub token_version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Copy, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::role::Entity",
+        from = "Column::RoleId",
+        to = "super::role::Column::Id"
+    )]
+    Role,
+}
+
+impl Related<super::role::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Role.def()
+    }
+}
 
 
 impl ActiveModelBehavior for ActiveModel {
@@ -41,7 +50,8 @@ impl ActiveModelBehavior for ActiveModel {
             id: ActiveValue::Set(Uuid::new_v4()),
             created_at: ActiveValue::Set(Utc::now()),
             updated_at: ActiveValue::Set(Utc::now()),
-            role: ActiveValue::Set(Role::User),
+            role_id: ActiveValue::Set(USER_ROLE_ID),
+            token_version: ActiveValue::Set(0),
             ..ActiveModelTrait::default()
 
         }
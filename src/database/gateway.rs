@@ -3,6 +3,18 @@ use std::sync::Arc;
 use sea_orm::ConnectionTrait;
 
 use crate::database::repositories::user::UserRepository;
+use crate::database::repositories::role::RoleRepository;
+use crate::database::repositories::refresh_token::RefreshTokenRepository;
+use crate::database::repositories::revoked_token::RevokedTokenRepository;
+use crate::database::repositories::login_attempt::LoginAttemptRepository;
+use crate::database::repositories::rate_limit::RateLimitRepository;
+use crate::database::repositories::email_verification::EmailVerificationRepository;
+use crate::database::repositories::password_reset::PasswordResetRepository;
+use crate::database::repositories::mfa::MfaRepository;
+use crate::database::repositories::user_identity::UserIdentityRepository;
+use crate::database::repositories::api_key::ApiKeyRepository;
+use crate::database::repositories::session::SessionRepository;
+use crate::database::repositories::audit_event::AuditEventRepository;
 
 use crate::database::repositories::base::Repository;
 
@@ -18,7 +30,55 @@ where Conn: ConnectionTrait + Send + Sync
         Self { conn }
     }
 
-    pub fn user(&self) -> Arc<UserRepository<Conn>> {
+    pub fn user(&self) -> Arc<UserRepository<'a, Conn>> {
         Arc::new(UserRepository::new(self.conn))
     }
+
+    pub fn role(&self) -> Arc<RoleRepository<'a, Conn>> {
+        Arc::new(RoleRepository::new(self.conn))
+    }
+
+    pub fn refresh_token(&self) -> Arc<RefreshTokenRepository<'a, Conn>> {
+        Arc::new(RefreshTokenRepository::new(self.conn))
+    }
+
+    pub fn revoked_token(&self) -> Arc<RevokedTokenRepository<'a, Conn>> {
+        Arc::new(RevokedTokenRepository::new(self.conn))
+    }
+
+    pub fn login_attempt(&self) -> Arc<LoginAttemptRepository<'a, Conn>> {
+        Arc::new(LoginAttemptRepository::new(self.conn))
+    }
+
+    pub fn rate_limit(&self) -> Arc<RateLimitRepository<'a, Conn>> {
+        Arc::new(RateLimitRepository::new(self.conn))
+    }
+
+    pub fn email_verification(&self) -> Arc<EmailVerificationRepository<'a, Conn>> {
+        Arc::new(EmailVerificationRepository::new(self.conn))
+    }
+
+    pub fn password_reset(&self) -> Arc<PasswordResetRepository<'a, Conn>> {
+        Arc::new(PasswordResetRepository::new(self.conn))
+    }
+
+    pub fn mfa(&self) -> Arc<MfaRepository<'a, Conn>> {
+        Arc::new(MfaRepository::new(self.conn))
+    }
+
+    pub fn user_identity(&self) -> Arc<UserIdentityRepository<'a, Conn>> {
+        Arc::new(UserIdentityRepository::new(self.conn))
+    }
+
+    pub fn api_key(&self) -> Arc<ApiKeyRepository<'a, Conn>> {
+        Arc::new(ApiKeyRepository::new(self.conn))
+    }
+
+    pub fn session(&self) -> Arc<SessionRepository<'a, Conn>> {
+        Arc::new(SessionRepository::new(self.conn))
+    }
+
+    pub fn audit_event(&self) -> Arc<AuditEventRepository<'a, Conn>> {
+        Arc::new(AuditEventRepository::new(self.conn))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{prelude::*, ActiveValue, Condition, QueryOrder};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::api_key::{self, ActiveModel, Entity as ApiKey, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewApiKey {
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl IntoActiveModel for NewApiKey {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.name = ActiveValue::Set(self.name);
+        model.prefix = ActiveValue::Set(self.prefix);
+        model.key_hash = ActiveValue::Set(self.key_hash);
+        model.scopes = ActiveValue::Set(self.scopes);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_key: NewApiKey) -> Result<Model, anyhow::Error> {
+        let key = new_key.into_active_model().insert(self.conn).await?;
+
+        Ok(key)
+    }
+
+    /// Records a use of the key unless one was recorded after `since`, to keep writes per key bounded.
+    pub async fn touch(&self, id: Uuid, since: DateTime<Utc>) -> Result<u64, anyhow::Error> {
+        let result = ApiKey::update_many()
+            .col_expr(api_key::Column::LastUsedAt, Expr::value(Utc::now()))
+            .filter(api_key::Column::Id.eq(id))
+            .filter(
+                Condition::any()
+                    .add(api_key::Column::LastUsedAt.is_null())
+                    .add(api_key::Column::LastUsedAt.lt(since))
+            )
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = ApiKey::delete_many()
+            .filter(api_key::Column::Id.eq(id))
+            .filter(api_key::Column::UserId.eq(user_id))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get_by_prefix(&self, prefix: &str) -> Result<Option<Model>, anyhow::Error> {
+        let key = ApiKey::find()
+            .filter(api_key::Column::Prefix.eq(prefix))
+            .one(self.conn)
+            .await?;
+
+        Ok(key)
+    }
+
+    pub async fn get_for_user(&self, user_id: Uuid) -> Result<Vec<Model>, anyhow::Error> {
+        let keys = ApiKey::find()
+            .filter(api_key::Column::UserId.eq(user_id))
+            .order_by_asc(api_key::Column::CreatedAt)
+            .all(self.conn)
+            .await?;
+
+        Ok(keys)
+    }
+}
+
+#[derive(Clone)]
+pub struct ApiKeyRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for ApiKeyRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> ApiKeyRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
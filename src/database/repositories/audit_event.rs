@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{prelude::*, ActiveValue, Condition, QueryOrder, QuerySelect};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::audit_event::{self, ActiveModel, AuditAction, Entity as AuditEvent, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewAuditEvent {
+    pub actor_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub target_id: Option<Uuid>,
+    pub changes: Option<Json>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    fn condition(&self) -> Condition {
+        Condition::all()
+            .add_option(self.actor_id.map(|id| audit_event::Column::ActorId.eq(id)))
+            .add_option(self.target_id.map(|id| audit_event::Column::TargetId.eq(id)))
+            .add_option(self.action.map(|action| audit_event::Column::Action.eq(action)))
+            .add_option(self.from.map(|from| audit_event::Column::CreatedAt.gte(from)))
+            .add_option(self.to.map(|to| audit_event::Column::CreatedAt.lt(to)))
+    }
+}
+
+impl IntoActiveModel for NewAuditEvent {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.actor_id = ActiveValue::Set(self.actor_id);
+        model.action = ActiveValue::Set(self.action);
+        model.target_id = ActiveValue::Set(self.target_id);
+        model.changes = ActiveValue::Set(self.changes);
+        model.ip = ActiveValue::Set(self.ip);
+        model.user_agent = ActiveValue::Set(self.user_agent);
+        model.request_id = ActiveValue::Set(self.request_id);
+
+        model
+    }
+}
+
+/// Only inserts: the table rejects updates and deletes.
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_event: NewAuditEvent) -> Result<Model, anyhow::Error> {
+        let event = new_event.into_active_model().insert(self.conn).await?;
+
+        Ok(event)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get_many(
+        &self, filter: &AuditFilter, offset: Option<u64>, limit: Option<u64>
+    ) -> Result<Vec<Model>, anyhow::Error> {
+        let events = AuditEvent::find()
+            .filter(filter.condition())
+            .order_by_desc(audit_event::Column::CreatedAt)
+            .offset(offset)
+            .limit(limit)
+            .all(self.conn)
+            .await?;
+
+        Ok(events)
+    }
+
+    pub async fn count(&self, filter: &AuditFilter) -> Result<u64, anyhow::Error> {
+        let count = AuditEvent::find().filter(filter.condition()).count(self.conn).await?;
+
+        Ok(count)
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditEventRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for AuditEventRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> AuditEventRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::email_verification::{self, ActiveModel, Entity as EmailVerification, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewEmailVerification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IntoActiveModel for NewEmailVerification {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.id = ActiveValue::Set(self.id);
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.email = ActiveValue::Set(self.email);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_verification: NewEmailVerification) -> Result<Model, anyhow::Error> {
+        let verification = new_verification.into_active_model().insert(self.conn).await?;
+
+        Ok(verification)
+    }
+
+    /// Marks the token as used only if it is unused and not expired, so it can't be redeemed twice.
+    pub async fn mark_used(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let now = Utc::now();
+        let result = EmailVerification::update_many()
+            .col_expr(email_verification::Column::UsedAt, Expr::value(now))
+            .filter(email_verification::Column::Id.eq(id))
+            .filter(email_verification::Column::UsedAt.is_null())
+            .filter(email_verification::Column::ExpiresAt.gt(now))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Invalidates every pending token of the user.
+    pub async fn invalidate_for_user(&self, user_id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = EmailVerification::update_many()
+            .col_expr(email_verification::Column::UsedAt, Expr::value(Utc::now()))
+            .filter(email_verification::Column::UserId.eq(user_id))
+            .filter(email_verification::Column::UsedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
+        let verification = EmailVerification::find_by_id(id).one(self.conn).await?;
+
+        Ok(verification)
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailVerificationRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for EmailVerificationRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> EmailVerificationRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
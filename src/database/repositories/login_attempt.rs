@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{prelude::*, ActiveValue, Condition};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::login_attempt::{self, ActiveModel, AttemptScope, Entity as LoginAttempt, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct SaveLoginAttempt {
+    pub scope: AttemptScope,
+    pub key: String,
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl IntoActiveModel for SaveLoginAttempt {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.scope = ActiveValue::Set(self.scope);
+        model.key = ActiveValue::Set(self.key);
+        model.failures = ActiveValue::Set(self.failures);
+        model.locked_until = ActiveValue::Set(self.locked_until);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    /// Atomically counts a failure and returns the failures counted so far. The count starts over,
+    /// and a lockout is lifted, when the last failure was not after `window_start`.
+    pub async fn add_failure(&self, scope: AttemptScope, key: String, window_start: DateTime<Utc>) -> Result<i32, anyhow::Error> {
+        let model = SaveLoginAttempt { scope, key, failures: 1, locked_until: None }.into_active_model();
+        let recent = Expr::col((LoginAttempt, login_attempt::Column::UpdatedAt)).gt(window_start);
+
+        let result = LoginAttempt::insert(model)
+            .on_conflict(
+                OnConflict::columns([login_attempt::Column::Scope, login_attempt::Column::Key])
+                    .value(
+                        login_attempt::Column::Failures,
+                        Expr::case(recent.clone(), Expr::col((LoginAttempt, login_attempt::Column::Failures)).add(1)).finally(1)
+                    )
+                    .value(
+                        login_attempt::Column::LockedUntil,
+                        Expr::case(recent, Expr::col((LoginAttempt, login_attempt::Column::LockedUntil)))
+                            .finally(Expr::value(Option::<DateTime<Utc>>::None))
+                    )
+                    .update_column(login_attempt::Column::UpdatedAt)
+                    .to_owned()
+            )
+            .exec_with_returning(self.conn)
+            .await?;
+
+        Ok(result.failures)
+    }
+
+    /// Locks the key until `locked_until`, unless it is already locked for longer.
+    pub async fn lock(&self, scope: AttemptScope, key: String, locked_until: DateTime<Utc>) -> Result<(), anyhow::Error> {
+        LoginAttempt::update_many()
+            .col_expr(login_attempt::Column::LockedUntil, Expr::value(locked_until))
+            .filter(login_attempt::Column::Scope.eq(scope))
+            .filter(login_attempt::Column::Key.eq(key))
+            .filter(
+                Condition::any()
+                    .add(login_attempt::Column::LockedUntil.is_null())
+                    .add(login_attempt::Column::LockedUntil.lt(locked_until))
+            )
+            .exec(self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, scope: AttemptScope, key: String) -> Result<u64, anyhow::Error> {
+        let result = LoginAttempt::delete_many()
+            .filter(login_attempt::Column::Scope.eq(scope))
+            .filter(login_attempt::Column::Key.eq(key))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, scope: AttemptScope, key: String) -> Result<Option<Model>, anyhow::Error> {
+        let attempt = LoginAttempt::find()
+            .filter(login_attempt::Column::Scope.eq(scope))
+            .filter(login_attempt::Column::Key.eq(key))
+            .one(self.conn)
+            .await?;
+
+        Ok(attempt)
+    }
+}
+
+#[derive(Clone)]
+pub struct LoginAttemptRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for LoginAttemptRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> LoginAttemptRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
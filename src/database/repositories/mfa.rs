@@ -0,0 +1,155 @@
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{prelude::*, ActiveValue, Condition};
+
+use crate::database::entity::mfa_recovery_code::{self, Entity as MfaRecoveryCode, Model as RecoveryCodeModel};
+use crate::database::entity::mfa_secret::{self, Entity as MfaSecret, Model as SecretModel};
+use super::base::Repository;
+
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    /// Stores a new unconfirmed secret, replacing the previous one.
+    pub async fn save_secret(&self, user_id: Uuid, secret: String) -> Result<SecretModel, anyhow::Error> {
+        let mut model = mfa_secret::ActiveModel::new();
+        model.user_id = ActiveValue::Set(user_id);
+        model.secret = ActiveValue::Set(secret);
+        model.last_used_step = ActiveValue::Set(None);
+        model.confirmed_at = ActiveValue::Set(None);
+
+        let result = MfaSecret::insert(model)
+            .on_conflict(
+                OnConflict::column(mfa_secret::Column::UserId)
+                    .update_columns([
+                        mfa_secret::Column::Secret,
+                        mfa_secret::Column::LastUsedStep,
+                        mfa_secret::Column::ConfirmedAt,
+                        mfa_secret::Column::CreatedAt,
+                    ])
+                    .to_owned()
+            )
+            .exec_with_returning(self.conn)
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn confirm(&self, user_id: Uuid, step: i64) -> Result<u64, anyhow::Error> {
+        let result = MfaSecret::update_many()
+            .col_expr(mfa_secret::Column::ConfirmedAt, Expr::value(Utc::now()))
+            .col_expr(mfa_secret::Column::LastUsedStep, Expr::value(step))
+            .filter(mfa_secret::Column::UserId.eq(user_id))
+            .filter(mfa_secret::Column::ConfirmedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Records the time step of an accepted code only if it is newer than the last one, so codes can't be replayed.
+    pub async fn use_step(&self, user_id: Uuid, step: i64) -> Result<u64, anyhow::Error> {
+        let result = MfaSecret::update_many()
+            .col_expr(mfa_secret::Column::LastUsedStep, Expr::value(step))
+            .filter(mfa_secret::Column::UserId.eq(user_id))
+            .filter(
+                Condition::any()
+                    .add(mfa_secret::Column::LastUsedStep.is_null())
+                    .add(mfa_secret::Column::LastUsedStep.lt(step))
+            )
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn replace_recovery_codes(&self, user_id: Uuid, code_hashes: Vec<String>) -> Result<(), anyhow::Error> {
+        MfaRecoveryCode::delete_many()
+            .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+            .exec(self.conn)
+            .await?;
+
+        let models: Vec<mfa_recovery_code::ActiveModel> = code_hashes
+            .into_iter()
+            .map(|code_hash| {
+                let mut model = mfa_recovery_code::ActiveModel::new();
+                model.user_id = ActiveValue::Set(user_id);
+                model.code_hash = ActiveValue::Set(code_hash);
+                model
+            })
+            .collect();
+
+        MfaRecoveryCode::insert_many(models)
+            .on_empty_do_nothing()
+            .exec(self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn use_recovery_code(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = MfaRecoveryCode::update_many()
+            .col_expr(mfa_recovery_code::Column::UsedAt, Expr::value(Utc::now()))
+            .filter(mfa_recovery_code::Column::Id.eq(id))
+            .filter(mfa_recovery_code::Column::UsedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get_secret(&self, user_id: Uuid) -> Result<Option<SecretModel>, anyhow::Error> {
+        let secret = MfaSecret::find_by_id(user_id).one(self.conn).await?;
+
+        Ok(secret)
+    }
+
+    pub async fn get_unused_recovery_codes(&self, user_id: Uuid) -> Result<Vec<RecoveryCodeModel>, anyhow::Error> {
+        let codes = MfaRecoveryCode::find()
+            .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+            .filter(mfa_recovery_code::Column::UsedAt.is_null())
+            .all(self.conn)
+            .await?;
+
+        Ok(codes)
+    }
+}
+
+#[derive(Clone)]
+pub struct MfaRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for MfaRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> MfaRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
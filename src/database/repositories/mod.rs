@@ -1,3 +1,15 @@
 pub mod base;
 pub mod user;
-pub mod macros;
\ No newline at end of file
+pub mod role;
+pub mod refresh_token;
+pub mod revoked_token;
+pub mod login_attempt;
+pub mod rate_limit;
+pub mod email_verification;
+pub mod password_reset;
+pub mod mfa;
+pub mod user_identity;
+pub mod api_key;
+pub mod session;
+pub mod macros;
+pub mod audit_event;
\ No newline at end of file
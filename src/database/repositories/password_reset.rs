@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::password_reset::{self, ActiveModel, Entity as PasswordReset, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewPasswordReset {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IntoActiveModel for NewPasswordReset {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.id = ActiveValue::Set(self.id);
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.token_hash = ActiveValue::Set(self.token_hash);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_reset: NewPasswordReset) -> Result<Model, anyhow::Error> {
+        let reset = new_reset.into_active_model().insert(self.conn).await?;
+
+        Ok(reset)
+    }
+
+    /// Marks the token as used only if it is unused and not expired, so it can't be redeemed twice.
+    pub async fn mark_used(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let now = Utc::now();
+        let result = PasswordReset::update_many()
+            .col_expr(password_reset::Column::UsedAt, Expr::value(now))
+            .filter(password_reset::Column::Id.eq(id))
+            .filter(password_reset::Column::UsedAt.is_null())
+            .filter(password_reset::Column::ExpiresAt.gt(now))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Invalidates every pending token of the user.
+    pub async fn invalidate_for_user(&self, user_id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = PasswordReset::update_many()
+            .col_expr(password_reset::Column::UsedAt, Expr::value(Utc::now()))
+            .filter(password_reset::Column::UserId.eq(user_id))
+            .filter(password_reset::Column::UsedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get_by_hash(&self, token_hash: String) -> Result<Option<Model>, anyhow::Error> {
+        let reset = PasswordReset::find()
+            .filter(password_reset::Column::TokenHash.eq(token_hash))
+            .one(self.conn)
+            .await?;
+
+        Ok(reset)
+    }
+}
+
+#[derive(Clone)]
+pub struct PasswordResetRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for PasswordResetRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> PasswordResetRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
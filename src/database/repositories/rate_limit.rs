@@ -0,0 +1,85 @@
+use sea_orm::sea_query::{Expr, OnConflict, Query, SimpleExpr};
+use sea_orm::{prelude::*, ActiveValue, QueryTrait};
+
+use crate::database::entity::rate_limit::{self, ActiveModel, Entity as RateLimit};
+use super::base::Repository;
+
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    /// Atomically counts a hit in the window starting at `window_start` and returns the hits of the
+    /// window starting at `previous_start` and of this one, in a single statement.
+    pub async fn hit(&self, key: String, window_start: i64, previous_start: i64) -> Result<(i64, i64), anyhow::Error> {
+        let model = ActiveModel {
+            key: ActiveValue::Set(key.clone()),
+            window_start: ActiveValue::Set(window_start),
+            hits: ActiveValue::Set(1),
+        };
+        let previous = Query::select()
+            .column(rate_limit::Column::Hits)
+            .from(RateLimit)
+            .and_where(Expr::col(rate_limit::Column::Key).eq(key))
+            .and_where(Expr::col(rate_limit::Column::WindowStart).eq(previous_start))
+            .to_owned();
+
+        let statement = RateLimit::insert(model)
+            .on_conflict(
+                OnConflict::columns([rate_limit::Column::Key, rate_limit::Column::WindowStart])
+                    .value(rate_limit::Column::Hits, Expr::col((RateLimit, rate_limit::Column::Hits)).add(1))
+                    .to_owned()
+            )
+            .into_query()
+            .returning(Query::returning().exprs([
+                Expr::col(rate_limit::Column::Hits).into(),
+                SimpleExpr::SubQuery(None, Box::new(previous.into_sub_query_statement())),
+            ]))
+            .to_owned();
+
+        let row = self.conn
+            .query_one(self.conn.get_database_backend().build(&statement))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Rate limit hit returned no row"))?;
+
+        Ok((row.try_get_by_index::<Option<i64>>(1)?.unwrap_or(0), row.try_get_by_index(0)?))
+    }
+
+    /// Deletes the windows of every key that started before `window_start`.
+    pub async fn delete_before(&self, window_start: i64) -> Result<u64, anyhow::Error> {
+        let result = RateLimit::delete_many()
+            .filter(rate_limit::Column::WindowStart.lt(window_start))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for RateLimitRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> RateLimitRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+}
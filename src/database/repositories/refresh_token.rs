@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::refresh_token::{self, ActiveModel, Entity as RefreshToken, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewRefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub device: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IntoActiveModel for NewRefreshToken {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.id = ActiveValue::Set(self.id);
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.family_id = ActiveValue::Set(self.family_id);
+        model.device = ActiveValue::Set(self.device);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_token: NewRefreshToken) -> Result<Model, anyhow::Error> {
+        let token = new_token.into_active_model().insert(self.conn).await?;
+
+        Ok(token)
+    }
+
+    /// Marks the token as used only if it is still active, so that concurrent
+    /// refreshes with the same token can't both succeed.
+    pub async fn mark_used(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = RefreshToken::update_many()
+            .col_expr(refresh_token::Column::UsedAt, Expr::value(Utc::now()))
+            .filter(refresh_token::Column::Id.eq(id))
+            .filter(refresh_token::Column::UsedAt.is_null())
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn revoke_family(&self, family_id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = RefreshToken::update_many()
+            .col_expr(refresh_token::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(refresh_token::Column::FamilyId.eq(family_id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn revoke_for_user(&self, user_id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = RefreshToken::update_many()
+            .col_expr(refresh_token::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(refresh_token::Column::UserId.eq(user_id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
+        let token = RefreshToken::find_by_id(id).one(self.conn).await?;
+
+        Ok(token)
+    }
+}
+
+#[derive(Clone)]
+pub struct RefreshTokenRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for RefreshTokenRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> RefreshTokenRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::revoked_token::{self, ActiveModel, Entity as RevokedToken};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewRevokedToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IntoActiveModel for NewRevokedToken {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.id = ActiveValue::Set(self.id);
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_token: NewRevokedToken) -> Result<u64, anyhow::Error> {
+        let result = RevokedToken::insert(new_token.into_active_model())
+            .on_conflict(OnConflict::column(revoked_token::Column::Id).do_nothing().to_owned())
+            .exec_without_returning(self.conn)
+            .await?;
+
+        Ok(result)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn exists(&self, id: Uuid) -> Result<bool, anyhow::Error> {
+        let count = RevokedToken::find_by_id(id).count(self.conn).await?;
+
+        Ok(count > 0)
+    }
+}
+
+#[derive(Clone)]
+pub struct RevokedTokenRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for RevokedTokenRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> RevokedTokenRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
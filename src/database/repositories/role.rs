@@ -0,0 +1,138 @@
+use sea_orm::{prelude::*, ActiveValue, JoinType, QueryOrder, QuerySelect};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::permission::{self, Entity as Permission};
+use crate::database::entity::role::{self, ActiveModel, Entity as Role, Model};
+use crate::database::entity::role_permission::{self, Entity as RolePermission};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewRole {
+    pub name: String
+}
+
+impl IntoActiveModel for NewRole {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.name = ActiveValue::Set(self.name);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_role: NewRole) -> Result<Model, anyhow::Error> {
+        let role = new_role.into_active_model().insert(self.conn).await?;
+
+        Ok(role)
+    }
+
+    /// Replaces the role's permissions with the given set.
+    pub async fn set_permissions(&self, role_id: Uuid, permission_ids: Vec<Uuid>) -> Result<(), anyhow::Error> {
+        RolePermission::delete_many()
+            .filter(role_permission::Column::RoleId.eq(role_id))
+            .exec(self.conn)
+            .await?;
+
+        let models: Vec<role_permission::ActiveModel> = permission_ids
+            .into_iter()
+            .map(|permission_id| role_permission::ActiveModel {
+                role_id: ActiveValue::Set(role_id),
+                permission_id: ActiveValue::Set(permission_id),
+            })
+            .collect();
+
+        RolePermission::insert_many(models)
+            .on_empty_do_nothing()
+            .exec_without_returning(self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
+        let role = Role::find_by_id(id).one(self.conn).await?;
+
+        Ok(role)
+    }
+
+    pub async fn get_by_name(&self, name: String) -> Result<Option<Model>, anyhow::Error> {
+        let role = Role::find().filter(role::Column::Name.eq(name)).one(self.conn).await?;
+
+        Ok(role)
+    }
+
+    pub async fn get_many_with_permissions(&self) -> Result<Vec<(Model, Vec<permission::Model>)>, anyhow::Error> {
+        let roles = Role::find()
+            .find_with_related(Permission)
+            .order_by_asc(role::Column::CreatedAt)
+            .all(self.conn)
+            .await?;
+
+        Ok(roles)
+    }
+
+    pub async fn get_permissions(&self, role_id: Uuid) -> Result<Vec<permission::Model>, anyhow::Error> {
+        let permissions = Permission::find()
+            .join(JoinType::InnerJoin, permission::Relation::RolePermission.def())
+            .filter(role_permission::Column::RoleId.eq(role_id))
+            .all(self.conn)
+            .await?;
+
+        Ok(permissions)
+    }
+
+    pub async fn get_permissions_by_names(&self, names: Vec<String>) -> Result<Vec<permission::Model>, anyhow::Error> {
+        let permissions = Permission::find()
+            .filter(permission::Column::Name.is_in(names))
+            .all(self.conn)
+            .await?;
+
+        Ok(permissions)
+    }
+}
+
+#[derive(Clone)]
+pub struct RoleRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for RoleRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> RoleRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
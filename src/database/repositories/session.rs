@@ -0,0 +1,158 @@
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::session::{self, ActiveModel, Entity as Session, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub token_version: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IntoActiveModel for NewSession {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.id = ActiveValue::Set(self.id);
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.device = ActiveValue::Set(self.device);
+        model.ip = ActiveValue::Set(self.ip);
+        model.token_version = ActiveValue::Set(self.token_version);
+        model.expires_at = ActiveValue::Set(self.expires_at);
+
+        model
+    }
+}
+
+#[derive(Debug)]
+pub struct TouchSession {
+    pub id: Uuid,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub token_version: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_session: NewSession) -> Result<Model, anyhow::Error> {
+        let session = new_session.into_active_model().insert(self.conn).await?;
+
+        Ok(session)
+    }
+
+    /// Records a refresh of the session. Device and ip are kept when the request didn't send them.
+    pub async fn touch(&self, touch: TouchSession) -> Result<u64, anyhow::Error> {
+        let mut update = Session::update_many()
+            .col_expr(session::Column::LastUsedAt, Expr::value(Utc::now()))
+            .col_expr(session::Column::TokenVersion, Expr::value(touch.token_version))
+            .col_expr(session::Column::ExpiresAt, Expr::value(touch.expires_at));
+
+        if let Some(device) = touch.device {
+            update = update.col_expr(session::Column::Device, Expr::value(device));
+        }
+        if let Some(ip) = touch.ip {
+            update = update.col_expr(session::Column::Ip, Expr::value(ip));
+        }
+
+        let result = update
+            .filter(session::Column::Id.eq(touch.id))
+            .filter(session::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn revoke(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = Session::update_many()
+            .col_expr(session::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(session::Column::Id.eq(id))
+            .filter(session::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn revoke_for_user(&self, user_id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = Session::update_many()
+            .col_expr(session::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(session::Column::UserId.eq(user_id))
+            .filter(session::Column::RevokedAt.is_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
+        let session = Session::find_by_id(id).one(self.conn).await?;
+
+        Ok(session)
+    }
+
+    /// Sessions of the user that can still be refreshed, most recently used first.
+    pub async fn get_active_for_user(&self, user_id: Uuid, token_version: i32) -> Result<Vec<Model>, anyhow::Error> {
+        let sessions = Session::find()
+            .filter(session::Column::UserId.eq(user_id))
+            .filter(session::Column::RevokedAt.is_null())
+            .filter(session::Column::ExpiresAt.gt(Utc::now()))
+            .filter(session::Column::TokenVersion.gte(token_version))
+            .order_by_desc(session::Column::LastUsedAt)
+            .all(self.conn)
+            .await?;
+
+        Ok(sessions)
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for SessionRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> SessionRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
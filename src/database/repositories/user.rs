@@ -1,6 +1,6 @@
 #![allow(unused)]
 
-use sea_orm::sea_query::OnConflict;
+use sea_orm::sea_query::{Expr, Func, OnConflict};
 use sea_orm::{
     prelude::*, 
     ActiveValue, 
@@ -11,7 +11,7 @@ use sea_orm::{
 #[allow(unused)]
 use crate::database::repositories::base::IntoActiveModel;
 use crate::into_active_model;
-use crate::database::entity::user::{self, ActiveModel, Entity as User, Model, Role};
+use crate::database::entity::user::{self, ActiveModel, Entity as User, Model};
 use super::base::Repository;
 
 use core::result::Result::Ok;
@@ -20,22 +20,26 @@ use core::result::Result::Ok;
 #[derive(Debug)]
 pub struct NewUser {
     pub login: String,
-    pub password: String
+    pub password: String,
+    pub email: Option<String>,
+    /// The User role when not set
+    pub role_id: Option<Uuid>
 }
 
 pub struct UpdateUser {
     pub id: Uuid,
     pub login: Option<String>,
     pub password: Option<String>,
-    pub role: Option<Role>
+    pub email: Option<Option<String>>,
+    pub email_verified_at: Option<Option<DateTimeUtc>>,
+    pub role_id: Option<Uuid>
 }
 
 pub struct DeleteUser {
     pub id: Uuid
 }
 
-into_active_model!(UpdateUser, ActiveModel, { mandatory: id }, { optional: login, optional: password, optional: role });
-into_active_model!(DeleteUser, ActiveModel, { mandatory: id }, {});
+into_active_model!(UpdateUser, ActiveModel, { mandatory: id }, { optional: login, optional: password, optional: email, optional: email_verified_at, optional: role_id });
 
 impl IntoActiveModel for NewUser {
     type Model = ActiveModel;
@@ -45,6 +49,10 @@ impl IntoActiveModel for NewUser {
 
         model.login = ActiveValue::Set(self.login);
         model.password = ActiveValue::Set(self.password);
+        model.email = ActiveValue::Set(self.email);
+        if let Some(role_id) = self.role_id {
+            model.role_id = ActiveValue::Set(role_id);
+        }
 
         model
     }
@@ -80,10 +88,64 @@ impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
         Ok(user)
     }
 
+    pub async fn bump_token_version(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = User::update_many()
+            .col_expr(user::Column::TokenVersion, Expr::col(user::Column::TokenVersion).add(1))
+            .filter(user::Column::Id.eq(id))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Marks the email as verified only if the user still has that email.
+    pub async fn verify_email(&self, id: Uuid, email: String) -> Result<u64, anyhow::Error> {
+        let result = User::update_many()
+            .col_expr(user::Column::EmailVerifiedAt, Expr::value(chrono::Utc::now()))
+            .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .filter(user::Column::Id.eq(id))
+            .filter(user::Column::Email.eq(email))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Marks the user as deleted. Bumping the token version ends all of their sessions,
+    /// also the ones that would be valid again after a restore.
     pub async fn delete(&self, delete_user: DeleteUser) -> Result<u64, anyhow::Error> {
-        let user = delete_user.into_active_model().delete(self.conn).await?;
+        let result = User::update_many()
+            .col_expr(user::Column::DeletedAt, Expr::value(chrono::Utc::now()))
+            .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .col_expr(user::Column::TokenVersion, Expr::col(user::Column::TokenVersion).add(1))
+            .filter(user::Column::Id.eq(delete_user.id))
+            .filter(user::Column::DeletedAt.is_null())
+            .exec(self.conn)
+            .await?;
 
-        Ok(user.rows_affected)
+        Ok(result.rows_affected)
+    }
+
+    pub async fn restore(&self, id: Uuid) -> Result<u64, anyhow::Error> {
+        let result = User::update_many()
+            .col_expr(user::Column::DeletedAt, Expr::value(Option::<DateTimeUtc>::None))
+            .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .filter(user::Column::Id.eq(id))
+            .filter(user::Column::DeletedAt.is_not_null())
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Removes users deleted before `deleted_before` for good, with everything that belongs to them.
+    pub async fn purge(&self, deleted_before: DateTimeUtc) -> Result<u64, anyhow::Error> {
+        let result = User::delete_many()
+            .filter(user::Column::DeletedAt.lt(deleted_before))
+            .exec(self.conn)
+            .await?;
+
+        Ok(result.rows_affected)
     }
 }
 
@@ -92,20 +154,47 @@ pub struct Reader<'a, Conn: ConnectionTrait> {
 }
 
 impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    /// Users that are not soft-deleted. Every query of the reader starts from here.
+    fn live() -> Select<User> {
+        User::find().filter(user::Column::DeletedAt.is_null())
+    }
+
     pub async fn get(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
-        let user = User::find_by_id(id).one(self.conn).await?;
+        let user = Self::live().filter(user::Column::Id.eq(id)).one(self.conn).await?;
         Ok(user)
         
     }
 
+    pub async fn get_deleted(&self, id: Uuid) -> Result<Option<Model>, anyhow::Error> {
+        let user = User::find_by_id(id)
+            .filter(user::Column::DeletedAt.is_not_null())
+            .one(self.conn)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Logins are unique regardless of case, so the lookup ignores it too.
     pub async fn get_by_login(&self, login: String) -> Result<Option<Model>, anyhow::Error> {
-        let user = User::find().filter(user::Column::Login.eq(login)).one(self.conn).await?;
+        let user = Self::live()
+            .filter(Expr::expr(Func::lower(Expr::col((User, user::Column::Login)))).eq(login.to_lowercase()))
+            .one(self.conn)
+            .await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_by_email(&self, email: String) -> Result<Option<Model>, anyhow::Error> {
+        let user = Self::live()
+            .filter(Expr::expr(Func::lower(Expr::col((User, user::Column::Email)))).eq(email.to_lowercase()))
+            .one(self.conn)
+            .await?;
 
         Ok(user)
     }
 
     pub async fn get_many(&self, offset: Option<u64>, limit: Option<u64>) -> Result<Vec<Model>, anyhow::Error>  {
-        let user = User::find()
+        let user = Self::live()
             .offset(offset)
             .limit(limit)
             .all(self.conn)
@@ -113,10 +202,22 @@ impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
         Ok(user)
     }
     pub async fn count(&self) -> Result<u64, anyhow::Error> {
-        let count = User::find().count(self.conn).await?;
+        let count = Self::live().count(self.conn).await?;
         Ok(count)
     }
 
+    /// Ids of the live users with the role, locked until the transaction ends,
+    /// so concurrent role changes and deletions see each other's result.
+    pub async fn lock_role_members(&self, role_id: Uuid) -> Result<Vec<Uuid>, anyhow::Error> {
+        let users = Self::live()
+            .filter(user::Column::RoleId.eq(role_id))
+            .lock_exclusive()
+            .all(self.conn)
+            .await?;
+
+        Ok(users.into_iter().map(|user| user.id).collect())
+    }
+
     pub async fn exists(&self, id: Uuid) -> Result<bool, anyhow::Error> {
         let user = self.get(id).await?;
         Ok(user.is_some())
@@ -0,0 +1,88 @@
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::database::repositories::base::IntoActiveModel;
+use crate::database::entity::user_identity::{self, ActiveModel, Entity as UserIdentity, Model};
+use super::base::Repository;
+
+
+#[derive(Debug)]
+pub struct NewUserIdentity {
+    pub user_id: Uuid,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+}
+
+impl IntoActiveModel for NewUserIdentity {
+    type Model = ActiveModel;
+
+    fn into_active_model(self) -> ActiveModel {
+        let mut model = ActiveModel::new();
+
+        model.user_id = ActiveValue::Set(self.user_id);
+        model.provider = ActiveValue::Set(self.provider);
+        model.subject = ActiveValue::Set(self.subject);
+        model.email = ActiveValue::Set(self.email);
+
+        model
+    }
+}
+
+pub struct Writer<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Writer<'a, Conn> {
+    pub async fn create(&self, new_identity: NewUserIdentity) -> Result<Model, anyhow::Error> {
+        let identity = new_identity.into_active_model().insert(self.conn).await?;
+
+        Ok(identity)
+    }
+}
+
+pub struct Reader<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+impl<'a, Conn: ConnectionTrait> Reader<'a, Conn> {
+    pub async fn get(&self, provider: &str, subject: &str) -> Result<Option<Model>, anyhow::Error> {
+        let identity = UserIdentity::find()
+            .filter(user_identity::Column::Provider.eq(provider))
+            .filter(user_identity::Column::Subject.eq(subject))
+            .one(self.conn)
+            .await?;
+
+        Ok(identity)
+    }
+}
+
+#[derive(Clone)]
+pub struct UserIdentityRepository<'a, Conn: ConnectionTrait> {
+    conn: &'a Conn
+}
+
+#[async_trait::async_trait]
+impl<'a, Conn> Repository<'a, Conn> for UserIdentityRepository<'a, Conn>
+where
+    Conn: ConnectionTrait + Send + Sync
+{
+
+    fn new(conn: &'a Conn) -> Self {
+        Self { conn }
+    }
+    fn connection(&self) -> &'a Conn {
+        self.conn
+    }
+}
+
+
+impl<'a, Conn: ConnectionTrait + Send + Sync> UserIdentityRepository<'a, Conn> {
+
+    pub fn writer(&self) -> Writer<'a, Conn> {
+        Writer { conn: self.connection() }
+    }
+
+    pub fn reader(&self) -> Reader<'a, Conn> {
+        Reader { conn: self.connection() }
+    }
+}
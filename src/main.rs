@@ -1,48 +1,107 @@
 use std::error::Error;
-use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
-use axum::http::{HeaderValue, Method};
+use std::process::ExitCode;
+use std::net::SocketAddr;
+use clap::Parser;
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
 use log::info;
 use simple_logger::SimpleLogger;
 
 use dotenv::dotenv;
 
-use tower_http::cors::CorsLayer;
 mod api;
+mod cli;
 pub mod core;
 mod common;
 mod database;
 mod services;
+use crate::api::common::middlewares::cors::cors;
+use crate::api::common::shutdown::spawn_shutdown;
+use crate::api::common::tls::{self, TlsFiles};
 use crate::api::setup::create_general_router;
-use crate::api::v1::setup::create_v1_router;
+use crate::api::v1::dependencies::{setup_dependencies, spawn_rate_limit_cleanup, spawn_user_purge};
+use crate::api::v1::setup::{create_v1_router, create_well_known_router};
+use crate::cli::{Cli, Command, ConfigAction};
 use crate::core::config::Config;
+use crate::database::connection::close_connection;
 
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> ExitCode {
 
     dotenv().ok();
+    let cli = Cli::parse();
     SimpleLogger::new().with_level(log::LevelFilter::Info).init().unwrap();
 
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
     info!("Getting config... ");
-    let config = Config::new();
+    let config = Config::load(cli.config.as_deref(), &cli.overrides)?;
 
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Config { action: ConfigAction::Check } => cli::config::check(config),
+        Command::Migrate { action } => cli::migrate::migrate(config, action).await,
+        Command::CreateAdmin { login, email } => cli::user::create_admin(config, login, email).await,
+        Command::ResetPassword { login } => cli::user::reset_password(config, login).await,
+        Command::RotateKeys { retire } => cli::keys::rotate_keys(config, retire),
+    }
+}
+
+async fn serve(config: Config) -> Result<(), Box<dyn Error>> {
     info!("Creating router... ");
-    let cors = CorsLayer::new()
-        .allow_origin(format!("http://{}:{}", config.server.host(), config.server.port()).parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE, Method::OPTIONS])
-        .allow_credentials(true)
-        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
+    let cors = cors(&config.cors);
+
+    let state = setup_dependencies(config.clone()).await?;
+    let purge = spawn_user_purge(state.clone());
+    let rate_limit_cleanup = spawn_rate_limit_cleanup(state.clone());
+
+    let handle = Handle::new();
+    spawn_shutdown(state.shutdown.clone(), handle.clone(), config.server.shutdown_delay(), config.server.shutdown_timeout());
 
     let app = create_general_router(
-        vec![create_v1_router(config.clone()).await], 
+        vec![create_v1_router(state.clone())?], 
+        vec![create_well_known_router(state.clone())],
     )
         .await
         .layer(cors);
 
-    info!("Starting server... ");
-    let listener = tokio::net::TcpListener::bind(
-        format!("{}:{}", config.server.host(), config.server.port())
-    ).await.unwrap();
-    axum::serve(listener, app.into_make_service()).await.unwrap();
+    let listener = std::net::TcpListener::bind(format!("{}:{}", config.server.host(), config.server.port()))?;
+    listener.set_nonblocking(true)?;
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    match TlsFiles::from_config(&config.server) {
+        Some(files) => {
+            let rustls = RustlsConfig::from_config(files.load()?);
+            tls::spawn_reload(files, rustls.clone(), config.server.tls_reload_interval_seconds(), state.shutdown.clone());
+            if let (Some(port), Some(public_url)) = (config.server.http_redirect_port(), config.server.public_url()) {
+                tls::spawn_redirect(config.server.host(), port, public_url, handle.clone())?;
+            }
+
+            info!("Starting server with TLS... ");
+            axum_server::from_tcp_rustls(listener, rustls).handle(handle).serve(app).await?;
+        },
+        None => {
+            info!("Starting server... ");
+            axum_server::from_tcp(listener).handle(handle).serve(app).await?;
+        },
+    }
+
+    info!("Server stopped, waiting for background tasks... ");
+    for task in [purge, rate_limit_cleanup].into_iter().flatten() {
+        task.await?;
+    }
+    state.tasks.close();
+    state.tasks.wait().await;
+    close_connection(&state.connection).await?;
+    info!("Shutdown complete");
     Ok(())
 }
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{TimeDelta, Utc};
+use sea_orm::ConnectionTrait;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::requests::api_key::CreateApiKey;
+use crate::common::structs::responses::api_key::{ApiKey, CreatedApiKey};
+use crate::common::structs::responses::status::Status;
+use crate::common::structs::responses::user::User;
+use crate::database::entity::api_key::Model;
+use crate::database::repositories::api_key::{ApiKeyRepository, NewApiKey, Reader, Writer};
+
+use super::security::hash::{generate_token, hash_token};
+use super::security::permission::Permission;
+
+const KEY_PREFIX: &str = "ak_";
+const PREFIX_BYTES: usize = 6;
+const MAX_NAME_LENGTH: usize = 128;
+const LAST_USED_PRECISION_SECONDS: i64 = 60;
+
+
+/// Permissions stored on the key, skipping names the API no longer knows.
+pub fn scopes(model: &Model) -> Vec<Permission> {
+    model.scopes
+        .split_whitespace()
+        .filter_map(|scope| scope.parse().ok())
+        .collect()
+}
+
+fn to_response(model: Model) -> ApiKey {
+    ApiKey {
+        scopes: scopes(&model),
+        id: model.id,
+        name: model.name,
+        prefix: format!("{KEY_PREFIX}{}", model.prefix),
+        expires_at: model.expires_at,
+        last_used_at: model.last_used_at,
+        created_at: model.created_at
+    }
+}
+
+fn invalid_key() -> AppError {
+    AppError::UnAuthorizedError(AppErrorMessage { message: "Invalid API key".into(), details: None })
+}
+
+pub struct ApiKeyService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> ApiKeyService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<ApiKeyRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Creates a key for the user. Keys look like `ak_<prefix>_<secret>`: the prefix finds
+    /// the record, and only a digest of the whole key is stored.
+    pub async fn create(&self, user: &User, data: CreateApiKey) -> Result<CreatedApiKey, AppError> {
+        let name = data.name.trim();
+
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(AppError::BadRequestError(AppErrorMessage {
+                message: "Invalid API key name".into(),
+                details: json!({ "max_length": MAX_NAME_LENGTH }).into()
+            }));
+        }
+
+        let denied: Vec<_> = data.scopes.iter().filter(|scope| !user.permissions.contains(scope)).collect();
+        if !denied.is_empty() {
+            return Err(AppError::ForbiddenError(AppErrorMessage {
+                message: "API key scopes exceed your permissions".into(),
+                details: json!({ "scopes": denied }).into()
+            }));
+        }
+
+        if data.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(AppError::BadRequestError(AppErrorMessage {
+                message: "Expiration date must be in the future".into(),
+                details: None
+            }));
+        }
+
+        let mut bytes = [0u8; PREFIX_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let prefix: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        let key = format!("{KEY_PREFIX}{prefix}_{}", generate_token());
+
+        let mut scopes: Vec<_> = data.scopes.iter().map(Permission::as_str).collect();
+        scopes.sort_unstable();
+        scopes.dedup();
+
+        let model = self.writer.create(
+            NewApiKey {
+                user_id: user.id,
+                name: name.into(),
+                prefix,
+                key_hash: hash_token(&key),
+                scopes: scopes.join(" "),
+                expires_at: data.expires_at
+            }
+        ).await?;
+
+        Ok(CreatedApiKey { key, api_key: to_response(model) })
+    }
+
+    pub async fn get_many(&self, user_id: Uuid) -> Result<Vec<ApiKey>, AppError> {
+        let keys = self.reader.get_for_user(user_id).await?;
+
+        Ok(keys.into_iter().map(to_response).collect())
+    }
+
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<Status, AppError> {
+        let rows = self.writer.delete(user_id, id).await?;
+
+        if rows == 0 {
+            return Err(AppError::NotFoundError(AppErrorMessage {
+                message: "API key not found".into(),
+                details: None
+            }));
+        }
+
+        Ok(Status { status: true })
+    }
+
+    /// Returns the record of a valid, unexpired key and records its use.
+    pub async fn authenticate(&self, key: &str) -> Result<Model, AppError> {
+        let prefix = key
+            .strip_prefix(KEY_PREFIX)
+            .and_then(|rest| rest.split_once('_'))
+            .map(|(prefix, _)| prefix)
+            .ok_or_else(invalid_key)?;
+
+        let model = self.reader
+            .get_by_prefix(prefix)
+            .await?
+            .filter(|model| model.key_hash == hash_token(key))
+            .ok_or_else(invalid_key)?;
+
+        if model.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(AppError::UnAuthorizedError(AppErrorMessage {
+                message: "API key expired".into(),
+                details: None
+            }));
+        }
+
+        self.writer.touch(model.id, Utc::now() - TimeDelta::seconds(LAST_USED_PRECISION_SECONDS)).await?;
+
+        Ok(model)
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use sea_orm::ConnectionTrait;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::common::error::AppError;
+use crate::common::structs::responses::audit::{AuditEvent, AuditEventData};
+use crate::common::structs::responses::user::User;
+use crate::database::entity::audit_event::{AuditAction, Model};
+use crate::database::repositories::audit_event::{AuditEventRepository, AuditFilter, NewAuditEvent, Reader, Writer};
+
+pub use crate::core::config::REDACTED;
+
+
+/// Who made a request and from where, attached to every event the request records.
+#[derive(Clone, Debug, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    /// The same request attributed to `actor_id`, for requests that authenticate the user themselves.
+    pub fn as_actor(&self, actor_id: Uuid) -> Self {
+        Self { actor_id: Some(actor_id), ..self.clone() }
+    }
+}
+
+/// Fields of a user the log tracks changes of.
+pub fn user_snapshot(user: &User) -> Value {
+    json!({
+        "login": user.login,
+        "email": user.email,
+        "email_verified_at": user.email_verified_at,
+        "role": user.role,
+    })
+}
+
+/// Fields that differ between two snapshots as `{"field": {"before": .., "after": ..}}`.
+/// A missing snapshot counts as empty, so creations and deletions list every field.
+pub fn diff(before: Option<&Value>, after: Option<&Value>) -> Option<Value> {
+    let empty = Map::new();
+    let before = before.and_then(Value::as_object).unwrap_or(&empty);
+    let after = after.and_then(Value::as_object).unwrap_or(&empty);
+
+    let changes: Map<String, Value> = before
+        .keys()
+        .chain(after.keys().filter(|key| !before.contains_key(*key)))
+        .map(|key| (key, before.get(key).unwrap_or(&Value::Null), after.get(key).unwrap_or(&Value::Null)))
+        .filter(|(_, before, after)| before != after)
+        .map(|(key, before, after)| (key.clone(), json!({ "before": before, "after": after })))
+        .collect();
+
+    (!changes.is_empty()).then_some(Value::Object(changes))
+}
+
+fn to_response(model: Model) -> AuditEvent {
+    AuditEvent {
+        id: model.id,
+        actor_id: model.actor_id,
+        action: model.action,
+        target_id: model.target_id,
+        changes: model.changes,
+        ip: model.ip,
+        user_agent: model.user_agent,
+        request_id: model.request_id,
+        created_at: model.created_at,
+    }
+}
+
+
+pub struct AuditService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> AuditService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<AuditEventRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Appends an event. Call it on the connection or transaction of the change it describes,
+    /// so the event is stored if and only if the change is.
+    pub async fn record(
+        &self, context: &AuditContext, action: AuditAction, target_id: Option<Uuid>, changes: Option<Value>
+    ) -> Result<(), AppError> {
+        self.writer.create(
+            NewAuditEvent {
+                actor_id: context.actor_id,
+                action,
+                target_id,
+                changes,
+                ip: context.ip.clone(),
+                user_agent: context.user_agent.clone(),
+                request_id: context.request_id.clone(),
+            }
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn get_many(
+        &self, filter: AuditFilter, offset: Option<u64>, limit: Option<u64>
+    ) -> Result<AuditEventData, AppError> {
+        let total = self.reader.count(&filter).await?;
+        let events = self.reader.get_many(&filter, offset, limit).await?;
+
+        Ok(AuditEventData {
+            total,
+            data: events.into_iter().map(to_response).collect()
+        })
+    }
+}
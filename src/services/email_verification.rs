@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::token::{CustomClaims, TokenClaims, TokenType};
+use crate::core::config::MailConfig;
+use crate::database::entity::email_verification::Model;
+use crate::database::repositories::email_verification::{EmailVerificationRepository, NewEmailVerification, Reader, Writer};
+
+use super::mailer::Mail;
+use super::security::jwt::JWT;
+
+pub struct EmailVerificationService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> EmailVerificationService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<EmailVerificationRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Issues a verification token for `email` and returns the mail with the verification link,
+    /// to send once the token is committed. Tokens issued to the user before stop working.
+    pub async fn issue(&self, user_id: Uuid, email: String, jwt: &JWT, config: &MailConfig) -> Result<Mail, AppError> {
+        let id = Uuid::new_v4();
+        let (exp, token) = jwt.create_token(
+            user_id.to_string(), 
+            TokenType::EMAIL_VERIFICATION, 
+            id, 
+            0, 
+            Some(TimeDelta::seconds(config.verification_expire_seconds)),
+            CustomClaims::default()
+        )?;
+
+        let expires_at = DateTime::<Utc>::from_timestamp(exp as i64, 0).ok_or_else(|| {
+            AppError::InternalServerError(
+                AppErrorMessage {
+                    message: "Failed to set expires token date".into(),
+                    details: None
+                }
+            )
+        })?;
+
+        self.writer.invalidate_for_user(user_id).await?;
+        self.writer.create(
+            NewEmailVerification {
+                id,
+                user_id,
+                email: email.clone(),
+                expires_at
+            }
+        ).await?;
+
+        Ok(Mail {
+            to: email,
+            subject: "Verify your email".into(),
+            body: format!(
+                "Open the link below to verify your email:\n\n{}?token={}\n", 
+                config.verification_url, 
+                token.token
+            ),
+        })
+    }
+
+    /// Redeems the token behind `claims`. Every token can be redeemed once.
+    pub async fn redeem(&self, claims: &TokenClaims) -> Result<Model, AppError> {
+        let invalid = || AppError::BadRequestError(
+            AppErrorMessage {
+                message: "Invalid verification token".into(),
+                details: None
+            }
+        );
+
+        if claims._type != TokenType::EMAIL_VERIFICATION {
+            return Err(invalid());
+        }
+
+        let record = self.reader
+            .get(claims.jti)
+            .await?
+            .filter(|record| record.user_id.to_string() == claims.sub)
+            .ok_or_else(invalid)?;
+
+        if self.writer.mark_used(record.id).await? == 0 {
+            return Err(AppError::BadRequestError(
+                AppErrorMessage {
+                    message: "Verification token was already used or has expired".into(),
+                    details: None
+                }
+            ));
+        }
+
+        Ok(record)
+    }
+}
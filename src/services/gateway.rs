@@ -4,6 +4,18 @@ use sea_orm::ConnectionTrait;
 
 use crate::database::gateway::DBGateway;
 use crate::services::user::UserService;
+use crate::services::role::RoleService;
+use crate::services::refresh_token::RefreshTokenService;
+use crate::services::revoked_token::RevokedTokenService;
+use crate::services::login_attempt::LoginAttemptService;
+use crate::services::rate_limit::RateLimitService;
+use crate::services::email_verification::EmailVerificationService;
+use crate::services::password_reset::PasswordResetService;
+use crate::services::mfa::MfaService;
+use crate::services::user_identity::UserIdentityService;
+use crate::services::api_key::ApiKeyService;
+use crate::services::session::SessionService;
+use crate::services::audit::AuditService;
 
 #[derive(Clone)]
 pub struct ServiceGateway<'a, Conn> 
@@ -19,8 +31,56 @@ where Conn: ConnectionTrait + Send + Sync
         Self { database }
     }
 
-    pub fn user(&self) -> Arc<UserService<Conn>> {
-        UserService::new(self.database.user())
+    pub fn user(&self) -> Arc<UserService<'a, Conn>> {
+        UserService::new(self.database.user(), self.database.role())
+    }
+
+    pub fn role(&self) -> Arc<RoleService<'a, Conn>> {
+        RoleService::new(self.database.role())
+    }
+
+    pub fn refresh_token(&self) -> Arc<RefreshTokenService<'a, Conn>> {
+        RefreshTokenService::new(self.database.refresh_token(), self.database.session())
+    }
+
+    pub fn revoked_token(&self) -> Arc<RevokedTokenService<'a, Conn>> {
+        RevokedTokenService::new(self.database.revoked_token())
+    }
+
+    pub fn login_attempt(&self) -> Arc<LoginAttemptService<'a, Conn>> {
+        LoginAttemptService::new(self.database.login_attempt())
+    }
+
+    pub fn rate_limit(&self) -> Arc<RateLimitService<'a, Conn>> {
+        RateLimitService::new(self.database.rate_limit())
+    }
+
+    pub fn email_verification(&self) -> Arc<EmailVerificationService<'a, Conn>> {
+        EmailVerificationService::new(self.database.email_verification())
+    }
+
+    pub fn password_reset(&self) -> Arc<PasswordResetService<'a, Conn>> {
+        PasswordResetService::new(self.database.password_reset())
+    }
+
+    pub fn mfa(&self) -> Arc<MfaService<'a, Conn>> {
+        MfaService::new(self.database.mfa())
+    }
+
+    pub fn user_identity(&self) -> Arc<UserIdentityService<'a, Conn>> {
+        UserIdentityService::new(self.database.user_identity(), self.database.user())
+    }
+
+    pub fn api_key(&self) -> Arc<ApiKeyService<'a, Conn>> {
+        ApiKeyService::new(self.database.api_key())
+    }
+
+    pub fn session(&self) -> Arc<SessionService<'a, Conn>> {
+        SessionService::new(self.database.session(), self.database.refresh_token(), self.database.user())
+    }
+
+    pub fn audit(&self) -> Arc<AuditService<'a, Conn>> {
+        AuditService::new(self.database.audit_event())
     }
 }
 
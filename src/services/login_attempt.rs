@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sea_orm::ConnectionTrait;
+use serde_json::json;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::LoginConfig;
+use crate::database::entity::login_attempt::AttemptScope;
+use crate::database::repositories::login_attempt::{LoginAttemptRepository, Reader, Writer};
+
+pub struct LoginAttemptService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> LoginAttemptService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<LoginAttemptRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Fails with `TooManyRequestsError` while any of the keys is locked.
+    pub async fn check(&self, keys: &[(AttemptScope, String)]) -> Result<(), AppError> {
+        let now = Utc::now();
+        let mut retry_after = 0;
+
+        for (scope, key) in keys {
+            let locked_until = self.reader
+                .get(*scope, key.clone())
+                .await?
+                .and_then(|attempt| attempt.locked_until)
+                .filter(|locked_until| *locked_until > now);
+
+            if let Some(locked_until) = locked_until {
+                retry_after = retry_after.max((locked_until - now).num_seconds() + 1);
+            }
+        }
+
+        if retry_after > 0 {
+            return Err(AppError::TooManyRequestsError(
+                AppErrorMessage {
+                    message: "Too many failed login attempts. Try again later".into(),
+                    details: json!({ "retry_after": retry_after }).into()
+                }
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Counts a failed attempt. Once the scope's threshold is reached the key gets locked,
+    /// doubling the lockout for every further failure up to `lockout_max_seconds`.
+    pub async fn register_failure(&self, scope: AttemptScope, key: String, config: &LoginConfig) -> Result<(), AppError> {
+        let now = Utc::now();
+        let max_attempts = match scope {
+            AttemptScope::Login => config.max_attempts,
+            AttemptScope::Ip => config.ip_max_attempts,
+        };
+
+        // Counted in one statement, concurrent failures must not overwrite each other's count
+        let window_start = now - Duration::seconds(config.attempt_window_seconds);
+        let failures = self.writer.add_failure(scope, key.clone(), window_start).await?;
+
+        if failures as u32 >= max_attempts {
+            let exponent = (failures as u32 - max_attempts).min(20);
+            let seconds = config.lockout_seconds
+                .saturating_mul(1 << exponent)
+                .min(config.lockout_max_seconds);
+
+            self.writer.lock(scope, key, now + Duration::seconds(seconds)).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn reset(&self, scope: AttemptScope, key: String) -> Result<(), AppError> {
+        self.writer.delete(scope, key).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use lettre::{
+    message::Mailbox,
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::json;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+use tokio_util::task::TaskTracker;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::MailConfig;
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), AppError>;
+}
+
+/// Delivers the mail in the background, so a slow or failing mail server neither holds the request
+/// nor changes its response. Failures are only logged, and shutdown waits for the send on `tasks`.
+pub fn spawn_send(tasks: &TaskTracker, mailer: Arc<dyn Mailer>, mail: Mail) {
+    tasks.spawn(async move {
+        let to = mail.to.clone();
+        if let Err(error) = mailer.send(mail).await {
+            warn!("Failed to send a mail to {}: {}", to, error);
+        }
+    });
+}
+
+fn mail_error(message: &str) -> AppError {
+    AppError::ServiceUnavailableError(AppErrorMessage { message: message.into(), details: None })
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    fn new(config: &MailConfig) -> Result<Self, anyhow::Error> {
+        let host = config.smtp_host.as_deref().ok_or_else(|| anyhow::anyhow!("SMTP_HOST must be set"))?;
+        let mut builder = match config.smtp_tls.as_ref() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            tls => return Err(anyhow::anyhow!("SMTP_TLS must be tls, starttls or none, got {tls}")),
+        };
+
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.to_string(), password.expose().to_string()));
+        }
+
+        Ok(Self { from: config.from.parse()?, transport: builder.build() })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse().map_err(|_| mail_error("Invalid mail recipient"))?)
+            .subject(mail.subject)
+            .body(mail.body)
+            .map_err(|_| mail_error("Failed to build a mail"))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|_| mail_error("Failed to send a mail"))?;
+
+        Ok(())
+    }
+}
+
+/// Appends every mail as a JSON line to a file instead of delivering it.
+/// Meant for development and tests, which can read the sent mails back from the file.
+pub struct FileMailer {
+    from: String,
+    path: String,
+    lock: Mutex<()>,
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        info!("Mail to {}: {}", mail.to, mail.subject);
+
+        let mut line = json!({ "from": self.from, "to": mail.to, "subject": mail.subject, "body": mail.body }).to_string();
+        line.push('\n');
+
+        let _guard = self.lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|_| mail_error("Failed to open the mail file"))?;
+
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|_| mail_error("Failed to write a mail"))?;
+
+        Ok(())
+    }
+}
+
+pub fn get_mailer(config: &MailConfig) -> Result<Arc<dyn Mailer>, anyhow::Error> {
+    match config.transport.as_ref() {
+        "smtp" => Ok(Arc::new(SmtpMailer::new(config).map_err(|error| anyhow::anyhow!("SMTP mailer was not created: {error}"))?)),
+        "file" => Ok(Arc::new(FileMailer { 
+            from: config.from.to_string(), 
+            path: config.file_path.to_string(), 
+            lock: Mutex::new(()) 
+        })),
+        transport => anyhow::bail!("MAIL_TRANSPORT must be smtp or file, got {transport}"),
+    }
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use sea_orm::ConnectionTrait;
+use totp_rs::{Algorithm, TOTP};
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::mfa::MfaEnrollment;
+use crate::database::repositories::mfa::{MfaRepository, Reader, Writer};
+
+use super::security::cipher::SecretCipher;
+use super::security::hash::Argon2Hasher;
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP: u64 = 30;
+const SECRET_LENGTH: usize = 20;
+const RECOVERY_CODES: usize = 10;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+
+fn totp(secret: Vec<u8>, issuer: &str, account: &str) -> TOTP {
+    TOTP::new_unchecked(
+        Algorithm::SHA1, 
+        TOTP_DIGITS, 
+        1, 
+        TOTP_STEP, 
+        secret, 
+        Some(issuer.replace(':', "")), 
+        account.replace(':', "")
+    )
+}
+
+/// Returns the time step of the code if it is valid at `now`, allowing one step of clock drift.
+fn match_step(totp: &TOTP, code: &str, now: u64) -> Option<i64> {
+    [now - TOTP_STEP, now, now + TOTP_STEP]
+        .into_iter()
+        .find(|time| totp.generate(*time) == code)
+        .map(|time| (time / TOTP_STEP) as i64)
+}
+
+fn recovery_code() -> String {
+    let chars: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[OsRng.next_u32() as usize % RECOVERY_CODE_ALPHABET.len()] as char)
+        .collect();
+
+    format!("{}-{}", &chars[..5], &chars[5..])
+}
+
+/// Whether the code has the `xxxxx-xxxxx` shape of a recovery code, anything else is not worth hashing.
+fn is_recovery_code(code: &str) -> bool {
+    code.len() == 11 && code.char_indices().all(|(index, char)| match index {
+        5 => char == '-',
+        _ => char.is_ascii() && RECOVERY_CODE_ALPHABET.contains(&(char as u8)),
+    })
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+fn invalid_code() -> AppError {
+    AppError::BadRequestError(
+        AppErrorMessage {
+            message: "Invalid code".into(),
+            details: None
+        }
+    )
+}
+
+pub struct MfaService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> MfaService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<MfaRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    pub async fn is_enabled(&self, user_id: Uuid) -> Result<bool, AppError> {
+        let secret = self.reader.get_secret(user_id).await?;
+
+        Ok(secret.is_some_and(|secret| secret.confirmed_at.is_some()))
+    }
+
+    /// Generates a new secret and recovery codes. MFA stays disabled until the secret is confirmed with a code.
+    pub async fn enroll(
+        &self, user_id: Uuid, login: &str, issuer: &str, cipher: &SecretCipher, hasher: &Argon2Hasher
+    ) -> Result<MfaEnrollment, AppError> {
+        if self.is_enabled(user_id).await? {
+            return Err(AppError::ConflictError(
+                AppErrorMessage {
+                    message: "MFA is already enabled".into(),
+                    details: None
+                }
+            ));
+        }
+
+        let mut secret = vec![0u8; SECRET_LENGTH];
+        OsRng.fill_bytes(&mut secret);
+        let totp = totp(secret.clone(), issuer, login);
+
+        self.writer.save_secret(user_id, cipher.encrypt(&secret)?).await?;
+
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODES).map(|_| recovery_code()).collect();
+        let (hasher, codes) = (hasher.clone(), recovery_codes.clone());
+        // Argon2 takes long enough to stall the runtime when hashing ten codes in a row
+        let code_hashes = tokio::task::spawn_blocking(move || {
+            codes
+                .iter()
+                .map(|code| hasher.hash_password(code))
+                .collect::<Result<Vec<String>, AppError>>()
+        })
+            .await
+            .map_err(anyhow::Error::from)??;
+        self.writer.replace_recovery_codes(user_id, code_hashes).await?;
+
+        Ok(MfaEnrollment {
+            otpauth_uri: totp.get_url(),
+            secret: totp.get_secret_base32(),
+            recovery_codes
+        })
+    }
+
+    /// Enables MFA once the user proves the authenticator app produces valid codes.
+    pub async fn confirm(&self, user_id: Uuid, code: &str, cipher: &SecretCipher) -> Result<(), AppError> {
+        let secret = self.reader.get_secret(user_id).await?.ok_or_else(|| {
+            AppError::BadRequestError(
+                AppErrorMessage {
+                    message: "MFA enrollment was not started".into(),
+                    details: None
+                }
+            )
+        })?;
+
+        if secret.confirmed_at.is_some() {
+            return Err(AppError::ConflictError(
+                AppErrorMessage {
+                    message: "MFA is already enabled".into(),
+                    details: None
+                }
+            ));
+        }
+
+        let step = match_step(&totp(cipher.decrypt(&secret.secret)?, "", ""), code.trim(), now()).ok_or_else(invalid_code)?;
+        self.writer.confirm(user_id, step).await?;
+
+        Ok(())
+    }
+
+    /// Accepts a TOTP code that was not used before, or an unused recovery code.
+    pub async fn verify(
+        &self, user_id: Uuid, code: &str, cipher: &SecretCipher, hasher: &Argon2Hasher
+    ) -> Result<(), AppError> {
+        let secret = self.reader
+            .get_secret(user_id)
+            .await?
+            .filter(|secret| secret.confirmed_at.is_some())
+            .ok_or_else(invalid_code)?;
+        let code = code.trim().to_lowercase();
+
+        if let Some(step) = match_step(&totp(cipher.decrypt(&secret.secret)?, "", ""), &code, now()) {
+            if self.writer.use_step(user_id, step).await? == 0 {
+                return Err(invalid_code());
+            }
+
+            return Ok(());
+        }
+
+        if !is_recovery_code(&code) {
+            return Err(invalid_code());
+        }
+
+        let recovery_codes = self.reader.get_unused_recovery_codes(user_id).await?;
+        let hasher = hasher.clone();
+        let recovery_code_id = tokio::task::spawn_blocking(move || {
+            recovery_codes
+                .into_iter()
+                .find(|recovery_code| hasher.verify_password(&recovery_code.code_hash, &code))
+                .map(|recovery_code| recovery_code.id)
+        })
+            .await
+            .map_err(anyhow::Error::from)?
+            .ok_or_else(invalid_code)?;
+
+        if self.writer.use_recovery_code(recovery_code_id).await? == 0 {
+            return Err(invalid_code());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::prelude::*;
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult};
+
+    use super::*;
+    use crate::core::config::{MfaConfig, Secret};
+    use crate::database::entity::{mfa_recovery_code, mfa_secret};
+    use crate::database::repositories::base::Repository;
+    use crate::services::security::cipher::get_cipher;
+    use crate::services::security::hash::get_argon2_default;
+
+    const SECRET: &[u8] = b"secret-for-tests-012";
+    const RECOVERY_CODE: &str = "abcde-fghjk";
+
+    fn cipher() -> Arc<SecretCipher> {
+        let key = BASE64_STANDARD.encode([7u8; 32]).into_boxed_str();
+        get_cipher(&MfaConfig { encryption_key: Some(Secret::new(key)), ..Default::default() }).unwrap()
+    }
+
+    fn secret(user_id: Uuid, cipher: &SecretCipher) -> mfa_secret::Model {
+        mfa_secret::Model {
+            user_id,
+            secret: cipher.encrypt(SECRET).unwrap(),
+            last_used_step: Some(0),
+            confirmed_at: Some(Utc::now()),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn rows_affected(rows_affected: u64) -> MockExecResult {
+        MockExecResult { last_insert_id: 0, rows_affected }
+    }
+
+    async fn verify(connection: &DatabaseConnection, user_id: Uuid, code: &str, cipher: &SecretCipher) -> Result<(), AppError> {
+        let service = MfaService::new(Arc::new(MfaRepository::new(connection)));
+        service.verify(user_id, code, cipher, &get_argon2_default()).await
+    }
+
+    #[test]
+    fn codes_are_accepted_within_one_step_of_drift() {
+        let totp = totp(SECRET.to_vec(), "", "");
+        let now = 1_700_000_010;
+        let step = (now / TOTP_STEP) as i64;
+
+        assert_eq!(match_step(&totp, &totp.generate(now), now), Some(step));
+        assert_eq!(match_step(&totp, &totp.generate(now - TOTP_STEP), now), Some(step - 1));
+        assert_eq!(match_step(&totp, &totp.generate(now + TOTP_STEP), now), Some(step + 1));
+        assert_eq!(match_step(&totp, &totp.generate(now - 2 * TOTP_STEP), now), None);
+        assert_eq!(match_step(&totp, &totp.generate(now + 2 * TOTP_STEP), now), None);
+    }
+
+    #[test]
+    fn only_codes_shaped_like_recovery_codes_are_recognised() {
+        assert!(is_recovery_code(&recovery_code()));
+        assert!(is_recovery_code(RECOVERY_CODE));
+        assert!(!is_recovery_code("123456"));
+        assert!(!is_recovery_code("abcdefghjkm"));
+        assert!(!is_recovery_code("abcde-fghj1"));
+        assert!(!is_recovery_code("abcdé-fghj"));
+    }
+
+    #[tokio::test]
+    async fn replayed_codes_are_rejected() {
+        let (user_id, cipher) = (Uuid::new_v4(), cipher());
+        // The second update finds the step already used and changes nothing
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[secret(user_id, &cipher)], [secret(user_id, &cipher)]])
+            .append_exec_results([rows_affected(1), rows_affected(0)])
+            .into_connection();
+        let code = totp(SECRET.to_vec(), "", "").generate(now());
+
+        assert!(verify(&connection, user_id, &code, &cipher).await.is_ok());
+        assert!(matches!(verify(&connection, user_id, &code, &cipher).await, Err(AppError::BadRequestError(_))));
+
+        // Debug escapes the quotes of the logged SQL
+        let log = format!("{:?}", connection.into_transaction_log());
+        assert!(log.contains(r#"\"last_used_step\" < $"#));
+    }
+
+    #[tokio::test]
+    async fn recovery_codes_are_single_use() {
+        let (user_id, cipher) = (Uuid::new_v4(), cipher());
+        let recovery_code = mfa_recovery_code::Model {
+            id: Uuid::new_v4(),
+            user_id,
+            code_hash: get_argon2_default().hash_password(RECOVERY_CODE).unwrap(),
+            used_at: None,
+            created_at: Utc::now(),
+        };
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[secret(user_id, &cipher)]])
+            .append_query_results([[recovery_code.clone()]])
+            .append_query_results([[secret(user_id, &cipher)]])
+            .append_query_results([Vec::<mfa_recovery_code::Model>::new()])
+            .append_query_results([[secret(user_id, &cipher)]])
+            .append_query_results([[recovery_code]])
+            .append_exec_results([rows_affected(1), rows_affected(0)])
+            .into_connection();
+
+        assert!(verify(&connection, user_id, " ABCDE-FGHJK ", &cipher).await.is_ok());
+        // Used codes are no longer read
+        assert!(verify(&connection, user_id, RECOVERY_CODE, &cipher).await.is_err());
+        // A concurrent request used it between the read and the update
+        assert!(verify(&connection, user_id, RECOVERY_CODE, &cipher).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn other_codes_are_not_checked_against_recovery_codes() {
+        let (user_id, cipher) = (Uuid::new_v4(), cipher());
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[secret(user_id, &cipher)]])
+            .into_connection();
+
+        assert!(verify(&connection, user_id, "not a code", &cipher).await.is_err());
+        assert_eq!(connection.into_transaction_log().len(), 1);
+    }
+}
@@ -1,3 +1,16 @@
 pub mod user;
+pub mod role;
+pub mod refresh_token;
+pub mod revoked_token;
+pub mod login_attempt;
+pub mod rate_limit;
+pub mod email_verification;
+pub mod password_reset;
+pub mod mfa;
+pub mod user_identity;
+pub mod api_key;
+pub mod session;
+pub mod audit;
+pub mod mailer;
 pub mod gateway;
 pub mod security;
\ No newline at end of file
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use chrono::{TimeDelta, Utc};
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::MailConfig;
+use crate::database::entity::password_reset::Model;
+use crate::database::repositories::password_reset::{NewPasswordReset, PasswordResetRepository, Reader, Writer};
+
+use super::mailer::Mail;
+use super::security::hash::{generate_token, hash_token};
+
+pub struct PasswordResetService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> PasswordResetService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<PasswordResetRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Issues a reset token and returns the mail with the reset link for `to`, to send once the token is committed.
+    /// Tokens issued to the user before stop working.
+    pub async fn issue(&self, user_id: Uuid, to: String, config: &MailConfig) -> Result<Mail, AppError> {
+        let token = generate_token();
+
+        self.writer.invalidate_for_user(user_id).await?;
+        self.writer.create(
+            NewPasswordReset {
+                id: Uuid::new_v4(),
+                user_id,
+                token_hash: hash_token(&token),
+                expires_at: Utc::now() + TimeDelta::seconds(config.password_reset_expire_seconds)
+            }
+        ).await?;
+
+        Ok(Mail {
+            to,
+            subject: "Reset your password".into(),
+            body: format!(
+                "Open the link below to set a new password:\n\n{}?token={}\n\nIgnore this mail if you did not ask for it.\n", 
+                config.password_reset_url, 
+                token
+            ),
+        })
+    }
+
+    /// Redeems the token and returns its record. Every token can be redeemed once.
+    pub async fn redeem(&self, token: &str) -> Result<Model, AppError> {
+        let invalid = || AppError::BadRequestError(
+            AppErrorMessage {
+                message: "Invalid or expired reset token".into(),
+                details: None
+            }
+        );
+
+        let record = self.reader
+            .get_by_hash(hash_token(token))
+            .await?
+            .ok_or_else(invalid)?;
+
+        if self.writer.mark_used(record.id).await? == 0 {
+            return Err(invalid());
+        }
+
+        self.writer.invalidate_for_user(record.user_id).await?;
+
+        Ok(record)
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use sea_orm::ConnectionTrait;
+
+use crate::common::error::AppError;
+use crate::database::repositories::rate_limit::{RateLimitRepository, Writer};
+
+pub struct RateLimitService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> RateLimitService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<RateLimitRepository<'a, Conn>>) -> Arc<Self> {
+        let writer = repository.writer();
+        Arc::new(Self { writer })
+    }
+
+    /// Counts a hit for `key` in the window starting at `window_start` and returns
+    /// the hits of the previous and the current window.
+    pub async fn hit(&self, key: String, window_start: i64, window: i64) -> Result<(u64, u64), AppError> {
+        let (previous, current) = self.writer.hit(key, window_start, window_start - window).await?;
+
+        Ok((previous.max(0) as u64, current.max(0) as u64))
+    }
+
+    /// Deletes the windows that started before `window_start`, which no limit looks at anymore.
+    pub async fn delete_before(&self, window_start: i64) -> Result<u64, AppError> {
+        Ok(self.writer.delete_before(window_start).await?)
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::token::{CustomClaims, Token, TokenClaims, TokenType};
+use crate::database::entity::refresh_token::Model;
+use crate::database::repositories::refresh_token::{NewRefreshToken, Reader, RefreshTokenRepository, Writer};
+use crate::database::repositories::session::{
+    NewSession, Reader as SessionReader, SessionRepository, TouchSession, Writer as SessionWriter
+};
+
+use super::security::jwt::JWT;
+
+pub struct RefreshTokenService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>,
+    pub sessions: SessionReader<'a, Conn>,
+    pub session_writer: SessionWriter<'a, Conn>
+}
+
+impl<'a, Conn> RefreshTokenService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<RefreshTokenRepository<'a, Conn>>, sessions: Arc<SessionRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        let session_writer = sessions.writer();
+        let sessions = sessions.reader();
+        Arc::new(Self { reader, writer, sessions, session_writer })
+    }
+
+    /// Starts a session for the user and issues the first refresh token of its family.
+    pub async fn start(
+        &self, user_id: Uuid, token_version: i32, device: Option<String>, ip: Option<String>, jwt: &JWT
+    ) -> Result<(Uuid, usize, Token), AppError> {
+        let session_id = Uuid::new_v4();
+        let (exp, token, expires_at) = self.issue(user_id, token_version, session_id, device.clone(), jwt).await?;
+
+        self.session_writer.create(
+            NewSession { id: session_id, user_id, device, ip, token_version, expires_at }
+        ).await?;
+
+        Ok((session_id, exp, token))
+    }
+
+    /// Issues a refresh token in the family `family_id` and stores its record.
+    async fn issue(
+        &self, user_id: Uuid, token_version: i32, family_id: Uuid, device: Option<String>, jwt: &JWT
+    ) -> Result<(usize, Token, DateTime<Utc>), AppError> {
+        let id = Uuid::new_v4();
+        let (exp, token) = jwt.create_token(user_id.to_string(), TokenType::REFRESH, id, token_version, None, CustomClaims::default())?;
+
+        let expires_at = DateTime::<Utc>::from_timestamp(exp as i64, 0).ok_or_else(|| {
+            AppError::InternalServerError(
+                AppErrorMessage {
+                    message: "Failed to set expires token date".into(),
+                    details: None
+                }
+            )
+        })?;
+
+        self.writer.create(
+            NewRefreshToken {
+                id,
+                user_id,
+                family_id,
+                device,
+                expires_at
+            }
+        ).await?;
+
+        Ok((exp, token, expires_at))
+    }
+
+    /// Revokes the session and every refresh token of its family.
+    pub async fn end_session(&self, session_id: Uuid) -> Result<u64, AppError> {
+        self.writer.revoke_family(session_id).await?;
+
+        Ok(self.session_writer.revoke(session_id).await?)
+    }
+
+    /// Looks up the stored record for a verified refresh token. A token that was already
+    /// rotated or revoked means it leaked, so its whole family gets revoked. Tokens issued
+    /// before the user's current `token_version` are revoked the same way.
+    pub async fn check(&self, claims: &TokenClaims, token_version: i32) -> Result<Model, AppError> {
+        let record = self.reader
+            .get(claims.jti)
+            .await?
+            .filter(|record| record.user_id.to_string() == claims.sub)
+            .ok_or_else(|| {
+                AppError::UnAuthorizedError(
+                    AppErrorMessage {
+                        message: "Invalid token".into(),
+                        details: None
+                    }
+                )
+            })?;
+
+        if claims.token_version < token_version {
+            self.end_session(record.family_id).await?;
+
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage {
+                    message: "Token was revoked. Try to login again".into(),
+                    details: None
+                }
+            ));
+        }
+
+        if record.used_at.is_some() || record.revoked_at.is_some() {
+            warn!("Refresh token reuse detected, revoking family {}", record.family_id);
+            self.end_session(record.family_id).await?;
+
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage {
+                    message: "Token was revoked. Try to login again".into(),
+                    details: None
+                }
+            ));
+        }
+
+        if !record.is_active() {
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage {
+                    message: "Token expired. Try to login again".into(),
+                    details: None
+                }
+            ));
+        }
+
+        let session = self.sessions.get(record.family_id).await?;
+
+        if session.is_none_or(|session| session.revoked_at.is_some()) {
+            self.writer.revoke_family(record.family_id).await?;
+
+            return Err(AppError::UnAuthorizedError(
+                AppErrorMessage {
+                    message: "Session was revoked. Try to login again".into(),
+                    details: None
+                }
+            ));
+        }
+
+        Ok(record)
+    }
+
+    /// Marks the record as used, issues its successor in the same family and records the use on the session.
+    /// Returns `None` when another request used the record first, which is reuse like in `check`, so the
+    /// session is ended. The caller has to commit that before rejecting the token.
+    pub async fn rotate(
+        &self, record: Model, token_version: i32, device: Option<String>, ip: Option<String>, jwt: &JWT
+    ) -> Result<Option<(usize, Token)>, AppError> {
+        let rows = self.writer.mark_used(record.id).await?;
+
+        if rows == 0 {
+            warn!("Refresh token reuse detected, revoking family {}", record.family_id);
+            self.end_session(record.family_id).await?;
+
+            return Ok(None);
+        }
+
+        let (exp, token, expires_at) = self
+            .issue(record.user_id, token_version, record.family_id, device.clone().or(record.device), jwt)
+            .await?;
+
+        self.session_writer.touch(
+            TouchSession { id: record.family_id, device, ip, token_version, expires_at }
+        ).await?;
+
+        Ok(Some((exp, token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    use super::*;
+    use crate::core::config::{Secret, TokenConfig};
+    use crate::database::repositories::base::Repository;
+
+    fn exec(rows_affected: u64) -> MockExecResult {
+        MockExecResult { last_insert_id: 0, rows_affected }
+    }
+
+    #[tokio::test]
+    async fn losing_a_rotation_race_ends_the_session() {
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([exec(0), exec(2), exec(1)])
+            .into_connection();
+        let service = RefreshTokenService::new(
+            Arc::new(RefreshTokenRepository::new(&connection)), 
+            Arc::new(SessionRepository::new(&connection))
+        );
+        let jwt = JWT::new(TokenConfig { 
+            algorithm: "HS256".into(), 
+            secret_key: Some(Secret::new("c2VjcmV0".into())), 
+            ..Default::default() 
+        }).unwrap();
+        let record = Model {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            family_id: Uuid::new_v4(),
+            device: None,
+            expires_at: Utc::now(),
+            used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+
+        let rotated = service.rotate(record, 1, None, None, &jwt).await.unwrap();
+
+        assert!(rotated.is_none());
+        let log = format!("{:?}", connection.into_transaction_log());
+        assert!(log.contains(r#"UPDATE \"refresh_token\" SET \"revoked_at\""#), "{log}");
+        assert!(log.contains(r#"UPDATE \"session\" SET \"revoked_at\""#), "{log}");
+    }
+}
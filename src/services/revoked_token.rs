@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::status::Status;
+use crate::common::structs::responses::token::TokenClaims;
+use crate::database::repositories::revoked_token::{NewRevokedToken, Reader, RevokedTokenRepository, Writer};
+
+pub struct RevokedTokenService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> RevokedTokenService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<RevokedTokenRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Puts the token on the denylist until it would have expired anyway.
+    pub async fn revoke(&self, claims: &TokenClaims) -> Result<Status, AppError> {
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+            AppError::UnAuthorizedError(
+                AppErrorMessage { message: "Invalid token".into(), details: None }
+            )
+        })?;
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp as i64, 0).ok_or_else(|| {
+            AppError::InternalServerError(
+                AppErrorMessage {
+                    message: "Failed to set expires token date".into(),
+                    details: None
+                }
+            )
+        })?;
+
+        let rows = self.writer.create(NewRevokedToken { id: claims.jti, user_id, expires_at }).await?;
+
+        Ok(Status { status: rows > 0 })
+    }
+
+    pub async fn is_revoked(&self, jti: Uuid) -> Result<bool, AppError> {
+        Ok(self.reader.exists(jti).await?)
+    }
+}
@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use sea_orm::ConnectionTrait;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::requests::role::{CreateRole, UpdateRolePermissions};
+use crate::common::structs::responses::role::Role;
+use crate::database::entity::permission::Model as PermissionModel;
+use crate::database::entity::role::ADMIN_ROLE_ID;
+use crate::database::repositories::role::{NewRole, Reader, RoleRepository, Writer};
+
+use super::security::permission::Permission;
+
+
+/// Maps stored permission rows to the permissions the API knows about, skipping unknown names.
+pub fn into_permissions(models: Vec<PermissionModel>) -> Vec<Permission> {
+    models
+        .into_iter()
+        .filter_map(|model| model.name.parse().ok())
+        .collect()
+}
+
+/// Refuses when `required` holds permissions the actor lacks, so nobody can hand out or act
+/// on more than they have.
+pub fn ensure_held(required: &[Permission], actor_permissions: &[Permission], message: &str) -> Result<(), AppError> {
+    let missing: Vec<Permission> = required
+        .iter()
+        .filter(|permission| !actor_permissions.contains(permission))
+        .copied()
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(AppError::ForbiddenError(AppErrorMessage { 
+            message: message.into(), 
+            details: json!({ "permissions": missing }).into()
+        }));
+    }
+
+    Ok(())
+}
+
+pub struct RoleService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>
+}
+
+impl<'a, Conn> RoleService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<RoleRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        Arc::new(Self { reader, writer })
+    }
+
+    /// Creates a role with permissions the actor holds themselves.
+    pub async fn create(&self, data: CreateRole, actor_permissions: &[Permission]) -> Result<Role, AppError> {
+        ensure_held(&data.permissions, actor_permissions, "Role grants permissions you do not have")?;
+
+        let exists = self.reader.get_by_name(data.name.to_string()).await?;
+
+        if exists.is_some() {
+            return Err(AppError::ConflictError(AppErrorMessage { 
+                message: "Role already exists".into(), 
+                details: json!({ "name": data.name }).into()
+            }));
+        }
+
+        let model = self.writer.create(NewRole { name: data.name.to_string() }).await?;
+        let permissions = self.set_permissions(model.id, data.permissions).await?;
+
+        Ok(Role {
+            id: model.id,
+            name: model.name,
+            permissions,
+            created_at: model.created_at
+        })
+    }
+
+    pub async fn get_many(&self) -> Result<Vec<Role>, AppError> {
+        let roles = self.reader.get_many_with_permissions().await?;
+
+        Ok(
+            roles
+                .into_iter()
+                .map(|(model, permissions)| Role {
+                    id: model.id,
+                    name: model.name,
+                    permissions: into_permissions(permissions),
+                    created_at: model.created_at
+                })
+                .collect()
+        )
+    }
+
+    /// Replaces the permissions of a role with ones the actor holds themselves. The Admin role
+    /// can gain permissions but never lose any, or nobody might be left to manage roles.
+    /// Returns the permissions before the change and the updated role.
+    pub async fn update_permissions(
+        &self, id: Uuid, data: UpdateRolePermissions, actor_permissions: &[Permission]
+    ) -> Result<(Vec<Permission>, Role), AppError> {
+        let model = self.reader.get(id).await?.ok_or_else(|| {
+            AppError::NotFoundError(AppErrorMessage { message: "Role not found".into(), details: None })
+        })?;
+        ensure_held(&data.permissions, actor_permissions, "Role grants permissions you do not have")?;
+
+        let before = into_permissions(self.reader.get_permissions(id).await?);
+        if id == ADMIN_ROLE_ID {
+            let removed: Vec<Permission> = before
+                .iter()
+                .filter(|permission| !data.permissions.contains(permission))
+                .copied()
+                .collect();
+            if !removed.is_empty() {
+                return Err(AppError::ConflictError(AppErrorMessage { 
+                    message: "Permissions cannot be removed from the Admin role".into(), 
+                    details: json!({ "permissions": removed }).into()
+                }));
+            }
+        }
+        let permissions = self.set_permissions(model.id, data.permissions).await?;
+
+        Ok((before, Role {
+            id: model.id,
+            name: model.name,
+            permissions,
+            created_at: model.created_at
+        }))
+    }
+
+    async fn set_permissions(&self, role_id: Uuid, permissions: Vec<Permission>) -> Result<Vec<Permission>, AppError> {
+        let names = permissions.iter().map(|permission| permission.as_str().to_string()).collect();
+        let models = self.reader.get_permissions_by_names(names).await?;
+
+        self.writer
+            .set_permissions(role_id, models.iter().map(|model| model.id).collect())
+            .await?;
+
+        Ok(into_permissions(models))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase};
+
+    use super::*;
+    use crate::database::entity::{permission, role};
+    use crate::database::repositories::base::Repository;
+
+    fn admin_role() -> role::Model {
+        role::Model { id: ADMIN_ROLE_ID, name: "Admin".into(), created_at: Utc::now() }
+    }
+
+    fn permission(permission: Permission) -> permission::Model {
+        permission::Model { id: Uuid::new_v4(), name: permission.as_str().into(), description: None }
+    }
+
+    async fn update(
+        connection: &DatabaseConnection, permissions: Vec<Permission>, actor: &[Permission]
+    ) -> Result<(Vec<Permission>, Role), AppError> {
+        let service = RoleService::new(Arc::new(RoleRepository::new(connection)));
+        service.update_permissions(ADMIN_ROLE_ID, UpdateRolePermissions { permissions }, actor).await
+    }
+
+    #[tokio::test]
+    async fn roles_cannot_grant_permissions_the_actor_lacks() {
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![admin_role()]])
+            .into_connection();
+        let actor = [Permission::RolesWrite];
+
+        let result = update(&connection, vec![Permission::RolesWrite, Permission::ConfigRead], &actor).await;
+
+        assert!(matches!(result, Err(AppError::ForbiddenError(message)) if message.details == Some(json!({ "permissions": ["config:read"] }))));
+
+        let service = RoleService::new(Arc::new(RoleRepository::new(&connection)));
+        let result = service.create(CreateRole { name: "Auditor".into(), permissions: vec![Permission::AuditRead] }, &actor).await;
+        assert!(matches!(result, Err(AppError::ForbiddenError(_))));
+    }
+
+    #[tokio::test]
+    async fn the_admin_role_keeps_its_permissions() {
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![admin_role()]])
+            .append_query_results([vec![permission(Permission::RolesWrite), permission(Permission::UsersRead)]])
+            .into_connection();
+        let actor = [Permission::RolesWrite, Permission::UsersRead];
+
+        let result = update(&connection, vec![Permission::UsersRead], &actor).await;
+
+        assert!(matches!(result, Err(AppError::ConflictError(message)) if message.details == Some(json!({ "permissions": ["roles:write"] }))));
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::common::structs::responses::user::User;
+
+
+/// In-process cache for the revocation state the `auth` middleware checks on every request.
+/// Entries expire after `ttl`, so changes made by other instances become visible within that window.
+pub struct TokenCache {
+    ttl: Duration,
+    users: RwLock<HashMap<Uuid, (Instant, i32, User)>>,
+    revoked: RwLock<HashMap<Uuid, (Instant, bool)>>,
+    sessions: RwLock<HashMap<Uuid, (Instant, bool)>>,
+}
+
+impl TokenCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            users: RwLock::new(HashMap::new()),
+            revoked: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn user(&self, id: Uuid) -> Option<(i32, User)> {
+        let users = self.users.read().ok()?;
+
+        users
+            .get(&id)
+            .filter(|(cached_at, _, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, version, user)| (*version, user.clone()))
+    }
+
+    pub fn set_user(&self, version: i32, user: User) {
+        if let Ok(mut users) = self.users.write() {
+            users.retain(|_, (cached_at, _, _)| cached_at.elapsed() < self.ttl);
+            users.insert(user.id, (Instant::now(), version, user));
+        }
+    }
+
+    pub fn invalidate_user(&self, id: Uuid) {
+        if let Ok(mut users) = self.users.write() {
+            users.remove(&id);
+        }
+    }
+
+    pub fn invalidate_users(&self) {
+        if let Ok(mut users) = self.users.write() {
+            users.clear();
+        }
+    }
+
+    pub fn is_revoked(&self, jti: Uuid) -> Option<bool> {
+        let revoked = self.revoked.read().ok()?;
+
+        revoked
+            .get(&jti)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, is_revoked)| *is_revoked)
+    }
+
+    pub fn set_revoked(&self, jti: Uuid, is_revoked: bool) {
+        if let Ok(mut revoked) = self.revoked.write() {
+            revoked.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            revoked.insert(jti, (Instant::now(), is_revoked));
+        }
+    }
+
+    pub fn is_session_revoked(&self, sid: Uuid) -> Option<bool> {
+        let sessions = self.sessions.read().ok()?;
+
+        sessions
+            .get(&sid)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, is_revoked)| *is_revoked)
+    }
+
+    pub fn set_session_revoked(&self, sid: Uuid, is_revoked: bool) {
+        if let Ok(mut sessions) = self.sessions.write() {
+            sessions.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            sessions.insert(sid, (Instant::now(), is_revoked));
+        }
+    }
+}
+
+
+
+pub fn get_token_cache(ttl_seconds: u64) -> Arc<TokenCache> {
+    Arc::new(TokenCache::new(Duration::from_secs(ttl_seconds)))
+}
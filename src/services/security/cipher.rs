@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use base64::prelude::*;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::MfaConfig;
+
+const NONCE_LENGTH: usize = 12;
+
+
+/// AES-256-GCM for secrets stored at rest. Values are encoded as base64 of nonce and ciphertext.
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+fn cipher_error(message: &str) -> AppError {
+    AppError::InternalServerError(AppErrorMessage { message: message.into(), details: None })
+}
+
+impl SecretCipher {
+    fn new(key: &[u8]) -> Result<Self, anyhow::Error> {
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| anyhow::anyhow!("Encryption key must be 32 bytes long"))?;
+
+        Ok(Self { cipher })
+    }
+
+    pub fn encrypt(&self, plain: &[u8]) -> Result<String, AppError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let encrypted = self.cipher
+            .encrypt(&nonce, plain)
+            .map_err(|_| cipher_error("Failed to encrypt a secret"))?;
+
+        Ok(BASE64_STANDARD.encode([nonce.as_slice(), &encrypted].concat()))
+    }
+
+    pub fn decrypt(&self, encoded: &str) -> Result<Vec<u8>, AppError> {
+        let decoded = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|_| cipher_error("Failed to decrypt a secret"))?;
+
+        if decoded.len() < NONCE_LENGTH {
+            return Err(cipher_error("Failed to decrypt a secret"));
+        }
+
+        let (nonce, encrypted) = decoded.split_at(NONCE_LENGTH);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map_err(|_| cipher_error("Failed to decrypt a secret"))
+    }
+}
+
+
+
+/// Uses `MFA_ENCRYPTION_KEY`, a base64 encoded 32 bytes key checked when the config is loaded.
+pub fn get_cipher(config: &MfaConfig) -> Result<Arc<SecretCipher>, anyhow::Error> {
+    let key = config.encryption_key.as_ref().ok_or_else(|| anyhow::anyhow!("MFA_ENCRYPTION_KEY must be set"))?;
+    let key = BASE64_STANDARD.decode(key.expose().as_bytes()).map_err(|_| anyhow::anyhow!("MFA_ENCRYPTION_KEY must be base64"))?;
+
+    Ok(Arc::new(SecretCipher::new(&key)?))
+}
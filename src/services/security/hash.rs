@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use argon2::{
     password_hash::{
-        rand_core::OsRng, SaltString
+        rand_core::{OsRng, RngCore}, SaltString
     }, 
     Algorithm, 
     Argon2, 
@@ -13,6 +13,9 @@ use argon2::{
     Version
 };
 
+use base64::prelude::*;
+use sha2::{Digest, Sha256};
+
 use crate::common::error::{AppError, AppErrorMessage};
 
 
@@ -26,9 +29,9 @@ impl Argon2Hasher {
 
     fn new(algorithm: Option<Algorithm>, version: Option<Version>, params: Option<Params>) -> Self {
         let argon2 = Argon2::new(
-            algorithm.unwrap_or(Algorithm::default()), 
-            version.unwrap_or(Version::default()),
-            params.unwrap_or(Params::default())
+            algorithm.unwrap_or_default(), 
+            version.unwrap_or_default(),
+            params.unwrap_or_default()
         );
 
         Self {
@@ -46,10 +49,10 @@ impl Argon2Hasher {
     }
 
     pub fn verify_password(&self, hashed_text: &str, plain_text: &str) -> bool {
-        let password_hash = PasswordHash::new(&hashed_text);
+        let password_hash = PasswordHash::new(hashed_text);
 
         if let Ok(pwd) = password_hash {
-            self.argon2.verify_password(&plain_text.as_bytes(), &pwd).is_ok()
+            self.argon2.verify_password(plain_text.as_bytes(), &pwd).is_ok()
         } else {
             false
         }
@@ -60,4 +63,17 @@ impl Argon2Hasher {
 
 pub fn get_argon2_default() -> Arc<Argon2Hasher> {
     Arc::new(Argon2Hasher::new(None, None, None))
-}
\ No newline at end of file
+}
+
+/// Random url-safe token for links sent to users. Only its `hash_token` digest gets stored.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Tokens are random enough to be stored as a plain SHA-256 digest, which keeps them searchable.
+pub fn hash_token(token: &str) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
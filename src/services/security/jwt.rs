@@ -1,62 +1,130 @@
-
-use std::str::FromStr;
+use std::{path::Path, str::FromStr, sync::Arc};
 
 use chrono::TimeDelta;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, Header, Validation, jwk::{Jwk, JwkSet}
+};
+
+use uuid::Uuid;
+
+use crate::{common::{error::{AppError, AppErrorMessage}, structs::responses::token::{CustomClaims, Token, TokenClaims, TokenType}}, core::config::TokenConfig};
+
+use super::keys::KeyRing;
 
-use base64::prelude::*;
+/// Custom claims that can be embedded into access tokens. `sid` is always embedded,
+/// so that revoking a session also stops its access tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmbeddedClaim {
+    Role,
+    Scope,
+}
+
+impl FromStr for EmbeddedClaim {
+    type Err = anyhow::Error;
 
-use crate::{common::{error::{AppError, AppErrorMessage}, structs::responses::token::{Token, TokenClaims, TokenType}}, core::config::TokenConfig};
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "role" => Ok(EmbeddedClaim::Role),
+            "scope" => Ok(EmbeddedClaim::Scope),
+            _ => Err(anyhow::anyhow!("Unknown claim to embed: {value}")),
+        }
+    }
+}
 
+/// Signs tokens with the active key and verifies them with any key of the ring,
+/// picked by the `kid` header.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 pub struct JWT {
     algorithm: Algorithm,
-    secret_key: EncodingKey,
-    public_key: DecodingKey,
+    keys: KeyRing,
+    validation: Validation,
+    issuer: Option<String>,
+    audience: Option<String>,
+    embed_claims: Vec<EmbeddedClaim>,
     access_token_expire_seconds: i64,
     refresh_token_expire_seconds: i64,
 }
 
 impl JWT {
-    fn new(
-        algorithm: Box<str>, 
-        secret_key: Box<str>, 
-        public_key: Box<str>,  
-        access_token_expire_seconds: i64, 
-        refresh_token_expire_seconds: i64
-    ) -> Result<Self, anyhow::Error> {
-        
-        let alg = Algorithm::from_str(&algorithm)?;
-        let decoded_secret_key = BASE64_STANDARD.decode(secret_key.as_ref())?;
-        let decoded_public_key = BASE64_STANDARD.decode(public_key.as_ref())?;
-        let (public_key, secret_key) = match alg {
-            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
-                (DecodingKey::from_secret(&decoded_public_key), EncodingKey::from_secret(&decoded_secret_key))
-            },
-            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 | 
-            Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 |
-            Algorithm::ES256 | Algorithm::ES384 => {
-                (DecodingKey::from_rsa_pem(&decoded_public_key)?, EncodingKey::from_rsa_pem(&decoded_secret_key)?)
-            },
-            Algorithm::EdDSA => {
-                (DecodingKey::from_ed_pem(&decoded_public_key)?, EncodingKey::from_ed_pem(&decoded_secret_key)?)
+    pub fn new(config: TokenConfig) -> Result<Self, anyhow::Error> {
+        let algorithm = Algorithm::from_str(&config.algorithm)
+            .map_err(|_| anyhow::anyhow!("ALGORITHM {} is not supported", config.algorithm))?;
+        let keys = match config.keys_dir.as_deref() {
+            Some(dir) => KeyRing::from_dir(algorithm, Path::new(dir), config.active_key_id.as_deref())?,
+            None => {
+                let secret_key = config.secret_key.as_ref().ok_or_else(|| anyhow::anyhow!("SECRET_KEY must be set"))?.expose();
+                KeyRing::from_env(
+                    algorithm,
+                    secret_key,
+                    config.public_key.as_deref().unwrap_or(secret_key),
+                    config.active_key_id.as_deref(),
+                )?
             },
         };
 
+        let mut validation = Validation::new(algorithm);
+        let mut required = vec!["exp"];
+        validation.leeway = config.leeway_seconds;
+        validation.validate_nbf = true;
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+            required.push("iss");
+        }
+        match &config.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required.push("aud");
+            },
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required);
+
+        let embed_claims = config.embed_claims
+            .split(',')
+            .map(str::trim)
+            .filter(|claim| !claim.is_empty())
+            .map(EmbeddedClaim::from_str)
+            .collect::<Result<_, _>>()?;
+
         Ok(Self {
-            algorithm: alg,
-            secret_key,
-            public_key,
-            access_token_expire_seconds,
-            refresh_token_expire_seconds,
+            algorithm,
+            keys,
+            validation,
+            issuer: config.issuer.map(String::from),
+            audience: config.audience.map(String::from),
+            embed_claims,
+            access_token_expire_seconds: config.access_token_expire_seconds,
+            refresh_token_expire_seconds: config.refresh_token_expire_seconds,
         })
     }
 
+    /// Whether access tokens carry the given custom claim.
+    pub fn embeds(&self, claim: EmbeddedClaim) -> bool {
+        self.embed_claims.contains(&claim)
+    }
+
+    /// Public keys of the ring, sorted by `kid`.
+    pub fn jwks(&self) -> JwkSet {
+        let mut keys: Vec<Jwk> = self.keys.public_keys.values().filter_map(|key| key.jwk.clone()).collect();
+        keys.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+        JwkSet { keys }
+    }
+
+    /// Creates a token. `custom` claims are only kept for access tokens and only `sid` and the embedded ones.
     pub fn create_token(
-        &self, sub: String, typ: TokenType, expire: Option<TimeDelta>
+        &self, sub: String, typ: TokenType, jti: Uuid, token_version: i32, expire: Option<TimeDelta>, custom: CustomClaims
     ) -> Result<(usize, Token), AppError> {
 
         let now = chrono::Utc::now();
+        let custom = match typ {
+            TokenType::ACCESS => CustomClaims {
+                role: custom.role.filter(|_| self.embeds(EmbeddedClaim::Role)),
+                scope: custom.scope.filter(|_| self.embeds(EmbeddedClaim::Scope)),
+                sid: custom.sid,
+            },
+            _ => CustomClaims::default(),
+        };
 
         let iat = now.timestamp() as usize;
         let exp = match typ {
@@ -68,6 +136,9 @@ impl JWT {
                 (now + expire.unwrap_or(chrono::Duration::seconds(self.refresh_token_expire_seconds)))
                     .timestamp() as usize
             }
+            TokenType::EMAIL_VERIFICATION | TokenType::MFA_PENDING => {
+                (now + expire.unwrap_or_default()).timestamp() as usize
+            }
         };
 
         if iat >= exp {
@@ -77,14 +148,20 @@ impl JWT {
         }
 
         let token = encode(
-            &Header::new(self.algorithm), 
+            &Header { kid: Some(self.keys.kid.clone()), ..Header::new(self.algorithm) }, 
             &TokenClaims {
                 _type: typ.clone(),
                 sub,
+                jti,
+                token_version,
+                iss: self.issuer.clone(),
+                aud: self.audience.clone(),
                 iat,
-                exp
+                nbf: iat,
+                exp,
+                custom,
             }, 
-            &self.secret_key
+            &self.keys.secret_key
         )
         .map_err(|_| {
             AppError::ServiceNotImplementedError(
@@ -99,29 +176,32 @@ impl JWT {
     }
 
     pub fn verify_token(&self, token: String) -> Result<TokenClaims, AppError> {
-        let now = chrono::Utc::now();
-
-        let data = decode::<TokenClaims>(
-            &token, 
-            &self.public_key,
-            &Validation::new(self.algorithm)
-        )
-        .map_err(|_| {
+        let invalid = || {
             AppError::UnAuthorizedError(
                 AppErrorMessage {
                     message: "Invalid token provided".into(),
                     details: None
                 }
-        )})?;
-        
-        if data.claims.exp < (now.timestamp() as usize) {
-            return Err(AppError::UnAuthorizedError(
+        )};
+
+        // Tokens issued before key ids were introduced carry none and belong to the active key
+        let kid = decode_header(&token).map_err(|_| invalid())?.kid.unwrap_or_else(|| self.keys.kid.clone());
+        let public_key = self.keys.public_keys.get(&kid).ok_or_else(invalid)?;
+
+        let data = decode::<TokenClaims>(
+            &token, 
+            &public_key.key,
+            &self.validation
+        )
+        .map_err(|error| match error.kind() {
+            ErrorKind::ExpiredSignature => AppError::UnAuthorizedError(
                 AppErrorMessage {
                     message: "Token expired. Try to login again".into(),
                     details: None
                 }
-            ));
-        }
+            ),
+            _ => invalid(),
+        })?;
 
         Ok(data.claims)
     }
@@ -129,12 +209,353 @@ impl JWT {
 
 
 
-pub fn get_jwt(config: TokenConfig) -> JWT {
-    JWT::new(
-        config.algorithm, 
-        config.secret_key, 
-        config.public_key.expect("public_key must be set"), 
-        config.access_token_expire_seconds, 
-        config.refresh_token_expire_seconds
-    ).expect("JWT service was not created")
-}
\ No newline at end of file
+pub fn get_jwt(config: TokenConfig) -> Result<Arc<JWT>, anyhow::Error> {
+    Ok(Arc::new(JWT::new(config).map_err(|error| anyhow::anyhow!("Invalid JWT configuration: {error}"))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use base64::prelude::*;
+    use jsonwebtoken::jwk::AlgorithmParameters;
+
+    use super::*;
+    use crate::core::config::Secret;
+    use crate::services::security::keys::{generate, remove, KeyError, KeyKind};
+
+    /// PEM private key, PEM public key and private JWK of one test key.
+    type TestKey = (&'static [u8], &'static [u8], &'static [u8]);
+
+    const SECRET: &[u8] = b"secret-for-tests-0123456789abcdef";
+    const RSA: TestKey = (
+        include_bytes!("testdata/rsa.pem"), include_bytes!("testdata/rsa.pub.pem"), include_bytes!("testdata/rsa.jwk")
+    );
+    const P256: TestKey = (
+        include_bytes!("testdata/p256.pem"), include_bytes!("testdata/p256.pub.pem"), include_bytes!("testdata/p256.jwk")
+    );
+    const P384: TestKey = (
+        include_bytes!("testdata/p384.pem"), include_bytes!("testdata/p384.pub.pem"), include_bytes!("testdata/p384.jwk")
+    );
+    const ED25519: TestKey = (
+        include_bytes!("testdata/ed25519.pem"), include_bytes!("testdata/ed25519.pub.pem"), include_bytes!("testdata/ed25519.jwk")
+    );
+
+    /// Every asymmetric algorithm with the key it is tested with.
+    const ASYMMETRIC: [(&str, TestKey); 9] = [
+        ("RS256", RSA), ("RS384", RSA), ("RS512", RSA),
+        ("PS256", RSA), ("PS384", RSA), ("PS512", RSA),
+        ("ES256", P256), ("ES384", P384), ("EdDSA", ED25519),
+    ];
+
+    fn config(algorithm: &str, secret_key: &[u8], public_key: &[u8]) -> TokenConfig {
+        TokenConfig {
+            algorithm: algorithm.into(),
+            secret_key: Some(Secret::new(BASE64_STANDARD.encode(secret_key).into())),
+            public_key: Some(BASE64_STANDARD.encode(public_key).into()),
+            keys_dir: None,
+            active_key_id: None,
+            issuer: None,
+            audience: None,
+            leeway_seconds: 0,
+            embed_claims: "".into(),
+            access_token_expire_seconds: 60,
+            refresh_token_expire_seconds: 120,
+            cache_ttl_seconds: 30,
+        }
+    }
+
+    fn der(pem: &[u8]) -> Vec<u8> {
+        pem::parse(pem).unwrap().into_contents()
+    }
+
+    fn key_error(result: Result<JWT, anyhow::Error>) -> KeyError {
+        result.err().expect("configuration must be rejected").downcast().expect("error must be a KeyError")
+    }
+
+    /// The `kid` header of the tokens `jwt` signs.
+    fn signing_kid(jwt: &JWT) -> Option<String> {
+        let (_, token) = jwt
+            .create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, CustomClaims::default())
+            .unwrap();
+        decode_header(&token.token).unwrap().kid
+    }
+
+    fn assert_round_trip(jwt: &JWT, algorithm: &str) {
+        let jti = Uuid::new_v4();
+        let (_, token) = jwt
+            .create_token("user".into(), TokenType::ACCESS, jti, 1, None, CustomClaims::default())
+            .unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+
+        let claims = jwt.verify_token(token.token.clone()).unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+        assert_eq!(claims.sub, "user", "{algorithm}");
+        assert_eq!(claims.jti, jti, "{algorithm}");
+
+        let (unsigned, _) = token.token.rsplit_once('.').unwrap();
+        let forged = format!("{unsigned}.{}", BASE64_URL_SAFE_NO_PAD.encode([0u8; 64]));
+        assert!(jwt.verify_token(forged).is_err(), "{algorithm} accepted a forged signature");
+    }
+
+    #[test]
+    fn hmac_algorithms_sign_and_verify() {
+        for algorithm in ["HS256", "HS384", "HS512"] {
+            let jwt = JWT::new(config(algorithm, SECRET, SECRET)).unwrap();
+            assert_round_trip(&jwt, algorithm);
+            assert!(jwt.jwks().keys.is_empty(), "{algorithm} published its secret");
+        }
+    }
+
+    #[test]
+    fn hmac_secret_can_be_a_jwk() {
+        let jwk = format!(r#"{{"kty":"oct","k":"{}"}}"#, BASE64_URL_SAFE_NO_PAD.encode(SECRET));
+        let jwt = JWT::new(config("HS256", jwk.as_bytes(), SECRET)).unwrap();
+        assert_round_trip(&jwt, "HS256");
+    }
+
+    #[test]
+    fn asymmetric_algorithms_sign_and_verify_pem_keys() {
+        for (algorithm, (private, public, _)) in ASYMMETRIC {
+            let jwt = JWT::new(config(algorithm, private, public)).unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+            assert_round_trip(&jwt, algorithm);
+        }
+    }
+
+    #[test]
+    fn asymmetric_algorithms_sign_and_verify_der_keys() {
+        for (algorithm, (private, public, _)) in ASYMMETRIC {
+            let jwt = JWT::new(config(algorithm, &der(private), &der(public)))
+                .unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+            assert_round_trip(&jwt, algorithm);
+        }
+    }
+
+    #[test]
+    fn asymmetric_algorithms_sign_and_verify_jwk_keys() {
+        for (algorithm, (_, _, jwk)) in ASYMMETRIC {
+            let jwt = JWT::new(config(algorithm, jwk, jwk)).unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+            assert_round_trip(&jwt, algorithm);
+        }
+    }
+
+    #[test]
+    fn private_key_formats_are_interchangeable() {
+        for (algorithm, (private, public, jwk)) in ASYMMETRIC {
+            // The key id is derived from the public key, so all three rings share it
+            let verifier = JWT::new(config(algorithm, private, public)).unwrap();
+
+            for signer in [config(algorithm, &der(private), public), config(algorithm, jwk, public)] {
+                let (_, token) = JWT::new(signer)
+                    .unwrap()
+                    .create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, CustomClaims::default())
+                    .unwrap();
+                assert!(verifier.verify_token(token.token).is_ok(), "{algorithm}");
+            }
+        }
+    }
+
+    #[test]
+    fn jwks_publishes_public_members_only() {
+        for (algorithm, (private, public, _)) in ASYMMETRIC {
+            let jwks = JWT::new(config(algorithm, private, public)).unwrap().jwks();
+            assert_eq!(jwks.keys.len(), 1, "{algorithm}");
+
+            let jwk = &jwks.keys[0];
+            let expected = KeyKind::expected(Algorithm::from_str(algorithm).unwrap());
+            let serialized = serde_json::to_value(jwk).unwrap();
+            assert_eq!(serialized["alg"], algorithm);
+            assert_eq!(serialized["use"], "sig");
+            assert!(serialized.get("d").is_none(), "{algorithm} published its private key");
+
+            match (&jwk.algorithm, expected) {
+                (AlgorithmParameters::RSA(params), KeyKind::Rsa) => assert_eq!(params.e, "AQAB"),
+                (AlgorithmParameters::EllipticCurve(params), KeyKind::Ec(curve)) => {
+                    let size = if curve == jsonwebtoken::jwk::EllipticCurve::P256 { 43 } else { 64 };
+                    assert_eq!(params.curve, curve);
+                    assert_eq!((params.x.len(), params.y.len()), (size, size), "{algorithm}");
+                },
+                (AlgorithmParameters::OctetKeyPair(params), KeyKind::Ed25519) => assert_eq!(params.x.len(), 43),
+                (parameters, expected) => panic!("{algorithm} published {parameters:?}, expected {expected}"),
+            }
+        }
+    }
+
+    #[test]
+    fn key_of_another_type_is_a_configuration_error() {
+        let cases: [(&str, &[u8], &[u8]); 6] = [
+            ("ES256", P384.0, P384.1),
+            ("ES384", P256.0, P256.1),
+            ("RS256", P256.0, P256.1),
+            ("EdDSA", RSA.0, RSA.1),
+            ("ES256", ED25519.2, ED25519.2),
+            ("PS256", &der(P256.0), &der(P256.1)),
+        ];
+
+        for (algorithm, private, public) in cases {
+            match key_error(JWT::new(config(algorithm, private, public))) {
+                KeyError::Mismatch { expected, found, .. } => assert_ne!(expected, found),
+                error => panic!("{algorithm}: unexpected {error}"),
+            }
+        }
+    }
+
+    #[test]
+    fn keys_of_different_pairs_are_a_configuration_error() {
+        // The P-256 base point, a valid public key that belongs to another private key
+        let base_point = br#"{
+            "kty": "EC", "crv": "P-256",
+            "x": "axfR8uEsQkf4vOblY6RA8ncDfYEt6zOg9KE5RdiYwpY",
+            "y": "T-NC4v4af5uO5-tKfA-eFivOM1drMV7Oy7ZAaDe_UfU"
+        }"#;
+        let cases: [(&str, &[u8], &[u8]); 2] = [("HS256", SECRET, b"another-secret"), ("ES256", P256.0, base_point)];
+
+        for (algorithm, private, public) in cases {
+            let error = key_error(JWT::new(config(algorithm, private, public)));
+            assert!(matches!(error, KeyError::PairMismatch(_)), "{algorithm}: unexpected {error}");
+        }
+    }
+
+    #[test]
+    fn unreadable_keys_are_a_configuration_error() {
+        let error = key_error(JWT::new(config("RS256", b"not a key", RSA.1)));
+        assert!(matches!(error, KeyError::InvalidKey { .. }), "unexpected {error}");
+
+        let mut config = config("RS256", RSA.0, RSA.1);
+        config.public_key = Some("not base64!".into());
+        let error = key_error(JWT::new(config));
+        assert!(matches!(error, KeyError::InvalidBase64("PUBLIC_KEY")), "unexpected {error}");
+
+        let error = JWT::new(self::config("RS1024", RSA.0, RSA.1)).err().unwrap();
+        assert_eq!(error.to_string(), "ALGORITHM RS1024 is not supported");
+    }
+
+    #[test]
+    fn key_ring_verifies_tokens_of_rotated_keys() {
+        let dir = std::env::temp_dir().join(format!("jwt-keys-{}", Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("2024.pem"), RSA.0).unwrap();
+        fs::write(dir.join("2024.pub.pem"), RSA.1).unwrap();
+        fs::write(dir.join("2025.der"), der(RSA.0)).unwrap();
+        fs::write(dir.join("2025.pub.jwk"), RSA.2).unwrap();
+        let ring = |active: &str| {
+            let mut config = config("RS256", b"", b"");
+            config.keys_dir = Some(dir.to_string_lossy().into());
+            config.active_key_id = Some(active.into());
+            JWT::new(config)
+        };
+
+        let old = ring("2024").unwrap();
+        let (_, token) = old
+            .create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, CustomClaims::default())
+            .unwrap();
+        assert_eq!(decode_header(&token.token).unwrap().kid.as_deref(), Some("2024"));
+
+        let new = ring("2025").unwrap();
+        assert!(new.verify_token(token.token.clone()).is_ok());
+        assert_eq!(new.jwks().keys.len(), 2);
+        assert_round_trip(&new, "RS256");
+
+        fs::remove_file(dir.join("2024.pub.pem")).unwrap();
+        let retired = ring("2025").unwrap();
+        assert!(retired.verify_token(token.token).is_err());
+        assert!(matches!(key_error(ring("2024")), KeyError::MissingVerificationKey(_)));
+        assert!(matches!(key_error(ring("2026")), KeyError::MissingSigningKey(_)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tokens_of_another_issuer_or_audience_are_rejected() {
+        let jwt = |issuer: &str, audience: &str| {
+            let mut config = config("HS256", SECRET, SECRET);
+            config.issuer = Some(issuer.into());
+            config.audience = Some(audience.into());
+            JWT::new(config).unwrap()
+        };
+        let staging = jwt("https://staging.example.com", "api");
+        let (_, token) = staging
+            .create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, CustomClaims::default())
+            .unwrap();
+
+        assert!(staging.verify_token(token.token.clone()).is_ok());
+        assert!(jwt("https://example.com", "api").verify_token(token.token.clone()).is_err());
+        assert!(jwt("https://staging.example.com", "admin").verify_token(token.token).is_err());
+    }
+
+    #[test]
+    fn access_tokens_always_carry_the_session() {
+        let jwt = JWT::new(TokenConfig { embed_claims: "role".into(), ..config("HS256", SECRET, SECRET) }).unwrap();
+        let (sid, custom) = (Uuid::new_v4(), |sid| CustomClaims { role: Some("admin".into()), scope: Some("users:read".into()), sid });
+
+        let (_, access) = jwt.create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, custom(Some(sid))).unwrap();
+        let claims = jwt.verify_token(access.token).unwrap().custom;
+        assert_eq!(claims.sid, Some(sid));
+        assert_eq!(claims.role.as_deref(), Some("admin"));
+        assert_eq!(claims.scope, None);
+
+        let plain = JWT::new(config("HS256", SECRET, SECRET)).unwrap();
+        let (_, access) = plain.create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, custom(Some(sid))).unwrap();
+        assert_eq!(plain.verify_token(access.token).unwrap().custom.sid, Some(sid));
+
+        let (_, refresh) = plain.create_token("user".into(), TokenType::REFRESH, Uuid::new_v4(), 1, None, custom(Some(sid))).unwrap();
+        assert_eq!(plain.verify_token(refresh.token).unwrap().custom.sid, None);
+
+        assert!(JWT::new(TokenConfig { embed_claims: "role,sid".into(), ..config("HS256", SECRET, SECRET) }).is_err());
+    }
+
+    #[test]
+    fn secrets_are_not_named_after_themselves() {
+        let kid = |config| signing_kid(&JWT::new(config).unwrap()).unwrap();
+
+        assert_eq!(kid(config("HS256", SECRET, SECRET)), "hmac");
+        assert_eq!(kid(config("HS256", b"another-secret-0123456789abcdef", b"another-secret-0123456789abcdef")), "hmac");
+        assert_eq!(kid(TokenConfig { active_key_id: Some("2025".into()), ..config("HS256", SECRET, SECRET) }), "2025");
+        assert_eq!(kid(TokenConfig { active_key_id: Some("2025".into()), ..config("RS256", RSA.0, RSA.1) }), "2025");
+    }
+
+    #[test]
+    fn key_ring_of_secrets_verifies_tokens_of_rotated_secrets() {
+        let dir = std::env::temp_dir().join(format!("jwt-keys-{}", Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("2024.der"), SECRET).unwrap();
+        let next = generate(Algorithm::HS256, &dir).unwrap();
+        let ring = |active: &str| {
+            let mut config = config("HS256", b"", b"");
+            config.keys_dir = Some(dir.to_string_lossy().into());
+            config.active_key_id = Some(active.into());
+            JWT::new(config)
+        };
+
+        let old = ring("2024").unwrap();
+        let (_, token) = old
+            .create_token("user".into(), TokenType::ACCESS, Uuid::new_v4(), 1, None, CustomClaims::default())
+            .unwrap();
+        let new = ring(&next).unwrap();
+        assert!(new.verify_token(token.token.clone()).is_ok());
+        assert!(new.jwks().keys.is_empty());
+
+        remove(&dir, "2024").unwrap();
+        assert!(ring(&next).unwrap().verify_token(token.token).is_err());
+
+        fs::write(dir.join(format!("{next}.pub.der")), SECRET).unwrap();
+        assert!(matches!(key_error(ring(&next)), KeyError::InvalidKey { .. }));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn generated_keys_sign_and_verify() {
+        for algorithm in ASYMMETRIC.map(|(algorithm, _)| algorithm).into_iter().chain(["HS256", "HS384", "HS512"]) {
+            let dir = std::env::temp_dir().join(format!("jwt-keys-{}", Uuid::new_v4()));
+            fs::create_dir(&dir).unwrap();
+
+            let kid = generate(Algorithm::from_str(algorithm).unwrap(), &dir).unwrap();
+            let mut config = config(algorithm, b"", b"");
+            config.keys_dir = Some(dir.to_string_lossy().into());
+            let jwt = JWT::new(config).unwrap_or_else(|error| panic!("{algorithm}: {error}"));
+            assert_round_trip(&jwt, algorithm);
+            assert_eq!(signing_kid(&jwt), Some(kid), "{algorithm}");
+
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,590 @@
+use std::{collections::HashMap, fmt, fs, io::Write, path::Path};
+
+use argon2::password_hash::rand_core::OsRng;
+use base64::prelude::*;
+use jsonwebtoken::{
+    crypto, Algorithm, DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters, EllipticCurveKeyType,
+        Jwk, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType
+    }
+};
+use pkcs1::der::Decode;
+use pkcs8::PrivateKeyInfo;
+use rsa::{pkcs8::{EncodePrivateKey, EncodePublicKey}, RsaPrivateKey};
+use ring::{
+    rand::{SecureRandom, SystemRandom},
+    signature::{EcdsaKeyPair, Ed25519KeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_FIXED_SIGNING}
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use spki::{ObjectIdentifier, SubjectPublicKeyInfoRef};
+use thiserror::Error;
+
+const RSA_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const EC_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const P256_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const P384_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+const RSA_KEY_BITS: usize = 2048;
+
+/// Id of the `SECRET_KEY` of HMAC algorithms when `JWT_ACTIVE_KID` is not set.
+const SECRET_KID: &str = "hmac";
+
+/// Key extensions accepted in `JWT_KEYS_DIR`, verification keys add `.pub` before them.
+const KEY_EXTENSIONS: [&str; 3] = [".pem", ".der", ".jwk"];
+
+/// Configuration errors of the token keys, reported at startup.
+#[derive(Debug, Error)]
+pub enum KeyError {
+    #[error("{0} must be base64 encoded")]
+    InvalidBase64(&'static str),
+    #[error("Failed to read key {kid}: {reason}")]
+    InvalidKey { kid: String, reason: String },
+    #[error("Key {kid} is {found} but {algorithm:?} needs {expected}")]
+    Mismatch { kid: String, algorithm: Algorithm, found: KeyKind, expected: KeyKind },
+    #[error("Signing key {0} does not match its verification key")]
+    PairMismatch(String),
+    #[error("Failed to read JWT_KEYS_DIR {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("JWT_ACTIVE_KID must be set when {0} signing keys are found")]
+    NoActiveKey(usize),
+    #[error("Signing key {0} was not found in JWT_KEYS_DIR")]
+    MissingSigningKey(String),
+    #[error("Verification key {0} was not found in JWT_KEYS_DIR")]
+    MissingVerificationKey(String),
+    #[error("{0:?} keys can't be generated, create them with openssl")]
+    UnsupportedGeneration(Algorithm),
+    #[error("Failed to generate a key")]
+    Generation,
+}
+
+/// Type of a key, checked against the algorithm it is used with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyKind {
+    Secret,
+    Rsa,
+    Ec(EllipticCurve),
+    Ed25519,
+    Other(String),
+}
+
+impl KeyKind {
+    /// The kind of key `algorithm` signs with.
+    pub fn expected(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => KeyKind::Secret,
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 |
+            Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 => KeyKind::Rsa,
+            Algorithm::ES256 => KeyKind::Ec(EllipticCurve::P256),
+            Algorithm::ES384 => KeyKind::Ec(EllipticCurve::P384),
+            Algorithm::EdDSA => KeyKind::Ed25519,
+        }
+    }
+
+    fn of_jwk(parameters: &AlgorithmParameters) -> Self {
+        match parameters {
+            AlgorithmParameters::RSA(_) => KeyKind::Rsa,
+            AlgorithmParameters::EllipticCurve(params) => KeyKind::Ec(params.curve.clone()),
+            AlgorithmParameters::OctetKeyPair(params) if params.curve == EllipticCurve::Ed25519 => KeyKind::Ed25519,
+            AlgorithmParameters::OctetKeyPair(params) => KeyKind::Other(format!("{:?}", params.curve)),
+            AlgorithmParameters::OctetKey(_) => KeyKind::Secret,
+        }
+    }
+
+    fn of_oid(oid: ObjectIdentifier, parameters: Option<ObjectIdentifier>) -> Self {
+        match (oid, parameters) {
+            (RSA_OID, _) => KeyKind::Rsa,
+            (EC_OID, Some(P256_OID)) => KeyKind::Ec(EllipticCurve::P256),
+            (EC_OID, Some(P384_OID)) => KeyKind::Ec(EllipticCurve::P384),
+            (EC_OID, curve) => KeyKind::Other(format!("EC {}", curve.map(|oid| oid.to_string()).unwrap_or_default())),
+            (ED25519_OID, _) => KeyKind::Ed25519,
+            (oid, _) => KeyKind::Other(oid.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyKind::Secret => write!(f, "a secret"),
+            KeyKind::Rsa => write!(f, "an RSA key"),
+            KeyKind::Ec(EllipticCurve::P256) => write!(f, "an EC P-256 key"),
+            KeyKind::Ec(EllipticCurve::P384) => write!(f, "an EC P-384 key"),
+            KeyKind::Ec(curve) => write!(f, "an EC {curve:?} key"),
+            KeyKind::Ed25519 => write!(f, "an Ed25519 key"),
+            KeyKind::Other(name) => write!(f, "a {name} key"),
+        }
+    }
+}
+
+/// Encoding of a key file, told apart by its first bytes. Anything that is neither
+/// PEM nor a JWK is taken as DER, or as the raw secret for HMAC algorithms.
+enum KeyFormat {
+    Pem,
+    Jwk,
+    Der,
+}
+
+impl KeyFormat {
+    fn of(key: &[u8]) -> Self {
+        let start = key.trim_ascii_start();
+        if start.starts_with(b"-----BEGIN") {
+            KeyFormat::Pem
+        } else if start.starts_with(b"{") {
+            KeyFormat::Jwk
+        } else {
+            KeyFormat::Der
+        }
+    }
+}
+
+/// Members of a private JWK needed to rebuild its DER encoding.
+#[derive(Deserialize)]
+struct PrivateJwk {
+    kty: String,
+    crv: Option<String>,
+    k: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    d: Option<String>,
+    p: Option<String>,
+    q: Option<String>,
+    dp: Option<String>,
+    dq: Option<String>,
+    qi: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+impl PrivateJwk {
+    fn kind(&self) -> KeyKind {
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("oct", _) => KeyKind::Secret,
+            ("RSA", _) => KeyKind::Rsa,
+            ("EC", Some("P-256")) => KeyKind::Ec(EllipticCurve::P256),
+            ("EC", Some("P-384")) => KeyKind::Ec(EllipticCurve::P384),
+            ("OKP", Some("Ed25519")) => KeyKind::Ed25519,
+            (kty, crv) => KeyKind::Other(format!("{kty} {}", crv.unwrap_or_default()).trim_end().into()),
+        }
+    }
+
+    fn member(&self, value: &Option<String>, name: &str) -> Result<Vec<u8>, anyhow::Error> {
+        let value = value.as_deref().ok_or_else(|| anyhow::anyhow!("JWK has no \"{name}\" member"))?;
+        Ok(BASE64_URL_SAFE_NO_PAD.decode(value)?)
+    }
+
+    /// PKCS#1 for RSA keys, PKCS#8 for the others.
+    fn to_der(&self) -> Result<Vec<u8>, anyhow::Error> {
+        match self.kind() {
+            KeyKind::Rsa => {
+                let mut content = der_uint(&[0]);
+                for (value, name) in [
+                    (&self.n, "n"), (&self.e, "e"), (&self.d, "d"), (&self.p, "p"),
+                    (&self.q, "q"), (&self.dp, "dp"), (&self.dq, "dq"), (&self.qi, "qi"),
+                ] {
+                    content.extend(der_uint(&self.member(value, name)?));
+                }
+                Ok(der(0x30, &content))
+            },
+            KeyKind::Ec(curve) => {
+                let curve = if curve == EllipticCurve::P256 { P256_OID } else { P384_OID };
+                let mut point = vec![0x00, 0x04];
+                point.extend(self.member(&self.x, "x")?);
+                point.extend(self.member(&self.y, "y")?);
+                let key = der(0x30, &[
+                    der_uint(&[1]),
+                    der(0x04, &self.member(&self.d, "d")?),
+                    der(0xa1, &der(0x03, &point)),
+                ].concat());
+                Ok(pkcs8(&[der(0x06, EC_OID.as_bytes()), der(0x06, curve.as_bytes())].concat(), &key))
+            },
+            KeyKind::Ed25519 => {
+                let key = der(0x04, &self.member(&self.d, "d")?);
+                Ok(pkcs8(&der(0x06, ED25519_OID.as_bytes()), &key))
+            },
+            kind => anyhow::bail!("JWK is {kind}"),
+        }
+    }
+}
+
+/// Encodes a DER element. Only what rebuilding private keys from JWKs needs.
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    if content.len() < 0x80 {
+        element.push(content.len() as u8);
+    } else {
+        let length = content.len().to_be_bytes();
+        let length = &length[length.iter().take_while(|byte| **byte == 0).count()..];
+        element.push(0x80 | length.len() as u8);
+        element.extend_from_slice(length);
+    }
+    element.extend_from_slice(content);
+    element
+}
+
+/// Encodes a big-endian unsigned integer as a DER INTEGER.
+fn der_uint(bytes: &[u8]) -> Vec<u8> {
+    let bytes = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+    let mut content = Vec::with_capacity(bytes.len() + 1);
+    if bytes.first().is_none_or(|byte| byte & 0x80 != 0) {
+        content.push(0);
+    }
+    content.extend_from_slice(bytes);
+    der(0x02, &content)
+}
+
+fn pkcs8(algorithm: &[u8], key: &[u8]) -> Vec<u8> {
+    der(0x30, &[der_uint(&[0]), der(0x30, algorithm), der(0x04, key)].concat())
+}
+
+fn spki(algorithm: &[u8], key: &[u8]) -> Vec<u8> {
+    der(0x30, &[der(0x30, algorithm), der(0x03, &[&[0], key].concat())].concat())
+}
+
+pub fn is_symmetric(algorithm: Algorithm) -> bool {
+    KeyKind::expected(algorithm) == KeyKind::Secret
+}
+
+fn check_kind(kid: &str, algorithm: Algorithm, found: KeyKind) -> Result<(), KeyError> {
+    let expected = KeyKind::expected(algorithm);
+    match found == expected {
+        true => Ok(()),
+        false => Err(KeyError::Mismatch { kid: kid.into(), algorithm, found, expected }),
+    }
+}
+
+fn secret(key: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    match KeyFormat::of(key) {
+        KeyFormat::Jwk => {
+            let jwk: PrivateJwk = serde_json::from_slice(key)?;
+            if jwk.kind() != KeyKind::Secret {
+                anyhow::bail!("JWK is {}", jwk.kind());
+            }
+            jwk.member(&jwk.k, "k")
+        },
+        KeyFormat::Pem => anyhow::bail!("HMAC secrets can't be PEM encoded"),
+        KeyFormat::Der => Ok(key.to_vec()),
+    }
+}
+
+fn spki_parameters(der: &[u8]) -> Result<AlgorithmParameters, anyhow::Error> {
+    let info = SubjectPublicKeyInfoRef::from_der(der)?;
+    let public_key = info.subject_public_key.raw_bytes();
+    let encode = |bytes: &[u8]| BASE64_URL_SAFE_NO_PAD.encode(bytes);
+
+    Ok(match KeyKind::of_oid(info.algorithm.oid, info.algorithm.parameters_oid().ok()) {
+        KeyKind::Rsa => rsa_parameters(public_key)?,
+        KeyKind::Ec(curve) => {
+            let size = if curve == EllipticCurve::P256 { 32 } else { 48 };
+            if public_key.len() != 1 + 2 * size || public_key[0] != 0x04 {
+                anyhow::bail!("Elliptic curve key must be an uncompressed point");
+            }
+            AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                key_type: EllipticCurveKeyType::EC,
+                curve,
+                x: encode(&public_key[1..=size]),
+                y: encode(&public_key[size + 1..]),
+            })
+        },
+        KeyKind::Ed25519 => AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: encode(public_key),
+        }),
+        kind => anyhow::bail!("Unsupported public key, {kind}"),
+    })
+}
+
+fn rsa_parameters(der: &[u8]) -> Result<AlgorithmParameters, anyhow::Error> {
+    let key = pkcs1::RsaPublicKey::from_der(der)?;
+    Ok(AlgorithmParameters::RSA(RSAKeyParameters {
+        key_type: RSAKeyType::RSA,
+        n: BASE64_URL_SAFE_NO_PAD.encode(key.modulus.as_bytes()),
+        e: BASE64_URL_SAFE_NO_PAD.encode(key.public_exponent.as_bytes()),
+    }))
+}
+
+/// Reads a public key into its JWK. PEM and DER keys can be SubjectPublicKeyInfo
+/// or PKCS#1 encoded, private members of a JWK are ignored.
+fn public_jwk(kid: &str, algorithm: Algorithm, key: &[u8]) -> Result<Jwk, KeyError> {
+    let parameters = match KeyFormat::of(key) {
+        KeyFormat::Pem => pem::parse(key)
+            .map_err(anyhow::Error::from)
+            .and_then(|pem| match pem.tag() {
+                "PUBLIC KEY" => spki_parameters(pem.contents()),
+                "RSA PUBLIC KEY" => rsa_parameters(pem.contents()),
+                tag => anyhow::bail!("Expected a public key, got {tag}"),
+            }),
+        KeyFormat::Jwk => serde_json::from_slice::<Jwk>(key)
+            .map(|jwk| jwk.algorithm)
+            .map_err(anyhow::Error::from),
+        KeyFormat::Der => spki_parameters(key).or_else(|_| rsa_parameters(key)),
+    }
+    .map_err(|error| KeyError::InvalidKey { kid: kid.into(), reason: error.to_string() })?;
+
+    check_kind(kid, algorithm, KeyKind::of_jwk(&parameters))?;
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: format!("{algorithm:?}").parse::<KeyAlgorithm>().ok(),
+            key_id: Some(kid.into()),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+/// Reads a private key. PEM and DER keys can be PKCS#8 or, for RSA, PKCS#1 encoded.
+fn signing_key(kid: &str, algorithm: Algorithm, key: &[u8]) -> Result<EncodingKey, KeyError> {
+    let invalid = |error: anyhow::Error| KeyError::InvalidKey { kid: kid.into(), reason: error.to_string() };
+
+    if is_symmetric(algorithm) {
+        return Ok(EncodingKey::from_secret(&secret(key).map_err(invalid)?));
+    }
+
+    let der = match KeyFormat::of(key) {
+        KeyFormat::Pem => {
+            let pem = pem::parse(key).map_err(|error| invalid(error.into()))?;
+            match pem.tag() {
+                "PRIVATE KEY" | "RSA PRIVATE KEY" => pem.into_contents(),
+                "EC PRIVATE KEY" => return Err(invalid(anyhow::anyhow!(
+                    "SEC1 keys are not supported, convert it to PKCS#8 with `openssl pkcs8 -topk8 -nocrypt`"
+                ))),
+                tag => return Err(invalid(anyhow::anyhow!("Expected a private key, got {tag}"))),
+            }
+        },
+        KeyFormat::Jwk => {
+            let jwk: PrivateJwk = serde_json::from_slice(key).map_err(|error| invalid(error.into()))?;
+            check_kind(kid, algorithm, jwk.kind())?;
+            jwk.to_der().map_err(invalid)?
+        },
+        KeyFormat::Der => key.to_vec(),
+    };
+
+    // PKCS#8 names the key type, anything else can only be a PKCS#1 RSA key
+    let (kind, rsa_key) = match PrivateKeyInfo::from_der(&der) {
+        Ok(info) => (
+            KeyKind::of_oid(info.algorithm.oid, info.algorithm.parameters_oid().ok()),
+            info.private_key,
+        ),
+        Err(_) => {
+            pkcs1::RsaPrivateKey::from_der(&der).map_err(|error| invalid(error.into()))?;
+            (KeyKind::Rsa, der.as_slice())
+        },
+    };
+    check_kind(kid, algorithm, kind.clone())?;
+
+    // ring reads RSA keys as PKCS#1 and the others from the whole PKCS#8 document
+    Ok(match kind {
+        KeyKind::Rsa => EncodingKey::from_rsa_der(rsa_key),
+        KeyKind::Ec(_) => EncodingKey::from_ec_der(&der),
+        _ => EncodingKey::from_ed_der(&der),
+    })
+}
+
+/// A key tokens are verified with. `jwk` is what gets published, symmetric keys have none.
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub key: DecodingKey,
+    pub jwk: Option<Jwk>,
+}
+
+fn verification_key(kid: &str, algorithm: Algorithm, key: &[u8]) -> Result<VerificationKey, KeyError> {
+    if is_symmetric(algorithm) {
+        let secret = secret(key).map_err(|error| KeyError::InvalidKey { kid: kid.into(), reason: error.to_string() })?;
+        return Ok(VerificationKey { key: DecodingKey::from_secret(&secret), jwk: None });
+    }
+
+    let jwk = public_jwk(kid, algorithm, key)?;
+    let key = DecodingKey::from_jwk(&jwk)
+        .map_err(|error| KeyError::InvalidKey { kid: kid.into(), reason: error.to_string() })?;
+
+    Ok(VerificationKey { key, jwk: Some(jwk) })
+}
+
+/// The active signing key and every key tokens are verified with, by `kid`.
+#[derive(Clone)]
+pub struct KeyRing {
+    pub kid: String,
+    pub secret_key: EncodingKey,
+    pub public_keys: HashMap<String, VerificationKey>,
+}
+
+impl KeyRing {
+    /// Single key pair from `SECRET_KEY`/`PUBLIC_KEY`, with `kid` as its id when set. Otherwise the
+    /// id of an asymmetric pair is derived from the public key so that it stays the same between
+    /// restarts, while secrets get `SECRET_KID` as anything derived from them would help guess them.
+    pub fn from_env(algorithm: Algorithm, secret_key: &str, public_key: &str, kid: Option<&str>) -> Result<Self, KeyError> {
+        let secret_key = BASE64_STANDARD.decode(secret_key).map_err(|_| KeyError::InvalidBase64("SECRET_KEY"))?;
+        let public_key = BASE64_STANDARD.decode(public_key).map_err(|_| KeyError::InvalidBase64("PUBLIC_KEY"))?;
+
+        let kid = match kid {
+            Some(kid) => kid.to_owned(),
+            None if is_symmetric(algorithm) => SECRET_KID.to_owned(),
+            None => public_kid(&public_key),
+        };
+        let public_keys = HashMap::from([(kid.clone(), verification_key(&kid, algorithm, &public_key)?)]);
+
+        Self { secret_key: signing_key(&kid, algorithm, &secret_key)?, kid, public_keys }.checked(algorithm)
+    }
+
+    /// Loads the key ring from a directory. `<kid>.pub.{pem,der,jwk}` files are the verification keys,
+    /// `<kid>.{pem,der,jwk}` files are signing keys, of which only the active one is used. HMAC secrets
+    /// both sign and verify, so for those every `<kid>.{der,jwk}` file is a verification key as well.
+    ///
+    /// To rotate, add the public key of the next signing key first, e.g. with `rotate-keys`, so that
+    /// every instance can verify its tokens, then switch `JWT_ACTIVE_KID` to it and remove the old
+    /// public key once the tokens signed with it have expired.
+    pub fn from_dir(algorithm: Algorithm, dir: &Path, active_kid: Option<&str>) -> Result<Self, KeyError> {
+        let symmetric = is_symmetric(algorithm);
+        let io = |path: &Path| {
+            let path = path.display().to_string();
+            move |source| KeyError::Io { path, source }
+        };
+
+        let mut public_keys = HashMap::new();
+        let mut secret_keys = HashMap::new();
+        for entry in fs::read_dir(dir).map_err(io(dir))? {
+            let path = entry.map_err(io(dir))?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+            let Some(stem) = KEY_EXTENSIONS.iter().find_map(|extension| name.strip_suffix(extension)) else { continue };
+
+            match stem.strip_suffix(".pub") {
+                Some(kid) if symmetric => return Err(KeyError::InvalidKey {
+                    kid: kid.into(),
+                    reason: "HMAC secrets have no verification key, remove the .pub file".into(),
+                }),
+                Some(kid) => {
+                    let key = fs::read(&path).map_err(io(&path))?;
+                    public_keys.insert(kid.to_owned(), verification_key(kid, algorithm, &key)?);
+                },
+                None => {
+                    if symmetric {
+                        let key = fs::read(&path).map_err(io(&path))?;
+                        public_keys.insert(stem.to_owned(), verification_key(stem, algorithm, &key)?);
+                    }
+                    secret_keys.insert(stem.to_owned(), path.clone());
+                },
+            }
+        }
+
+        let kid = match active_kid {
+            Some(kid) => kid.to_owned(),
+            None if secret_keys.len() == 1 => secret_keys.keys().next().cloned().unwrap_or_default(),
+            None => return Err(KeyError::NoActiveKey(secret_keys.len())),
+        };
+        let path = secret_keys.get(&kid).ok_or_else(|| KeyError::MissingSigningKey(kid.clone()))?;
+        if !public_keys.contains_key(&kid) {
+            return Err(KeyError::MissingVerificationKey(kid));
+        }
+        let secret_key = signing_key(&kid, algorithm, &fs::read(path).map_err(io(path))?)?;
+
+        Self { kid, secret_key, public_keys }.checked(algorithm)
+    }
+
+    /// Signs a message with the active key and verifies it with its public key, which catches
+    /// keys of a pair that don't belong together.
+    fn checked(self, algorithm: Algorithm) -> Result<Self, KeyError> {
+        let message = b"key check";
+        let verified = crypto::sign(message, &self.secret_key, algorithm)
+            .and_then(|signature| crypto::verify(&signature, message, &self.public_keys[&self.kid].key, algorithm))
+            .map_err(|error| KeyError::InvalidKey { kid: self.kid.clone(), reason: error.to_string() })?;
+
+        match verified {
+            true => Ok(self),
+            false => Err(KeyError::PairMismatch(self.kid)),
+        }
+    }
+}
+
+/// Generates a signing key pair into `dir` as `<kid>.pem` and `<kid>.pub.pem`, PKCS#8 and SPKI encoded.
+/// RSA keys have `RSA_KEY_BITS` bits. The id is derived from the public key like in `KeyRing::from_env`.
+/// HMAC secrets are as long as their hash output and written as `<kid>.jwk` with a random id.
+/// Returns the id of the new key.
+pub fn generate(algorithm: Algorithm, dir: &Path) -> Result<String, KeyError> {
+    let rng = SystemRandom::new();
+
+    let (private_key, public_key) = match KeyKind::expected(algorithm) {
+        KeyKind::Secret => {
+            let length = match algorithm {
+                Algorithm::HS384 => 48,
+                Algorithm::HS512 => 64,
+                _ => 32,
+            };
+            let (mut secret, mut id) = (vec![0u8; length], [0u8; 12]);
+            rng.fill(&mut secret).and_then(|_| rng.fill(&mut id)).map_err(|_| KeyError::Generation)?;
+
+            let kid = BASE64_URL_SAFE_NO_PAD.encode(id);
+            let jwk = serde_json::json!({ "kty": "oct", "kid": kid, "k": BASE64_URL_SAFE_NO_PAD.encode(secret) });
+            write_new(&dir.join(format!("{kid}.jwk")), &jwk.to_string(), 0o600)?;
+            return Ok(kid);
+        },
+        KeyKind::Rsa => {
+            let key = RsaPrivateKey::new(&mut OsRng, RSA_KEY_BITS).map_err(|_| KeyError::Generation)?;
+            let private_key = key.to_pkcs8_der().map_err(|_| KeyError::Generation)?;
+            let public_key = key.to_public_key().to_public_key_der().map_err(|_| KeyError::Generation)?;
+            (private_key.as_bytes().to_vec(), public_key.into_vec())
+        },
+        KeyKind::Ec(curve) => {
+            let (signing, curve) = match curve {
+                EllipticCurve::P256 => (&ECDSA_P256_SHA256_FIXED_SIGNING, P256_OID),
+                _ => (&ECDSA_P384_SHA384_FIXED_SIGNING, P384_OID),
+            };
+            let document = EcdsaKeyPair::generate_pkcs8(signing, &rng).map_err(|_| KeyError::Generation)?;
+            let pair = EcdsaKeyPair::from_pkcs8(signing, document.as_ref(), &rng).map_err(|_| KeyError::Generation)?;
+            let algorithm = [der(0x06, EC_OID.as_bytes()), der(0x06, curve.as_bytes())].concat();
+            (document.as_ref().to_vec(), spki(&algorithm, pair.public_key().as_ref()))
+        },
+        KeyKind::Ed25519 => {
+            let document = Ed25519KeyPair::generate_pkcs8(&rng).map_err(|_| KeyError::Generation)?;
+            let pair = Ed25519KeyPair::from_pkcs8(document.as_ref()).map_err(|_| KeyError::Generation)?;
+            (document.as_ref().to_vec(), spki(&der(0x06, ED25519_OID.as_bytes()), pair.public_key().as_ref()))
+        },
+        _ => return Err(KeyError::UnsupportedGeneration(algorithm)),
+    };
+
+    let kid = public_kid(&public_key);
+    write_new(&dir.join(format!("{kid}.pub.pem")), &pem::encode(&pem::Pem::new("PUBLIC KEY", public_key)), 0o644)?;
+    write_new(&dir.join(format!("{kid}.pem")), &pem::encode(&pem::Pem::new("PRIVATE KEY", private_key)), 0o600)?;
+
+    Ok(kid)
+}
+
+fn public_kid(public_key: &[u8]) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(&Sha256::digest(public_key)[..12])
+}
+
+/// Deletes the signing and verification keys of `kid` from `dir`. Returns how many files were removed.
+pub fn remove(dir: &Path, kid: &str) -> Result<usize, KeyError> {
+    let mut removed = 0;
+
+    for extension in KEY_EXTENSIONS {
+        for name in [format!("{kid}{extension}"), format!("{kid}.pub{extension}")] {
+            let path = dir.join(name);
+            match fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+                Err(source) => return Err(KeyError::Io { path: path.display().to_string(), source }),
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+fn write_new(path: &Path, contents: &str, mode: u32) -> Result<(), KeyError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|source| KeyError::Io { path: path.display().to_string(), source })
+}
@@ -1,2 +1,8 @@
 pub mod hash;
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod cache;
+pub mod permission;
+pub mod password;
+pub mod cipher;
+pub mod keys;
+pub mod oidc;
\ No newline at end of file
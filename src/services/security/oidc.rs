@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use base64::prelude::*;
+use chrono::Utc;
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+use url::{form_urlencoded::byte_serialize, Url};
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::{OidcConfig, OidcProviderConfig};
+
+use super::hash::generate_token;
+
+const METADATA_TTL: Duration = Duration::from_secs(3600);
+const JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const ID_TOKEN_LEEWAY_SECONDS: u64 = 60;
+
+
+/// The part of the provider's discovery document the login flow needs.
+#[derive(Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
+    #[serde(default)]
+    token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    aud: serde_json::Value,
+    azp: Option<String>,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    email_verified: bool,
+    preferred_username: Option<String>,
+}
+
+/// Some providers send `email_verified` as a string.
+fn lenient_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Str(String),
+    }
+
+    Ok(match Flag::deserialize(deserializer)? {
+        Flag::Bool(flag) => flag,
+        Flag::Str(flag) => flag.eq_ignore_ascii_case("true"),
+    })
+}
+
+/// State of a login between the redirect to the provider and its callback.
+#[derive(Serialize, Deserialize)]
+pub struct OidcLogin {
+    pub provider: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub exp: i64,
+}
+
+/// Identity of the end user, taken from a validated ID token.
+#[derive(Debug)]
+pub struct OidcIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+struct Cached<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+struct Provider {
+    config: OidcProviderConfig,
+    metadata: RwLock<Option<Cached<ProviderMetadata>>>,
+    jwks: RwLock<Option<Cached<JwkSet>>>,
+}
+
+fn provider_error(provider: &str, error: impl std::fmt::Display) -> AppError {
+    warn!("OIDC provider {provider} failed: {error}");
+
+    AppError::ServiceUnavailableError(AppErrorMessage {
+        message: "Identity provider is unavailable".into(),
+        details: json!({ "provider": provider }).into()
+    })
+}
+
+fn invalid_id_token(provider: &str, reason: impl std::fmt::Display) -> AppError {
+    warn!("Rejected ID token from {provider}: {reason}");
+
+    AppError::UnAuthorizedError(AppErrorMessage { message: "Invalid ID token".into(), details: None })
+}
+
+fn form_encode(value: &str) -> String {
+    byte_serialize(value.as_bytes()).collect()
+}
+
+/// OpenID Connect relying party for the configured providers. Discovery documents
+/// and key sets are cached; key sets are fetched again when a token names an unknown key.
+pub struct OidcClient {
+    http: reqwest::Client,
+    providers: HashMap<Box<str>, Provider>,
+    redirect_url: Box<str>,
+    login_expire_seconds: i64,
+}
+
+impl OidcClient {
+    fn new(config: &OidcConfig) -> Result<Self, anyhow::Error> {
+        let http = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let providers = config.providers
+            .iter()
+            .map(|(name, config)| {
+                let provider = Provider { config: config.clone(), metadata: RwLock::new(None), jwks: RwLock::new(None) };
+                (name.clone(), provider)
+            })
+            .collect();
+
+        Ok(Self {
+            http,
+            providers,
+            redirect_url: config.redirect_url.clone(),
+            login_expire_seconds: config.login_expire_seconds
+        })
+    }
+
+    fn provider(&self, name: &str) -> Result<&Provider, AppError> {
+        self.providers.get(name).ok_or_else(|| AppError::NotFoundError(AppErrorMessage {
+            message: "Unknown identity provider".into(),
+            details: json!({ "provider": name }).into()
+        }))
+    }
+
+    pub fn provider_config(&self, name: &str) -> Result<&OidcProviderConfig, AppError> {
+        Ok(&self.provider(name)?.config)
+    }
+
+    pub fn redirect_uri(&self, name: &str) -> String {
+        format!("{}/{}/callback", self.redirect_url, name)
+    }
+
+    /// Path the login state cookie is scoped to.
+    pub fn cookie_path(&self) -> String {
+        Url::parse(&self.redirect_url)
+            .map(|url| url.path().to_owned())
+            .unwrap_or_else(|_| "/".into())
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, name: &str, url: &str) -> Result<T, AppError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| provider_error(name, error))?
+            .json()
+            .await
+            .map_err(|error| provider_error(name, error))
+    }
+
+    async fn metadata(&self, name: &str) -> Result<ProviderMetadata, AppError> {
+        let provider = self.provider(name)?;
+
+        if let Some(cached) = provider.metadata.read().ok().as_deref().and_then(Option::as_ref) {
+            if cached.fetched_at.elapsed() < METADATA_TTL {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let issuer = provider.config.issuer.trim_end_matches('/');
+        let metadata: ProviderMetadata = self
+            .fetch(name, &format!("{issuer}/.well-known/openid-configuration"))
+            .await?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(provider_error(name, format!("discovery names issuer {}", metadata.issuer)));
+        }
+
+        if let Ok(mut cached) = provider.metadata.write() {
+            *cached = Some(Cached { value: metadata.clone(), fetched_at: Instant::now() });
+        }
+
+        Ok(metadata)
+    }
+
+    /// Key set of the provider. `refetch` asks for a fresh copy, which is limited to one fetch per interval.
+    async fn jwks(&self, name: &str, metadata: &ProviderMetadata, refetch: bool) -> Result<JwkSet, AppError> {
+        let provider = self.provider(name)?;
+
+        if let Some(cached) = provider.jwks.read().ok().as_deref().and_then(Option::as_ref) {
+            let age = cached.fetched_at.elapsed();
+            if age < METADATA_TTL && (!refetch || age < JWKS_REFETCH_INTERVAL) {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let jwks: JwkSet = self.fetch(name, &metadata.jwks_uri).await?;
+
+        if let Ok(mut cached) = provider.jwks.write() {
+            *cached = Some(Cached { value: jwks.clone(), fetched_at: Instant::now() });
+        }
+
+        Ok(jwks)
+    }
+
+    /// Starts a login: returns the authorization url to redirect to and the state to keep until the callback.
+    pub async fn authorize(&self, name: &str) -> Result<(String, OidcLogin), AppError> {
+        let config = self.provider_config(name)?;
+        let metadata = self.metadata(name).await?;
+        let login = OidcLogin {
+            provider: name.into(),
+            state: generate_token(),
+            nonce: generate_token(),
+            code_verifier: generate_token(),
+            exp: Utc::now().timestamp() + self.login_expire_seconds,
+        };
+        let challenge = BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(login.code_verifier.as_bytes()));
+
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", &config.client_id),
+                ("redirect_uri", &self.redirect_uri(name)),
+                ("scope", &config.scopes),
+                ("state", &login.state),
+                ("nonce", &login.nonce),
+                ("code_challenge", &challenge),
+                ("code_challenge_method", "S256"),
+            ]
+        ).map_err(|error| provider_error(name, error))?;
+
+        Ok((url.into(), login))
+    }
+
+    /// Redeems the authorization code and returns the identity from the validated ID token.
+    pub async fn authenticate(&self, name: &str, code: &str, login: &OidcLogin) -> Result<OidcIdentity, AppError> {
+        let config = self.provider_config(name)?;
+        let metadata = self.metadata(name).await?;
+        let redirect_uri = self.redirect_uri(name);
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &redirect_uri),
+            ("code_verifier", &login.code_verifier),
+        ];
+        let mut request = self.http.post(&metadata.token_endpoint);
+
+        match &config.client_secret {
+            Some(secret) if metadata.token_endpoint_auth_methods_supported.iter().any(|m| m == "client_secret_post")
+                && !metadata.token_endpoint_auth_methods_supported.iter().any(|m| m == "client_secret_basic") => {
+                form.push(("client_id", &config.client_id));
+                form.push(("client_secret", secret.expose()));
+            }
+            Some(secret) => {
+                request = request.basic_auth(form_encode(&config.client_id), Some(form_encode(secret.expose())));
+            }
+            None => form.push(("client_id", &config.client_id)),
+        }
+
+        let response = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|error| provider_error(name, error))?;
+
+        if response.status().is_client_error() {
+            warn!("OIDC provider {name} refused the code: {}", response.text().await.unwrap_or_default());
+            return Err(AppError::UnAuthorizedError(AppErrorMessage {
+                message: "Authorization code was rejected".into(),
+                details: None
+            }));
+        }
+
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(|error| provider_error(name, error))?
+            .json()
+            .await
+            .map_err(|error| provider_error(name, error))?;
+
+        self.validate_id_token(name, config, &metadata, &tokens.id_token, &login.nonce).await
+    }
+
+    async fn validate_id_token(
+        &self,
+        name: &str,
+        config: &OidcProviderConfig,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+        nonce: &str
+    ) -> Result<OidcIdentity, AppError> {
+        let header = decode_header(id_token).map_err(|error| invalid_id_token(name, error))?;
+
+        let allowed = match metadata.id_token_signing_alg_values_supported.is_empty() {
+            true => vec![Algorithm::RS256],
+            false => metadata.id_token_signing_alg_values_supported.iter().filter_map(|alg| alg.parse().ok()).collect(),
+        };
+        if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) || !allowed.contains(&header.alg) {
+            return Err(invalid_id_token(name, format!("algorithm {:?} is not allowed", header.alg)));
+        }
+
+        let mut jwks = self.jwks(name, metadata, false).await?;
+        let find = |jwks: &JwkSet| match &header.kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+        let jwk = match find(&jwks) {
+            Some(jwk) => jwk,
+            None => {
+                jwks = self.jwks(name, metadata, true).await?;
+                find(&jwks).ok_or_else(|| invalid_id_token(name, format!("no key {:?}", header.kid)))?
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk).map_err(|error| invalid_id_token(name, error))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = ID_TOKEN_LEEWAY_SECONDS;
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&*config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|error| invalid_id_token(name, error))?
+            .claims;
+
+        let audiences = claims.aud.as_array().map_or(1, Vec::len);
+        if (audiences > 1 || claims.azp.is_some()) && claims.azp.as_deref() != Some(&*config.client_id) {
+            return Err(invalid_id_token(name, "azp does not match the client"));
+        }
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid_id_token(name, "nonce does not match"));
+        }
+
+        Ok(OidcIdentity {
+            provider: name.into(),
+            subject: claims.sub,
+            email: claims.email.map(|email| email.trim().to_lowercase()),
+            email_verified: claims.email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+}
+
+
+
+pub fn get_oidc(config: &OidcConfig) -> Result<Arc<OidcClient>, anyhow::Error> {
+    Ok(Arc::new(OidcClient::new(config).map_err(|error| anyhow::anyhow!("Invalid OIDC configuration: {error}"))?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Mutex;
+
+    use axum::{extract::State, routing::{get, post}, Form, Json, Router};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::Value;
+
+    use super::*;
+
+    pub(crate) const PROVIDER: &str = "mock";
+    const CLIENT_ID: &str = "client";
+    const KID: &str = "mock-key";
+    const RSA_KEY: &[u8] = include_bytes!("testdata/rsa.pem");
+    const RSA_JWK: &str = include_str!("testdata/rsa.jwk");
+
+    #[derive(Default)]
+    struct ProviderState {
+        issuer: String,
+        id_token: Mutex<String>,
+        token_requests: Mutex<Vec<HashMap<String, String>>>,
+    }
+
+    /// Identity provider on a local port, serving discovery, its key set and a token
+    /// endpoint that answers every code with the ID token set by `issue`.
+    pub(crate) struct MockProvider {
+        state: Arc<ProviderState>,
+    }
+
+    impl MockProvider {
+        pub(crate) async fn start() -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let state = Arc::new(ProviderState { issuer, ..Default::default() });
+
+            let router = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/jwks", get(jwks))
+                .route("/token", post(token))
+                .with_state(state.clone());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            Self { state }
+        }
+
+        pub(crate) fn config(&self) -> OidcConfig {
+            let provider = OidcProviderConfig {
+                issuer: self.state.issuer.clone().into(),
+                client_id: CLIENT_ID.into(),
+                ..Default::default()
+            };
+
+            OidcConfig {
+                providers: HashMap::from([(PROVIDER.into(), provider)]),
+                redirect_url: "https://api.example.com/api/v1/auth/oidc".into(),
+                ..Default::default()
+            }
+        }
+
+        /// Claims of a valid ID token for the login with `nonce`.
+        pub(crate) fn claims(&self, nonce: &str) -> Value {
+            let now = Utc::now().timestamp();
+
+            json!({
+                "iss": self.state.issuer,
+                "sub": "subject-1",
+                "aud": CLIENT_ID,
+                "iat": now,
+                "exp": now + 300,
+                "nonce": nonce,
+                "email": "Alice@Example.com",
+                "email_verified": "true",
+                "preferred_username": "alice"
+            })
+        }
+
+        /// Signs `claims` with the provider's RSA key, or with a shared secret for the HMAC algorithms.
+        pub(crate) fn issue(&self, claims: &Value, algorithm: Algorithm) {
+            let key = match algorithm {
+                Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => EncodingKey::from_secret(CLIENT_ID.as_bytes()),
+                _ => EncodingKey::from_rsa_pem(RSA_KEY).unwrap(),
+            };
+            let header = Header { kid: Some(KID.into()), ..Header::new(algorithm) };
+
+            *self.state.id_token.lock().unwrap() = encode(&header, claims, &key).unwrap();
+        }
+
+        fn token_requests(&self) -> Vec<HashMap<String, String>> {
+            self.state.token_requests.lock().unwrap().clone()
+        }
+    }
+
+    async fn discovery(State(state): State<Arc<ProviderState>>) -> Json<Value> {
+        Json(json!({
+            "issuer": state.issuer,
+            "authorization_endpoint": format!("{}/authorize", state.issuer),
+            "token_endpoint": format!("{}/token", state.issuer),
+            "jwks_uri": format!("{}/jwks", state.issuer),
+            // Advertised to check HMAC tokens are refused anyway
+            "id_token_signing_alg_values_supported": ["RS256", "HS256"]
+        }))
+    }
+
+    async fn jwks() -> Json<Value> {
+        let private: Value = serde_json::from_str(RSA_JWK).unwrap();
+
+        Json(json!({
+            "keys": [{ "kty": "RSA", "kid": KID, "use": "sig", "alg": "RS256", "n": private["n"], "e": private["e"] }]
+        }))
+    }
+
+    async fn token(State(state): State<Arc<ProviderState>>, Form(form): Form<HashMap<String, String>>) -> Json<Value> {
+        state.token_requests.lock().unwrap().push(form);
+        let id_token = state.id_token.lock().unwrap().clone();
+
+        Json(json!({ "access_token": "access", "token_type": "Bearer", "id_token": id_token }))
+    }
+
+    /// Runs a login whose ID token carries the valid claims as changed by `change`.
+    async fn login(algorithm: Algorithm, change: impl FnOnce(&mut Value)) -> Result<OidcIdentity, AppError> {
+        let provider = MockProvider::start().await;
+        let client = OidcClient::new(&provider.config()).unwrap();
+        let (_, login) = client.authorize(PROVIDER).await.unwrap();
+
+        let mut claims = provider.claims(&login.nonce);
+        change(&mut claims);
+        provider.issue(&claims, algorithm);
+
+        client.authenticate(PROVIDER, "code", &login).await
+    }
+
+    fn is_rejected(result: Result<OidcIdentity, AppError>) -> bool {
+        matches!(result, Err(AppError::UnAuthorizedError(_)))
+    }
+
+    #[tokio::test]
+    async fn login_returns_the_identity_of_the_id_token() {
+        let provider = MockProvider::start().await;
+        let client = OidcClient::new(&provider.config()).unwrap();
+
+        let (url, login) = client.authorize(PROVIDER).await.unwrap();
+        let url = Url::parse(&url).unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert!(url.as_str().starts_with(&format!("{}/authorize?", provider.state.issuer)));
+        assert_eq!(params["state"], login.state);
+        assert_eq!(params["nonce"], login.nonce);
+        assert_eq!(params["redirect_uri"], "https://api.example.com/api/v1/auth/oidc/mock/callback");
+
+        provider.issue(&provider.claims(&login.nonce), Algorithm::RS256);
+        let identity = client.authenticate(PROVIDER, "code", &login).await.unwrap();
+        assert_eq!(identity.subject, "subject-1");
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.preferred_username.as_deref(), Some("alice"));
+
+        // The token request proves the PKCE verifier behind the challenge of the redirect
+        let request = &provider.token_requests()[0];
+        assert_eq!(request["grant_type"], "authorization_code");
+        assert_eq!(request["code"], "code");
+        assert_eq!(request["client_id"], CLIENT_ID);
+        assert_eq!(request["redirect_uri"], params["redirect_uri"]);
+        assert_eq!(BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(request["code_verifier"].as_bytes())), params["code_challenge"]);
+    }
+
+    #[tokio::test]
+    async fn id_tokens_for_another_login_are_rejected() {
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["nonce"] = json!("other")).await));
+        assert!(is_rejected(login(Algorithm::RS256, |claims| { claims.as_object_mut().unwrap().remove("nonce"); }).await));
+    }
+
+    #[tokio::test]
+    async fn id_tokens_for_another_client_are_rejected() {
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["aud"] = json!("other")).await));
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["aud"] = json!([CLIENT_ID, "other"])).await));
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["azp"] = json!("other")).await));
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["iss"] = json!("https://evil.example.com")).await));
+
+        let shared = login(Algorithm::RS256, |claims| {
+            claims["aud"] = json!([CLIENT_ID, "other"]);
+            claims["azp"] = json!(CLIENT_ID);
+        });
+        assert!(shared.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn hmac_signed_id_tokens_are_rejected() {
+        assert!(is_rejected(login(Algorithm::HS256, |_| {}).await));
+    }
+
+    #[tokio::test]
+    async fn expired_id_tokens_are_rejected() {
+        let expired = Utc::now().timestamp() - ID_TOKEN_LEEWAY_SECONDS as i64 - 60;
+        assert!(is_rejected(login(Algorithm::RS256, |claims| claims["exp"] = json!(expired)).await));
+    }
+}
@@ -0,0 +1,54 @@
+use serde_json::json;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::PasswordConfig;
+
+
+/// Frequently leaked passwords, compared case-insensitively.
+static COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+fn violation(rule: &str, message: String) -> serde_json::Value {
+    json!({ "rule": rule, "message": message })
+}
+
+/// Checks a new password against the policy. Every violated rule is listed
+/// in the `violations` details of the `UnprocessableEntityError`.
+pub fn check_password_policy(password: &str, login: &str, config: &PasswordConfig) -> Result<(), AppError> {
+    let mut violations = vec![];
+
+    if password.chars().count() < config.min_length {
+        violations.push(violation(
+            "min_length", 
+            format!("Password must be at least {} characters long", config.min_length)
+        ));
+    }
+    if config.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+        violations.push(violation("lowercase", "Password must contain a lowercase letter".into()));
+    }
+    if config.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        violations.push(violation("uppercase", "Password must contain an uppercase letter".into()));
+    }
+    if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        violations.push(violation("digit", "Password must contain a digit".into()));
+    }
+    if config.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+        violations.push(violation("symbol", "Password must contain a symbol".into()));
+    }
+    if password.eq_ignore_ascii_case(login) {
+        violations.push(violation("login", "Password must not be the same as the login".into()));
+    }
+    if COMMON_PASSWORDS.lines().any(|common| common.eq_ignore_ascii_case(password)) {
+        violations.push(violation("common", "Password is too common".into()));
+    }
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    Err(AppError::UnprocessableEntityError(
+        AppErrorMessage {
+            message: "Password does not meet the policy".into(),
+            details: json!({ "violations": violations }).into()
+        }
+    ))
+}
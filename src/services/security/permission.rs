@@ -0,0 +1,97 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+
+/// Permissions the API checks for. Roles get them through the `role_permission` table,
+/// whose `permission` rows are seeded with the same names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Permission {
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    #[serde(rename = "users:delete")]
+    UsersDelete,
+    #[serde(rename = "roles:read")]
+    RolesRead,
+    #[serde(rename = "roles:write")]
+    RolesWrite,
+    #[serde(rename = "audit:read")]
+    AuditRead,
+    #[serde(rename = "config:read")]
+    ConfigRead,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::UsersRead => "users:read",
+            Permission::UsersWrite => "users:write",
+            Permission::UsersDelete => "users:delete",
+            Permission::RolesRead => "roles:read",
+            Permission::RolesWrite => "roles:write",
+            Permission::AuditRead => "audit:read",
+            Permission::ConfigRead => "config:read",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "users:read" => Ok(Permission::UsersRead),
+            "users:write" => Ok(Permission::UsersWrite),
+            "users:delete" => Ok(Permission::UsersDelete),
+            "roles:read" => Ok(Permission::RolesRead),
+            "roles:write" => Ok(Permission::RolesWrite),
+            "audit:read" => Ok(Permission::AuditRead),
+            "config:read" => Ok(Permission::ConfigRead),
+            _ => Err(anyhow::anyhow!("Unknown permission: {value}")),
+        }
+    }
+}
+
+/// Marker types for `RequirePermission`, one per `Permission` variant.
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+pub struct UsersRead;
+pub struct UsersWrite;
+pub struct UsersDelete;
+pub struct RolesRead;
+pub struct RolesWrite;
+pub struct AuditRead;
+pub struct ConfigRead;
+
+impl RequiredPermission for UsersRead {
+    const PERMISSION: Permission = Permission::UsersRead;
+}
+
+impl RequiredPermission for UsersWrite {
+    const PERMISSION: Permission = Permission::UsersWrite;
+}
+
+impl RequiredPermission for UsersDelete {
+    const PERMISSION: Permission = Permission::UsersDelete;
+}
+
+impl RequiredPermission for RolesRead {
+    const PERMISSION: Permission = Permission::RolesRead;
+}
+
+impl RequiredPermission for RolesWrite {
+    const PERMISSION: Permission = Permission::RolesWrite;
+}
+
+impl RequiredPermission for AuditRead {
+    const PERMISSION: Permission = Permission::AuditRead;
+}
+
+impl RequiredPermission for ConfigRead {
+    const PERMISSION: Permission = Permission::ConfigRead;
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::common::structs::responses::session::ActiveSession;
+use crate::common::structs::responses::status::Status;
+use crate::database::repositories::refresh_token::{RefreshTokenRepository, Writer as RefreshTokenWriter};
+use crate::database::repositories::session::{Reader, SessionRepository, Writer};
+use crate::database::repositories::user::{Reader as UserReader, UserRepository, Writer as UserWriter};
+
+
+fn user_not_found() -> AppError {
+    AppError::NotFoundError(AppErrorMessage { message: "User not found".into(), details: None })
+}
+
+pub struct SessionService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>,
+    pub refresh_tokens: RefreshTokenWriter<'a, Conn>,
+    pub users: UserReader<'a, Conn>,
+    pub user_writer: UserWriter<'a, Conn>
+}
+
+impl<'a, Conn> SessionService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(
+        repository: Arc<SessionRepository<'a, Conn>>, 
+        refresh_tokens: Arc<RefreshTokenRepository<'a, Conn>>,
+        users: Arc<UserRepository<'a, Conn>>
+    ) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        let refresh_tokens = refresh_tokens.writer();
+        let user_writer = users.writer();
+        let users = users.reader();
+        Arc::new(Self { reader, writer, refresh_tokens, users, user_writer })
+    }
+
+    /// Active sessions of the user. `current` marks the session the request came from.
+    pub async fn get_many(&self, user_id: Uuid, current: Option<Uuid>) -> Result<Vec<ActiveSession>, AppError> {
+        let user = self.users.get(user_id).await?.ok_or_else(user_not_found)?;
+        let sessions = self.reader.get_active_for_user(user.id, user.token_version).await?;
+
+        Ok(
+            sessions
+                .into_iter()
+                .map(|session| ActiveSession {
+                    current: current == Some(session.id),
+                    id: session.id,
+                    device: session.device,
+                    ip: session.ip,
+                    created_at: session.created_at,
+                    last_used_at: session.last_used_at,
+                    expires_at: session.expires_at
+                })
+                .collect()
+        )
+    }
+
+    /// Revokes one active session of the user and every refresh token issued to it.
+    pub async fn revoke(&self, user_id: Uuid, id: Uuid) -> Result<Status, AppError> {
+        let user = self.users.get(user_id).await?.ok_or_else(user_not_found)?;
+        let session = self.reader
+            .get(id)
+            .await?
+            .filter(|session| session.user_id == user.id && session.is_active(user.token_version))
+            .ok_or_else(|| AppError::NotFoundError(AppErrorMessage { message: "Session not found".into(), details: None }))?;
+
+        self.refresh_tokens.revoke_family(session.id).await?;
+        self.writer.revoke(session.id).await?;
+
+        Ok(Status { status: true })
+    }
+
+    /// Logs the user out everywhere. Bumping `token_version` also stops the access tokens already issued.
+    pub async fn revoke_all(&self, user_id: Uuid) -> Result<Status, AppError> {
+        if !self.users.exists(user_id).await? {
+            return Err(user_not_found());
+        }
+
+        self.user_writer.bump_token_version(user_id).await?;
+        self.refresh_tokens.revoke_for_user(user_id).await?;
+        self.writer.revoke_for_user(user_id).await?;
+
+        Ok(Status { status: true })
+    }
+}
@@ -1,38 +1,98 @@
 #![allow(unused)]
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use argon2::PasswordHash;
-use sea_orm::ConnectionTrait;
+use sea_orm::{ConnectionTrait, DbErr, SqlErr};
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::common::structs::responses::status::Status;
+use crate::database::entity::role::ADMIN_ROLE_ID;
+use crate::database::entity::user::Model;
+use crate::database::repositories::role::{Reader as RoleReader, RoleRepository};
 use crate::database::repositories::user::{DeleteUser, NewUser, Reader, UpdateUser, UserRepository, Writer};
 use crate::common::error::{AppError, AppErrorMessage};
 use crate::common::structs::requests::user::{CreateUser, UpdateUser as UpdateUserRequest};
 use crate::common::structs::responses::user::{User, UserData};
+use crate::core::config::PasswordConfig;
 
+use super::role::{ensure_held, into_permissions};
 use super::security::hash::Argon2Hasher;
+use super::security::password::check_password_policy;
+use super::security::permission::Permission;
 
 pub struct UserService<'a, Conn>
 where Conn: ConnectionTrait + Send + Sync
 {
     pub reader: Reader<'a, Conn>,
-    pub writer: Writer<'a, Conn>
+    pub writer: Writer<'a, Conn>,
+    pub roles: RoleReader<'a, Conn>
 }
 
 impl<'a, Conn> UserService<'a, Conn> 
 where Conn: ConnectionTrait + Send + Sync
 {
-    pub fn new(repository: Arc<UserRepository<'a, Conn>>) -> Arc<Self> {
+    pub fn new(repository: Arc<UserRepository<'a, Conn>>, roles: Arc<RoleRepository<'a, Conn>>) -> Arc<Self> {
         let reader = repository.reader();
         let writer = repository.writer();
-        Arc::new(Self { reader, writer })
+        let roles = roles.reader();
+        Arc::new(Self { reader, writer, roles })
     }
 
-    pub async fn create(&self, mut data: CreateUser, hasher: &Argon2Hasher) -> Result<User, AppError> {
+    /// Builds the user response with the role name and its effective permissions.
+    pub async fn to_response(&self, model: Model) -> Result<User, AppError> {
+        let role = self.roles.get(model.role_id).await?.ok_or_else(|| {
+            AppError::InternalServerError(AppErrorMessage { message: "Role not found".into(), details: None })
+        })?;
+        let permissions = self.roles.get_permissions(role.id).await?;
 
+        Ok(User { 
+            id: model.id, 
+            login: model.login, 
+            email: model.email,
+            email_verified_at: model.email_verified_at,
+            role: role.name, 
+            permissions: into_permissions(permissions),
+            created_at: model.created_at 
+        })
+    }
+
+    /// Normalizes the email and makes sure no other user has it.
+    async fn check_email(&self, email: &str) -> Result<String, AppError> {
+        let email = email.trim().to_lowercase();
+
+        if email.len() > 255 || email.parse::<lettre::Address>().is_err() {
+            return Err(AppError::BadRequestError(AppErrorMessage { 
+                message: "Invalid email".into(), 
+                details: json!({ "email": email }).into()
+            }));
+        }
+
+        if self.reader.get_by_email(email.clone()).await?.is_some() {
+            return Err(AppError::ConflictError(AppErrorMessage { 
+                message: "Email already exists".into(), 
+                details: json!({ "email": email }).into()
+            }));
+        }
+
+        Ok(email)
+    }
+
+    pub async fn create(
+        &self, data: CreateUser, hasher: &Argon2Hasher, password_config: &PasswordConfig
+    ) -> Result<User, AppError> {
+        self.create_with_role(data, None, hasher, password_config).await
+    }
+
+    /// Creates a user with the given role instead of the default one. Not reachable through
+    /// the API, the CLI bootstraps admins with it.
+    pub async fn create_with_role(
+        &self, mut data: CreateUser, role_id: Option<Uuid>, hasher: &Argon2Hasher, password_config: &PasswordConfig
+    ) -> Result<User, AppError> {
+
+        check_password_policy(&data.password, &data.login, password_config)?;
         data.password = hasher.hash_password(&data.password)?.into_boxed_str();
         
         let exists = self.reader.get_by_login(data.login.to_string()).await?;
@@ -44,15 +104,15 @@ where Conn: ConnectionTrait + Send + Sync
             }));
         }
 
+        let email = match data.email {
+            Some(email) => Some(self.check_email(&email).await?),
+            None => None
+        };
+
         let model = self.writer
-            .create(NewUser { login: data.login.to_string(), password: data.password.to_string() }).await?;
+            .create(NewUser { login: data.login.to_string(), password: data.password.to_string(), email, role_id }).await?;
        
-        Ok(User { 
-            id: model.id, 
-            login: model.login, 
-            role: model.role, 
-            created_at: model.created_at 
-        })
+        self.to_response(model).await
         
     }
 
@@ -60,7 +120,7 @@ where Conn: ConnectionTrait + Send + Sync
         let user = self.reader.get(id).await?;
 
         if let Some(r) = user {
-            return Ok(User { id: r.id, login: r.login, role: r.role, created_at: r.created_at});
+            self.to_response(r).await
         } else {
             Err(AppError::NotFoundError(AppErrorMessage { message: "User not found".into(), details: None}))
         }
@@ -73,14 +133,31 @@ where Conn: ConnectionTrait + Send + Sync
         match count {
             Ok(total) => {
                 let models = self.reader.get_many(offset, limit).await?;
+                let roles: HashMap<Uuid, (String, Vec<Permission>)> = self.roles
+                    .get_many_with_permissions()
+                    .await?
+                    .into_iter()
+                    .map(|(role, permissions)| (role.id, (role.name, into_permissions(permissions))))
+                    .collect();
         
                 let users = models
                     .into_iter()
-                    .map(|model| User { id: model.id, login: model.login, role: model.role, created_at: model.created_at})
+                    .map(|model| {
+                        let (role, permissions) = roles.get(&model.role_id).cloned().unwrap_or_default();
+                        User { 
+                            id: model.id, 
+                            login: model.login, 
+                            email: model.email,
+                            email_verified_at: model.email_verified_at,
+                            role, 
+                            permissions, 
+                            created_at: model.created_at
+                        }
+                    })
                     .collect();
 
                 Ok(UserData {
-                    total: total,
+                    total,
                     data: users
                 })
             },
@@ -92,7 +169,42 @@ where Conn: ConnectionTrait + Send + Sync
         }
     }
 
-    pub async fn update(&self, id: Uuid, mut data: UpdateUserRequest, hasher: &Argon2Hasher) -> Result<User, AppError> {
+    /// Checks the password the user confirmed a sensitive change with.
+    pub async fn verify_current_password(
+        &self, id: Uuid, current_password: Option<&str>, hasher: &Argon2Hasher
+    ) -> Result<(), AppError> {
+        let current_password = current_password.ok_or_else(|| {
+            AppError::BadRequestError(AppErrorMessage { 
+                message: "Current password is required".into(), 
+                details: json!({ "field": "current_password" }).into()
+            })
+        })?;
+
+        let verified = self.reader
+            .get(id)
+            .await?
+            .is_some_and(|user| hasher.verify_password(&user.password, current_password));
+
+        if !verified {
+            return Err(AppError::BadRequestError(AppErrorMessage { 
+                message: "Invalid current password".into(), 
+                details: json!({ "field": "current_password" }).into()
+            }));
+        }
+
+        Ok(())
+    }
+
+    pub async fn update(
+        &self, id: Uuid, mut data: UpdateUserRequest, hasher: &Argon2Hasher, password_config: &PasswordConfig
+    ) -> Result<User, AppError> {
+        if data.role.is_some() {
+            return Err(AppError::BadRequestError(AppErrorMessage { 
+                message: "Roles are changed through PUT /api/v1/users/{id}/role".into(), 
+                details: json!({ "field": "role" }).into()
+            }));
+        }
+
 
         if let Some(login) = data.login.clone() {
             let exists = self.reader.get_by_login(login.to_string()).await?;
@@ -105,28 +217,102 @@ where Conn: ConnectionTrait + Send + Sync
             }
         }
         if let Some(pwd) = data.password.clone() {
+            let login = match data.login.clone() {
+                Some(login) => login,
+                None => self.reader.get(id).await?.map(|user| user.login).unwrap_or_default()
+            };
+            check_password_policy(&pwd, &login, password_config)?;
             data.password = Some(hasher.hash_password(&pwd)?);
         }
+        let email = match data.email {
+            Some(email) => Some(self.check_email(&email).await?),
+            None => None
+        };
+        let revoke_tokens = data.password.is_some();
 
         let model = self.writer.update(
             UpdateUser { 
                 id, 
                 login: data.login, 
                 password: data.password, 
-                role: data.role
+                email_verified_at: email.as_ref().map(|_| None),
+                email: email.map(Some),
+                role_id: None
             }
             )
             .await?;
 
-        Ok(User { 
-            id: model.id, 
-            login: model.login, 
-            role: model.role, 
-            created_at: model.created_at 
-        })
+        if revoke_tokens {
+            self.writer.bump_token_version(id).await?;
+        }
+
+        self.to_response(model).await
   
     }
 
+    /// Gives the user another role. The role may not grant permissions the actor lacks, and
+    /// the last admin can't be demoted. Returns the user before and after the change.
+    pub async fn change_role(
+        &self, id: Uuid, role_name: &str, actor_permissions: &[Permission]
+    ) -> Result<(User, User), AppError> {
+        let role = self.roles.get_by_name(role_name.to_string()).await?.ok_or_else(|| {
+            AppError::BadRequestError(AppErrorMessage { 
+                message: "Role not found".into(), 
+                details: json!({ "role": role_name }).into()
+            })
+        })?;
+
+        let permissions = into_permissions(self.roles.get_permissions(role.id).await?);
+        ensure_held(&permissions, actor_permissions, "Role grants permissions you do not have")?;
+
+        if role.id != ADMIN_ROLE_ID {
+            self.ensure_other_admin(id).await?;
+        }
+
+        let before = self.get(id).await?;
+
+        if before.role == role.name {
+            return Ok((before.clone(), before));
+        }
+
+        let model = self.writer.update(
+            UpdateUser { 
+                id, 
+                login: None, 
+                password: None, 
+                email: None,
+                email_verified_at: None,
+                role_id: Some(role.id)
+            }
+        ).await?;
+        self.writer.bump_token_version(id).await?;
+
+        Ok((before, self.to_response(model).await?))
+    }
+
+    /// Refuses to let the actor manage a user holding permissions the actor lacks, so a limited
+    /// role can't take over or remove an admin.
+    pub async fn ensure_outranks(&self, id: Uuid, actor_permissions: &[Permission]) -> Result<(), AppError> {
+        let target = self.get(id).await?;
+
+        ensure_held(&target.permissions, actor_permissions, "User has permissions you do not have")
+    }
+
+    /// Refuses to let the user stop being an admin when no other live admin is left.
+    /// Locks the admins' rows, so two admins can't demote each other at the same time.
+    async fn ensure_other_admin(&self, id: Uuid) -> Result<(), AppError> {
+        let admins = self.reader.lock_role_members(ADMIN_ROLE_ID).await?;
+
+        if admins.contains(&id) && admins.len() == 1 {
+            return Err(AppError::ConflictError(AppErrorMessage { 
+                message: "Cannot remove the last admin".into(), 
+                details: None
+            }));
+        }
+
+        Ok(())
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<Status, AppError> {
         let exists = self.reader.exists(id).await?;
 
@@ -139,9 +325,112 @@ where Conn: ConnectionTrait + Send + Sync
             ));
         }
 
+        self.ensure_other_admin(id).await?;
+
         let rows = self.writer.delete(DeleteUser { id }).await?;
 
         Ok(Status { status: rows > 0 })
     }
 
-}
\ No newline at end of file
+    /// Brings back a soft-deleted user, unless a live user took the login or the email meanwhile.
+    pub async fn restore(&self, id: Uuid) -> Result<User, AppError> {
+        let user = self.reader.get_deleted(id).await?.ok_or_else(|| {
+            AppError::NotFoundError(AppErrorMessage { message: "Deleted user not found".into(), details: None })
+        })?;
+
+        if self.reader.get_by_login(user.login.clone()).await?.is_some() {
+            return Err(AppError::ConflictError(AppErrorMessage { 
+                message: "Login already exists".into(), 
+                details: json!({ "login": user.login }).into()
+            }));
+        }
+
+        if let Some(email) = user.email.clone() {
+            if self.reader.get_by_email(email.clone()).await?.is_some() {
+                return Err(AppError::ConflictError(AppErrorMessage { 
+                    message: "Email already exists".into(), 
+                    details: json!({ "email": email }).into()
+                }));
+            }
+        }
+
+        // The checks above can race a concurrent create, so the unique indexes have the last word.
+        if let Err(error) = self.writer.restore(id).await {
+            return match error.downcast_ref::<DbErr>().and_then(DbErr::sql_err) {
+                Some(SqlErr::UniqueConstraintViolation(_)) => Err(AppError::ConflictError(AppErrorMessage {
+                    message: "Login or email already exists".into(),
+                    details: None
+                })),
+                _ => Err(error.into()),
+            };
+        }
+
+        self.get(id).await
+    }
+
+    /// Hard deletes users that were soft-deleted longer than `retention_seconds` ago.
+    pub async fn purge(&self, retention_seconds: i64) -> Result<u64, AppError> {
+        let deleted_before = chrono::Utc::now() - chrono::Duration::seconds(retention_seconds);
+
+        Ok(self.writer.purge(deleted_before).await?)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use super::*;
+    use crate::database::entity::role::USER_ROLE_ID;
+    use crate::database::entity::user;
+    use crate::database::repositories::base::Repository;
+
+    fn user(login: &str, email: Option<&str>, deleted: bool) -> user::Model {
+        user::Model {
+            id: Uuid::new_v4(),
+            login: login.into(),
+            password: String::new(),
+            email: email.map(Into::into),
+            email_verified_at: None,
+            role_id: USER_ROLE_ID,
+            token_version: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: deleted.then(Utc::now),
+        }
+    }
+
+    #[tokio::test]
+    async fn restore_conflicts_with_a_login_that_differs_only_in_case() {
+        let deleted = user("Bob", None, true);
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![deleted.clone()], vec![user("bob", None, false)]])
+            .into_connection();
+        let service = UserService::new(Arc::new(UserRepository::new(&connection)), Arc::new(RoleRepository::new(&connection)));
+
+        let result = service.restore(deleted.id).await;
+
+        assert!(matches!(result, Err(AppError::ConflictError(_))));
+        let log = format!("{:?}", connection.into_transaction_log());
+        assert!(log.contains(r#"LOWER(\"user\".\"login\") = $"#), "{log}");
+        assert!(log.contains(r#"String(Some("bob"))"#), "{log}");
+    }
+
+    #[tokio::test]
+    async fn restore_conflicts_with_an_email_that_differs_only_in_case() {
+        let deleted = user("bob", Some("Bob@Example.com"), true);
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![deleted.clone()], vec![], vec![user("robert", Some("bob@example.com"), false)]])
+            .into_connection();
+        let service = UserService::new(Arc::new(UserRepository::new(&connection)), Arc::new(RoleRepository::new(&connection)));
+
+        let result = service.restore(deleted.id).await;
+
+        assert!(matches!(result, Err(AppError::ConflictError(_))));
+        let log = format!("{:?}", connection.into_transaction_log());
+        assert!(log.contains(r#"LOWER(\"user\".\"email\") = $"#), "{log}");
+        assert!(log.contains(r#"String(Some("bob@example.com"))"#), "{log}");
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use sea_orm::ConnectionTrait;
+use serde_json::json;
+
+use crate::common::error::{AppError, AppErrorMessage};
+use crate::core::config::OidcProviderConfig;
+use crate::database::entity::user::Model as UserModel;
+use crate::database::repositories::user::{NewUser, Reader as UserReader, UserRepository, Writer as UserWriter};
+use crate::database::repositories::user_identity::{NewUserIdentity, Reader, UserIdentityRepository, Writer};
+
+use super::security::hash::{generate_token, Argon2Hasher};
+use super::security::oidc::OidcIdentity;
+
+const MAX_LOGIN_LENGTH: usize = 64;
+
+
+pub struct UserIdentityService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub reader: Reader<'a, Conn>,
+    pub writer: Writer<'a, Conn>,
+    pub users: UserReader<'a, Conn>,
+    pub user_writer: UserWriter<'a, Conn>
+}
+
+impl<'a, Conn> UserIdentityService<'a, Conn>
+where Conn: ConnectionTrait + Send + Sync
+{
+    pub fn new(repository: Arc<UserIdentityRepository<'a, Conn>>, users: Arc<UserRepository<'a, Conn>>) -> Arc<Self> {
+        let reader = repository.reader();
+        let writer = repository.writer();
+        let user_writer = users.writer();
+        let users = users.reader();
+        Arc::new(Self { reader, writer, users, user_writer })
+    }
+
+    /// Finds the user the identity belongs to. Unknown identities are linked to the user
+    /// with the same verified email or get a new user, when the provider allows it.
+    pub async fn resolve(
+        &self, identity: OidcIdentity, config: &OidcProviderConfig, hasher: &Argon2Hasher
+    ) -> Result<UserModel, AppError> {
+        if let Some(linked) = self.reader.get(&identity.provider, &identity.subject).await? {
+            return self.users.get(linked.user_id).await?.ok_or_else(|| AppError::NotFoundError(AppErrorMessage {
+                message: "User not found".into(),
+                details: None
+            }));
+        }
+
+        let verified_email = identity.email.clone().filter(|_| identity.email_verified);
+        let owner = match &verified_email {
+            Some(email) => self.users.get_by_email(email.clone()).await?,
+            None => None
+        };
+
+        let user = match owner {
+            Some(user) if config.link_by_email && user.email_verified_at.is_some() => user,
+            Some(_) => {
+                return Err(AppError::ConflictError(AppErrorMessage {
+                    message: "Email already belongs to another user".into(),
+                    details: json!({ "email": verified_email }).into()
+                }));
+            }
+            None if config.auto_provision => self.provision(&identity, verified_email, hasher).await?,
+            None => {
+                return Err(AppError::ForbiddenError(AppErrorMessage {
+                    message: "No user is linked to this identity".into(),
+                    details: json!({ "provider": identity.provider }).into()
+                }));
+            }
+        };
+
+        self.writer.create(
+            NewUserIdentity {
+                user_id: user.id,
+                provider: identity.provider,
+                subject: identity.subject,
+                email: identity.email
+            }
+        ).await?;
+
+        Ok(user)
+    }
+
+    /// Creates a user for the identity. It gets an unusable random password, so it
+    /// can only log in through the provider until a password is reset.
+    async fn provision(
+        &self, identity: &OidcIdentity, email: Option<String>, hasher: &Argon2Hasher
+    ) -> Result<UserModel, AppError> {
+        let login = self.free_login(identity).await?;
+        let password = hasher.hash_password(&generate_token())?;
+
+        let user = self.user_writer.create(NewUser { login, password, email: email.clone(), role_id: None }).await?;
+
+        if let Some(email) = email {
+            self.user_writer.verify_email(user.id, email).await?;
+            return Ok(self.users.get(user.id).await?.unwrap_or(user));
+        }
+
+        Ok(user)
+    }
+
+    /// Picks the first untaken login from the username the provider suggests, the
+    /// local part of the email and the provider's subject.
+    async fn free_login(&self, identity: &OidcIdentity) -> Result<String, AppError> {
+        let candidates = [
+            identity.preferred_username.clone(),
+            identity.email.as_deref().and_then(|email| email.split('@').next()).map(str::to_owned),
+            Some(format!("{}-{}", identity.provider, identity.subject)),
+        ];
+
+        for candidate in candidates.into_iter().flatten() {
+            let login: String = candidate.trim().chars().take(MAX_LOGIN_LENGTH).collect();
+
+            if !login.is_empty() && self.users.get_by_login(login.clone()).await?.is_none() {
+                return Ok(login);
+            }
+        }
+
+        Ok(format!("{}-{}", identity.provider, &generate_token()[..8]))
+    }
+}